@@ -0,0 +1,9 @@
+address 0x1 {
+module Coin {
+    struct Coin { value: u64 }
+
+    public fun zero(): Coin {
+        Coin{value:0}
+    }
+}
+}
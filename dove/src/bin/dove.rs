@@ -12,6 +12,7 @@ use dove::cmd::new::New;
 use dove::cmd::metadata::Metadata;
 use dove::cmd::fetch::Fetch;
 use dove::cmd::build::Build;
+use dove::cmd::fmt::Fmt;
 use dove::cmd::test::Test;
 use dove::cmd::run::Run;
 use dove::cmd::ct::CreateTransactionCmd;
@@ -44,6 +45,11 @@ enum Opt {
         #[structopt(flatten)]
         cmd: Build,
     },
+    #[structopt(about = "Format project sources")]
+    Fmt {
+        #[structopt(flatten)]
+        cmd: Fmt,
+    },
     #[structopt(about = "Remove the target directory")]
     Clean {
         #[structopt(flatten)]
@@ -77,6 +83,7 @@ fn main() {
         Opt::Metadata { cmd } => cmd.execute(),
         Opt::Fetch { cmd } => cmd.execute(),
         Opt::Build { cmd } => cmd.execute(),
+        Opt::Fmt { cmd } => cmd.execute(),
         Opt::Test { cmd } => cmd.execute(),
         Opt::Run { cmd } => cmd.execute(),
         Opt::Ct { cmd } => cmd.execute(),
@@ -0,0 +1,81 @@
+use std::fs;
+
+use anyhow::Error;
+use structopt::StructOpt;
+use lang::compiler::file::find_move_files;
+use lang::format::format;
+
+use crate::cmd::Cmd;
+use crate::context::Context;
+
+/// Format project sources.
+#[derive(StructOpt, Debug)]
+pub struct Fmt {
+    #[structopt(
+        help = "Do not write the files, exit with an error if any of them is not formatted.",
+        long = "check"
+    )]
+    check: bool,
+}
+
+impl Cmd for Fmt {
+    fn apply(self, ctx: Context) -> Result<(), Error> {
+        let dirs = ctx.paths_for(&[
+            &ctx.manifest.layout.script_dir,
+            &ctx.manifest.layout.module_dir,
+            &ctx.manifest.layout.tests_dir,
+        ]);
+
+        let mut unformatted = vec![];
+        for dir in dirs {
+            for path in find_move_files(dir)? {
+                let source = fs::read_to_string(&path)?;
+                let formatted = format(&source)
+                    .map_err(|err| anyhow!("could not format {}: {}", path, err))?;
+                if formatted == source {
+                    continue;
+                }
+                if self.check {
+                    unformatted.push(path);
+                } else {
+                    fs::write(&path, formatted)?;
+                }
+            }
+        }
+
+        if unformatted.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "{} file(s) are not formatted:\n{}",
+                unformatted.len(),
+                unformatted.join("\n")
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::context::get_context;
+
+    use super::*;
+
+    #[test]
+    fn check_lists_unformatted_files() {
+        let project_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("resources")
+            .join("unformatted_project");
+        let context = get_context(project_dir.clone()).unwrap();
+
+        let err = Fmt { check: true }.apply(context).unwrap_err();
+
+        let coin = project_dir.join("modules").join("coin.move");
+        assert_eq!(
+            err.to_string(),
+            format!("1 file(s) are not formatted:\n{}", coin.display())
+        );
+    }
+}
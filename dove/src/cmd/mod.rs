@@ -16,6 +16,8 @@ pub mod clean;
 pub mod ct;
 /// Dependencies fetcher.
 pub mod fetch;
+/// Source formatter.
+pub mod fmt;
 /// Project initializer.
 pub mod init;
 /// Project metadata.
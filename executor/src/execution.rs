@@ -182,7 +182,7 @@ pub fn execute_script(
     if !oracle_prices.is_empty() {
         // check if module exists, and fail with MISSING_DEPENDENCY if not
         if ds.get_module(&oracle_coins_module()).is_err() {
-            return Ok(StepExecutionResult::Error(
+            return Ok(StepExecutionResult::with_error(
                 "Cannot use `price:` comments: missing `0x1::Coins` module".to_string(),
             ));
         }
@@ -14,6 +14,8 @@ use lang::compiler::error::CompilerError;
 use libra::move_lang::errors::report_errors;
 use crate::format::format_step_result;
 
+pub const DRY_RUN_META: &str = "dry_run: true";
+
 pub struct Executor<'d, 'n, 'c> {
     dialect: &'d dyn Dialect,
     sender: ProvidedAccountAddress,
@@ -46,6 +48,25 @@ impl<'d, 'n, 'c> Executor<'d, 'n, 'c> {
         &self,
         script: MoveFile,
         args: Vec<String>,
+    ) -> Result<PipelineExecutionResult, Error> {
+        self.execute_script_with_meta(script, args, &[])
+    }
+
+    /// Executes every script of the file as if it had `dry_run: true` meta comment.
+    pub fn dry_run_script(
+        &self,
+        script: MoveFile,
+        args: Vec<String>,
+    ) -> Result<PipelineExecutionResult, Error> {
+        self.execute_script_with_meta(script, args, &[DRY_RUN_META.to_owned()])
+    }
+
+    /// Meta comments, like `signers: 0x1`, are applied to every script after its own ones.
+    pub fn execute_script_with_meta(
+        &self,
+        script: MoveFile,
+        args: Vec<String>,
+        meta: &[String],
     ) -> Result<PipelineExecutionResult, Error> {
         let script_args = parse_script_arguments(args)?;
 
@@ -53,7 +74,8 @@ impl<'d, 'n, 'c> Executor<'d, 'n, 'c> {
         sources.push(script);
         sources.extend(self.deps.clone());
 
-        let session = SessionBuilder::new(self.dialect, &self.sender).build(&sources, &[])?;
+        let mut session = SessionBuilder::new(self.dialect, &self.sender).build(&sources, &[])?;
+        session.apply_meta_comments(meta);
         session.execute(script_args, self.dialect.cost_table())
     }
 }
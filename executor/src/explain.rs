@@ -35,32 +35,35 @@ impl PipelineExecutionResult {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
 pub enum StepExecutionResult {
-    Error(String),
-    ExpectedError(String),
+    Error(ExecutionError),
+    ExpectedError(ExecutionError),
     Success(ExplainedTransactionEffects),
 }
 
 impl StepExecutionResult {
-    pub fn with_expected_error(s: String) -> StepExecutionResult {
-        StepExecutionResult::ExpectedError(format!("Expected error: {}", s))
+    pub fn with_expected_error<E: Into<ExecutionError>>(error: E) -> StepExecutionResult {
+        let mut error = error.into();
+        error.message = format!("Expected error: {}", error.message);
+        StepExecutionResult::ExpectedError(error)
     }
 
-    pub fn with_error(s: String) -> StepExecutionResult {
-        StepExecutionResult::Error(s)
+    pub fn with_error<E: Into<ExecutionError>>(error: E) -> StepExecutionResult {
+        StepExecutionResult::Error(error.into())
     }
 
     pub fn error(self) -> String {
         match self {
-            StepExecutionResult::Error(error) => error,
+            StepExecutionResult::Error(error) => error.message,
             _ => panic!(),
         }
     }
 
     pub fn expected_error(self) -> String {
         match self {
-            StepExecutionResult::ExpectedError(error) => error,
+            StepExecutionResult::ExpectedError(error) => error.message,
             _ => panic!(),
         }
     }
@@ -68,17 +71,42 @@ impl StepExecutionResult {
     pub fn effects(self) -> ExplainedTransactionEffects {
         match self {
             StepExecutionResult::Success(effects) => effects,
-            StepExecutionResult::Error(msg) | StepExecutionResult::ExpectedError(msg) => {
-                panic!("{}", msg)
+            StepExecutionResult::Error(error) | StepExecutionResult::ExpectedError(error) => {
+                panic!("{}", error.message)
             }
         }
     }
 }
 
-#[derive(Debug, Clone, serde::Serialize, Eq, PartialEq)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct ExecutionError {
+    pub message: String,
+    /// Set if the execution is aborted with `abort` or `assert`.
+    pub abort: Option<Abort>,
+}
+
+impl From<String> for ExecutionError {
+    fn from(message: String) -> Self {
+        ExecutionError {
+            message,
+            abort: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct Abort {
+    pub code: u64,
+    /// Module the execution is aborted in, `None` for the script.
+    pub module: Option<String>,
+    /// Name of the module constant with the abort code, like `ERR_NOT_FOUND`.
+    pub constant: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
 pub struct ResourceChange(pub String, pub Option<String>);
 
-#[derive(Debug, Clone, serde::Serialize, Eq, PartialEq)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
 pub struct AddressResourceChanges {
     pub address: String,
     pub changes: Vec<(String, ResourceChange)>,
@@ -93,10 +121,12 @@ impl AddressResourceChanges {
     }
 }
 
-#[derive(Debug, Default, Clone, serde::Serialize, Eq, PartialEq)]
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
 pub struct ExplainedTransactionEffects {
     events: Vec<ResourceChange>,
     resources: Vec<AddressResourceChanges>,
+    /// Reported along with the gas for every step.
+    #[serde(skip)]
     write_set_size: usize,
 }
 
@@ -313,7 +343,7 @@ pub fn explain_type_error(
     "Execution failed with type error when binding type arguments to type parameters".to_string()
 }
 
-pub fn explain_abort(vm_status: VMStatus, consts_map: &ConstsMap) -> String {
+pub fn explain_abort(vm_status: VMStatus, consts_map: &ConstsMap) -> ExecutionError {
     match vm_status {
         VMStatus::MoveAbort(AbortLocation::Module(id), error_code) => {
             let const_key = (
@@ -326,19 +356,32 @@ pub fn explain_abort(vm_status: VMStatus, consts_map: &ConstsMap) -> String {
                 Some(name) => format!("{}: {}", error_code, name),
                 None => format!("{}", error_code),
             };
-            return format!(
-                "Execution aborted with code {} in module {}::{}.",
-                error,
-                short_address(id.address()),
-                id.name()
-            );
+            let module = format!("{}::{}", short_address(id.address()), id.name());
+            ExecutionError {
+                message: format!(
+                    "Execution aborted with code {} in module {}.",
+                    error, module
+                ),
+                abort: Some(Abort {
+                    code: error_code,
+                    module: Some(module),
+                    constant: const_name.cloned(),
+                }),
+            }
         }
         VMStatus::MoveAbort(AbortLocation::Script, error_code) => {
             // TODO: map to source code location
-            return format!(
-                "Execution aborted with code {} in transaction script",
-                error_code
-            );
+            ExecutionError {
+                message: format!(
+                    "Execution aborted with code {} in transaction script",
+                    error_code
+                ),
+                abort: Some(Abort {
+                    code: error_code,
+                    module: None,
+                    constant: None,
+                }),
+            }
         }
         _ => unreachable!(),
     }
@@ -85,10 +85,10 @@ pub fn format_step_result(
     }
 
     match step_result {
-        StepExecutionResult::Error(error) => format_error(&mut out, error),
+        StepExecutionResult::Error(error) => format_error(&mut out, error.message),
         StepExecutionResult::ExpectedError(error) => {
             if verbose {
-                format_error(&mut out, error)
+                format_error(&mut out, error.message)
             }
         }
         StepExecutionResult::Success(effects) => {
@@ -38,6 +38,16 @@ impl ExecutionSession {
         &self.consts
     }
 
+    pub fn apply_meta_comments(&mut self, comments: &[String]) {
+        for unit in &mut self.units {
+            if let ExecutionUnit::Script((_, _, meta)) = unit {
+                for comment in comments {
+                    meta.apply_meta_comment(comment.to_owned());
+                }
+            }
+        }
+    }
+
     pub fn execute(
         self,
         script_args: Vec<Value>,
@@ -934,6 +934,43 @@ fn test_extract_error_name_if_prefixed_with_err() {
     );
 }
 
+#[test]
+fn test_serialize_abort_with_error_constant() {
+    let _pool = ConstPool::new();
+    let text = r"
+        script {
+            use 0x2::Record;
+
+            fun main(s: &signer) {
+                Record::destroy_record(s);
+            }
+        }
+    ";
+
+    let step = execute_script(
+        MoveFile::with_content(script_path(), text),
+        vec![stdlib_mod("signer.move"), modules_mod("record.move")],
+        "libra",
+        "0x3",
+        vec![],
+    )
+    .unwrap()
+    .last()
+    .unwrap();
+    assert_eq!(
+        serde_json::to_value(&step).unwrap(),
+        serde_json::json!({
+            "status": "error",
+            "message": "Execution aborted with code 101: ERR_RECORD_DOES_NOT_EXIST in module 0x2::Record.",
+            "abort": {
+                "code": 101,
+                "module": "0x2::Record",
+                "constant": "ERR_RECORD_DOES_NOT_EXIST",
+            },
+        })
+    );
+}
+
 #[test]
 fn test_dry_run_do_not_apply_writeset_changes() {
     let _pool = ConstPool::new();
@@ -981,6 +1018,44 @@ script {
     );
 }
 
+#[test]
+fn test_dry_run_script_applies_no_changes_of_any_step() {
+    let _pool = ConstPool::new();
+    let text = r"
+script {
+    use 0x2::Record;
+
+    fun step_1(s: &signer) {
+        Record::create_record(s, 10);
+    }
+}
+
+script {
+    use 0x2::Record;
+
+    fun step_2(s: &signer) {
+        Record::destroy_record(s);
+    }
+}
+    ";
+
+    let dialect = DialectName::from_str("libra").unwrap().get_dialect();
+    let sender = dialect.normalize_account_address("0x3").unwrap();
+    let executor = Executor::new(
+        dialect.as_ref(),
+        sender,
+        vec![stdlib_mod("signer.move"), modules_mod("record.move")],
+    );
+    let result = executor
+        .dry_run_script(MoveFile::with_content(script_path(), text), vec![])
+        .unwrap();
+    assert_eq!(result.step_results.len(), 2);
+    assert_eq!(
+        result.last().unwrap().error(),
+        "Execution aborted with code 101: ERR_RECORD_DOES_NOT_EXIST in module 0x2::Record."
+    );
+}
+
 #[test]
 fn test_set_balance_via_meta() {
     let _pool = ConstPool::new();
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 use libra::move_lang::shared::Address;
 use libra::move_core_types::account_address::AccountAddress;
+use crate::compiler::bech32::{bech32_into_libra, libra_into_bech32, HRP};
+use crate::compiler::dialects::DialectName;
+use crate::compiler::ss58::{libra_into_ss58, ss58_to_libra};
 
 #[derive(Debug, Clone)]
 pub struct ProvidedAccountAddress {
@@ -77,3 +80,74 @@ impl AddressMap {
             .collect()
     }
 }
+
+/// Hex, bech32 and ss58 forms of the same address literal.
+/// A form is missing if the address doesn't fit into it, e.g. 32-byte ss58 address into bech32.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressFormats {
+    pub hex: String,
+    pub bech32: Option<String>,
+    pub ss58: Option<String>,
+}
+
+impl AddressFormats {
+    pub fn of(literal: &str) -> Option<AddressFormats> {
+        let hex = if literal.starts_with("0x") {
+            literal.to_owned()
+        } else if literal.starts_with(HRP) {
+            bech32_into_libra(literal).ok()?
+        } else {
+            ss58_to_libra(literal).ok()?
+        };
+        let digits = &hex[2..];
+        if digits.is_empty() || !digits.chars().all(|ch| ch.is_ascii_hexdigit()) {
+            return None;
+        }
+        let digits = digits.trim_start_matches('0').to_uppercase();
+        let hex = format!("0x{}", if digits.is_empty() { "0" } else { &digits });
+        Some(AddressFormats {
+            bech32: libra_into_bech32(&hex).ok(),
+            ss58: libra_into_ss58(&hex).ok(),
+            hex,
+        })
+    }
+
+    /// Hex form and the own form of the dialect, named, `None` if the address doesn't fit into it.
+    pub fn of_dialect(&self, dialect: &DialectName) -> Vec<(&'static str, Option<&str>)> {
+        let mut formats = vec![("hex", Some(self.hex.as_str()))];
+        match dialect {
+            DialectName::Libra => {}
+            DialectName::DFinance => formats.push(("bech32", self.bech32.as_deref())),
+            DialectName::Polkadot => formats.push(("ss58", self.ss58.as_deref())),
+        }
+        formats
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AddressFormats;
+
+    #[test]
+    fn test_address_formats() {
+        let formats = AddressFormats::of("0x0001").unwrap();
+        assert_eq!(formats.hex, "0x1");
+        let bech32 = formats.bech32.clone().unwrap();
+        let ss58 = formats.ss58.clone().unwrap();
+        assert_eq!(AddressFormats::of(&bech32), Some(formats.clone()));
+        assert_eq!(AddressFormats::of(&ss58), Some(formats));
+
+        // 32-byte address is too long for bech32
+        let formats = AddressFormats::of(
+            "0x9C786090E2598AE884FF9D1F01D6A1A9BAF13A9E61F73633A8928F4D80BF7DFE",
+        )
+        .unwrap();
+        assert!(formats.bech32.is_none());
+        assert!(formats.ss58.is_some());
+
+        assert!(AddressFormats::of("0x").is_none());
+        assert!(AddressFormats::of("0xZZ").is_none());
+        assert!(AddressFormats::of("wallet1xxx").is_none());
+        assert!(AddressFormats::of("Signer").is_none());
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::{ensure, Result};
 use bech32::u5;
 use lazy_static::lazy_static;
 
-use regex::Regex;
+use regex::{Match, Regex};
 use crate::compiler::source_map::FileOffsetMap;
 
 pub static HRP: &str = "wallet";
@@ -26,8 +26,12 @@ pub fn libra_into_bech32(libra_address: &str) -> Result<String> {
         libra_address.starts_with("0x"),
         "Pass address with 0x prefix"
     );
-    ensure!(libra_address.len() == 42, "Address should be of length 42");
-    let data = hex::decode(&libra_address[2..])?;
+    let hex_address = &libra_address[2..];
+    ensure!(
+        hex_address.len() <= 40,
+        "Address must not be longer than 20 bytes"
+    );
+    let data = hex::decode(format!("{:0>40}", hex_address))?;
     let data = bech32::convert_bits(&data, 8, 5, true)?
         .into_iter()
         .map(u5::try_from_u8)
@@ -36,25 +40,81 @@ pub fn libra_into_bech32(libra_address: &str) -> Result<String> {
     Ok(bech32::encode(&HRP, data)?)
 }
 
+/// Bech32 addresses of the source with their libra equivalents.
+fn bech32_addresses(source: &str) -> Vec<(Match<'_>, String)> {
+    BECH32_REGEX
+        .captures_iter(source)
+        .filter_map(|mat| {
+            let item = mat.get(1).unwrap();
+            if item.as_str().starts_with("0x") {
+                // libra match, don't replace
+                return None;
+            }
+            bech32_into_libra(item.as_str())
+                .ok()
+                .map(|libra_address| (item, libra_address))
+        })
+        .collect()
+}
+
+/// Spans of the bech32 addresses, which are replaced by `replace_bech32_addresses`.
+pub fn find_bech32_addresses(source: &str) -> Vec<(usize, usize)> {
+    bech32_addresses(source)
+        .into_iter()
+        .map(|(item, _)| (item.start(), item.end()))
+        .collect()
+}
+
 pub fn replace_bech32_addresses(source: &str, file_source_map: &mut FileOffsetMap) -> String {
     let mut transformed_source = source.to_string();
 
-    for mat in BECH32_REGEX.captures_iter(source).into_iter() {
-        let item = mat.get(1).unwrap();
-
+    for (item, libra_address) in bech32_addresses(source) {
         let orig_address = item.as_str();
-        if orig_address.starts_with("0x") {
-            // libra match, don't replace
-            continue;
-        }
-        if let Ok(libra_address) = bech32_into_libra(orig_address) {
-            file_source_map.insert_address_layer(
-                item.end(),
-                orig_address.to_owned(),
-                libra_address.clone(),
-            );
-            transformed_source = transformed_source.replace(orig_address, &libra_address);
-        }
+        file_source_map.insert_address_layer(
+            item.end(),
+            orig_address.to_owned(),
+            libra_address.clone(),
+        );
+        transformed_source = transformed_source.replace(orig_address, &libra_address);
     }
     transformed_source
 }
+
+#[cfg(test)]
+mod test {
+    use super::{bech32_into_libra, find_bech32_addresses, libra_into_bech32};
+
+    #[test]
+    fn test_libra_into_bech32() {
+        let bech32_address = "wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh";
+        let libra_address = bech32_into_libra(bech32_address).unwrap();
+        assert_eq!(libra_into_bech32(&libra_address).unwrap(), bech32_address);
+
+        let short_address = libra_into_bech32("0x1").unwrap();
+        assert_eq!(bech32_into_libra(&short_address).unwrap(), "0x1");
+
+        assert!(libra_into_bech32("1").is_err());
+        assert!(libra_into_bech32(&format!("0x{}", "1".repeat(41))).is_err());
+    }
+
+    #[test]
+    fn test_find_bech32_addresses() {
+        let source = r"
+            address wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh {
+                module M {
+                    // wallet1 is not an address
+                    fun f(): address { 0x1 }
+                    fun g(): address { wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vx }
+                }
+            }
+        ";
+        let addresses: Vec<&str> = find_bech32_addresses(source)
+            .into_iter()
+            .map(|(start, end)| &source[start..end])
+            .collect();
+        assert_eq!(
+            addresses,
+            vec!["wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh"]
+        );
+    }
+}
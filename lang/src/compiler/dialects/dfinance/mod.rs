@@ -14,7 +14,10 @@ use libra::vm::{
     file_format_common::instruction_key,
 };
 use crate::compiler::address::ProvidedAccountAddress;
-use crate::compiler::bech32::{bech32_into_libra, HRP, replace_bech32_addresses};
+use crate::compiler::bech32::{
+    bech32_into_libra, find_bech32_addresses, libra_into_bech32, HRP, replace_bech32_addresses,
+};
+use libra::move_lang::shared::Address;
 
 #[derive(Default)]
 pub struct DFinanceDialect;
@@ -47,6 +50,15 @@ impl Dialect for DFinanceDialect {
     fn replace_addresses(&self, source_text: &str, source_map: &mut FileOffsetMap) -> String {
         replace_bech32_addresses(&source_text, source_map)
     }
+
+    fn find_addresses(&self, source_text: &str) -> Vec<(usize, usize)> {
+        find_bech32_addresses(source_text)
+    }
+
+    fn format_address(&self, address: &Address) -> String {
+        let hex_address = address.to_string();
+        libra_into_bech32(&format!("0x{:0>40}", &hex_address[2..])).unwrap_or(hex_address)
+    }
 }
 
 pub fn dfinance_cost_table() -> CostTable {
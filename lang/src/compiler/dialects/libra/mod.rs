@@ -7,6 +7,7 @@ use libra::move_core_types::gas_schedule::{CostTable};
 use crate::compiler::source_map::FileOffsetMap;
 use crate::compiler::dialects::libra::addresses::replace_libra_address;
 use crate::compiler::address::ProvidedAccountAddress;
+use libra::move_lang::shared::Address;
 use std::ops::Deref;
 
 #[derive(Default)]
@@ -37,4 +38,12 @@ impl Dialect for LibraDialect {
     fn replace_addresses(&self, source_text: &str, source_map: &mut FileOffsetMap) -> String {
         replace_libra_address(source_text, source_map)
     }
+
+    fn find_addresses(&self, _source_text: &str) -> Vec<(usize, usize)> {
+        vec![]
+    }
+
+    fn format_address(&self, address: &Address) -> String {
+        address.to_string()
+    }
 }
@@ -11,6 +11,7 @@ use crate::compiler::dialects::libra::LibraDialect;
 use crate::compiler::dialects::dfinance::DFinanceDialect;
 use crate::compiler::dialects::polkadot::PolkadotDialect;
 use crate::compiler::address::ProvidedAccountAddress;
+use ::libra::move_lang::shared::Address;
 
 pub trait Dialect {
     fn name(&self) -> &str;
@@ -20,6 +21,12 @@ pub trait Dialect {
     fn cost_table(&self) -> CostTable;
 
     fn replace_addresses(&self, source_text: &str, source_map: &mut FileOffsetMap) -> String;
+
+    /// Spans of the address literals specific to the dialect, which are replaced before parsing.
+    fn find_addresses(&self, source_text: &str) -> Vec<(usize, usize)>;
+
+    /// Formats the address the way it's written in the source code of this dialect.
+    fn format_address(&self, address: &Address) -> String;
 }
 
 #[derive(serde::Deserialize, Debug, Clone, PartialEq, Eq)]
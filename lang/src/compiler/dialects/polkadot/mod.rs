@@ -6,7 +6,10 @@ use anyhow::Result;
 use libra::move_core_types::gas_schedule::CostTable;
 use std::ops::Deref;
 use crate::compiler::address::ProvidedAccountAddress;
-use crate::compiler::ss58::{replace_ss58_addresses, ss58_to_libra};
+use crate::compiler::ss58::{
+    find_ss58_addresses, libra_into_ss58, replace_ss58_addresses, ss58_to_libra,
+};
+use libra::move_lang::shared::Address;
 
 #[derive(Default)]
 pub struct PolkadotDialect;
@@ -47,4 +50,13 @@ impl Dialect for PolkadotDialect {
     fn replace_addresses(&self, source_text: &str, source_map: &mut FileOffsetMap) -> String {
         replace_ss58_addresses(&source_text, source_map)
     }
+
+    fn find_addresses(&self, source_text: &str) -> Vec<(usize, usize)> {
+        find_ss58_addresses(source_text)
+    }
+
+    fn format_address(&self, address: &Address) -> String {
+        let hex_address = address.to_string();
+        libra_into_ss58(&hex_address).unwrap_or(hex_address)
+    }
 }
@@ -60,6 +60,17 @@ where
         ))
     }
 
+    pub fn offset(&self, position: Position) -> Option<ByteIndex> {
+        let line_start = self.line_start(LineIndex::from(position.line)).ok()?;
+        let column_len: usize = self.source.as_ref()[line_start.to_usize()..]
+            .chars()
+            .take_while(|ch| *ch != '\n')
+            .take(position.character as usize)
+            .map(char::len_utf8)
+            .sum();
+        Some(ByteIndex::from((line_start.to_usize() + column_len) as u32))
+    }
+
     fn line_start(&self, line_index: LineIndex) -> Result<ByteIndex, LineIndexOutOfBoundsError> {
         use std::cmp::Ordering;
 
@@ -1,11 +1,13 @@
-use rust_base58::base58::FromBase58;
+use rust_base58::base58::{FromBase58, ToBase58};
 use anyhow::{anyhow, ensure, Result};
 use lazy_static::lazy_static;
-use regex::Regex;
+use regex::{Match, Regex};
 use crate::compiler::source_map::FileOffsetMap;
 
 const SS58_PREFIX: &[u8] = b"SS58PRE";
 const PUB_KEY_LENGTH: usize = 32;
+/// Generic Substrate address type.
+const SS58_ADDRESS_TYPE: u8 = 42;
 
 lazy_static! {
     static ref SS58_REGEX: Regex = Regex::new(r#"[1-9A-HJ-NP-Za-km-z]{40,}"#,).unwrap();
@@ -37,27 +39,61 @@ pub fn ss58_to_libra(ss58: &str) -> Result<String> {
     Ok(format!("0x{}", hex::encode_upper(addr)))
 }
 
+pub fn libra_into_ss58(libra_address: &str) -> Result<String> {
+    ensure!(
+        libra_address.starts_with("0x"),
+        "Pass address with 0x prefix"
+    );
+    let hex_address = &libra_address[2..];
+    ensure!(
+        hex_address.len() <= PUB_KEY_LENGTH * 2,
+        format!("Address must not be longer than {} bytes", PUB_KEY_LENGTH)
+    );
+    let mut data = vec![SS58_ADDRESS_TYPE];
+    data.extend(hex::decode(format!("{:0>64}", hex_address))?);
+    let checksum = ss58hash(&data);
+    data.extend(&checksum.as_bytes()[0..2]);
+    Ok(data.to_base58())
+}
+
+/// SS58 addresses of the source with their libra equivalents.
+fn ss58_addresses(source: &str) -> Vec<(Match<'_>, String)> {
+    SS58_REGEX
+        .captures_iter(source)
+        .filter_map(|mat| {
+            let item = mat
+                .get(0)
+                .expect("can't extract match from SS58 regex capture");
+            if item.as_str().starts_with("0x") {
+                // libra match, don't replace
+                return None;
+            }
+            ss58_to_libra(item.as_str())
+                .ok()
+                .map(|libra_address| (item, libra_address))
+        })
+        .collect()
+}
+
+/// Spans of the SS58 addresses, which are replaced by `replace_ss58_addresses`.
+pub fn find_ss58_addresses(source: &str) -> Vec<(usize, usize)> {
+    ss58_addresses(source)
+        .into_iter()
+        .map(|(item, _)| (item.start(), item.end()))
+        .collect()
+}
+
 pub fn replace_ss58_addresses(source: &str, file_source_map: &mut FileOffsetMap) -> String {
     let mut transformed_source = source.to_string();
 
-    for mat in SS58_REGEX.captures_iter(source).into_iter() {
-        let item = mat
-            .get(0)
-            .expect("can't extract match from SS58 regex capture");
-
+    for (item, libra_address) in ss58_addresses(source) {
         let orig_address = item.as_str();
-        if orig_address.starts_with("0x") {
-            // libra match, don't replace
-            continue;
-        }
-        if let Ok(libra_address) = ss58_to_libra(orig_address) {
-            file_source_map.insert_address_layer(
-                item.end(),
-                orig_address.to_owned(),
-                libra_address.clone(),
-            );
-            transformed_source = transformed_source.replace(orig_address, &libra_address);
-        }
+        file_source_map.insert_address_layer(
+            item.end(),
+            orig_address.to_owned(),
+            libra_address.clone(),
+        );
+        transformed_source = transformed_source.replace(orig_address, &libra_address);
     }
     transformed_source
 }
@@ -65,7 +101,10 @@ pub fn replace_ss58_addresses(source: &str, file_source_map: &mut FileOffsetMap)
 #[cfg(test)]
 mod test {
     use crate::compiler::source_map::FileOffsetMap;
-    use super::{PUB_KEY_LENGTH, replace_ss58_addresses, ss58_to_libra, ss58hash};
+    use super::{
+        PUB_KEY_LENGTH, find_ss58_addresses, libra_into_ss58, replace_ss58_addresses,
+        ss58_to_libra, ss58hash,
+    };
 
     #[test]
     fn test_ss58_to_libra() {
@@ -83,6 +122,21 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_libra_into_ss58() {
+        let libra_address = "0x9C786090E2598AE884FF9D1F01D6A1A9BAF13A9E61F73633A8928F4D80BF7DFE";
+        let polka_address = libra_into_ss58(libra_address).unwrap();
+        assert_eq!(ss58_to_libra(&polka_address).unwrap(), libra_address);
+
+        let short_address = libra_into_ss58("0x1").unwrap();
+        assert_eq!(
+            ss58_to_libra(&short_address).unwrap(),
+            "0x0000000000000000000000000000000000000000000000000000000000000001"
+        );
+
+        assert!(libra_into_ss58("1").is_err());
+    }
+
     #[test]
     fn test_ss58hash() {
         let msg = b"hello, world!";
@@ -121,4 +175,23 @@ mod test {
             res
         );
     }
+
+    #[test]
+    fn test_find_ss58_addresses() {
+        let source = r"
+            script {
+                use 0x01::Event;
+                use 1exaAg2VJRQbyUBAeXcktChCAqjVP9TUxF3zo23R2T6EGdE::Math;
+                use 1exaAg2VJRQbyUBAeXcktChCAqjVP9TUxF3zo23R2T6EGdX::Wrong;
+            }
+        ";
+        let addresses: Vec<&str> = find_ss58_addresses(source)
+            .into_iter()
+            .map(|(start, end)| &source[start..end])
+            .collect();
+        assert_eq!(
+            addresses,
+            vec!["1exaAg2VJRQbyUBAeXcktChCAqjVP9TUxF3zo23R2T6EGdE"]
+        );
+    }
 }
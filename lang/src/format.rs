@@ -0,0 +1,366 @@
+//! Whitespace-only formatter of the Move source code.
+//! Blocks are re-indented and spacing around `:`, `,` and operators is normalized,
+//! while every token, including comments and address literals in any dialect, is kept as written.
+//! Line breaks are never added or removed, so the line `n` of the result is the line `n` of the source.
+//! Comments are tokens of their own, instead of being taken from the parser's `FileCommentMap`,
+//! so files with syntax errors are formatted too.
+
+use anyhow::Result;
+
+const INDENT: &str = "    ";
+
+const SENDER_PLACEHOLDER: &str = "{{sender}}";
+
+/// Longest first.
+const OPERATORS: &[&str] = &[
+    "<==>", "==>", "::", "==", "!=", "<=", ">=", "&&", "||", "..", "<<", ">>",
+];
+
+/// Operators, which are always surrounded with spaces.
+const BINARY_OPERATORS: &[&str] = &[
+    "=", "==", "!=", "<=", ">=", "+", "-", "/", "%", "&&", "||", "^", "==>", "<==>",
+];
+
+/// Keywords, after which `&` and `*` are borrow and dereference.
+const NON_OPERAND_KEYWORDS: &[&str] = &[
+    "return", "abort", "if", "else", "while", "loop", "let", "move", "copy", "mut", "in",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Word,
+    String,
+    Comment,
+    Punct,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    start: usize,
+    /// `)` of the `if` or `while` condition, `&` and `*` after it are borrow and dereference.
+    closes_condition: bool,
+}
+
+impl<'a> Token<'a> {
+    fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    fn is(&self, text: &str) -> bool {
+        self.kind == TokenKind::Punct && self.text == text
+    }
+
+    fn is_opening(&self) -> bool {
+        self.is("{") || self.is("(") || self.is("[")
+    }
+
+    fn is_closing(&self) -> bool {
+        self.is("}") || self.is(")") || self.is("]")
+    }
+
+    /// Whether the token could end an operand of the binary operator.
+    fn is_operand_end(&self) -> bool {
+        match self.kind {
+            TokenKind::Word => !NON_OPERAND_KEYWORDS.contains(&self.text),
+            TokenKind::String => true,
+            TokenKind::Comment => false,
+            TokenKind::Punct => (self.is(")") && !self.closes_condition) || self.is("]"),
+        }
+    }
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_'
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token<'_>>> {
+    let mut tokens = vec![];
+    let mut pos = 0;
+    while let Some(ch) = text[pos..].chars().next() {
+        let rest = &text[pos..];
+        let (kind, len) = if ch.is_whitespace() {
+            pos += ch.len_utf8();
+            continue;
+        } else if rest.starts_with("//") {
+            (TokenKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            match comment.find("*/") {
+                Some(end) => (TokenKind::Comment, end + 4),
+                None => bail!("Unterminated block comment at {}", pos),
+            }
+        } else if ch == '"' {
+            (
+                TokenKind::String,
+                string_len(rest).ok_or_else(|| anyhow!("Unterminated string at {}", pos))?,
+            )
+        } else if rest.starts_with(SENDER_PLACEHOLDER) {
+            (TokenKind::Word, SENDER_PLACEHOLDER.len())
+        } else if is_word_char(ch) {
+            let len = rest.find(|ch| !is_word_char(ch)).unwrap_or(rest.len());
+            (TokenKind::Word, len)
+        } else {
+            let len = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(*op))
+                .map_or(ch.len_utf8(), |op| op.len());
+            (TokenKind::Punct, len)
+        };
+        tokens.push(Token {
+            kind,
+            text: &rest[..len],
+            start: pos,
+            closes_condition: false,
+        });
+        pos += len;
+    }
+    Ok(tokens)
+}
+
+/// Length of the string literal at the start of `text`, including quotes.
+fn string_len(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (idx, ch) in text.char_indices().skip(1) {
+        match ch {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return Some(idx + 1),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/// Formats the source, returns an error if it cannot be split into tokens.
+pub fn format(text: &str) -> Result<String> {
+    let mut tokens = tokenize(text)?;
+    mark_conditions(&mut tokens);
+
+    let mut formatted = String::with_capacity(text.len());
+    // indents of the contents of the open brackets
+    let mut indents: Vec<usize> = vec![];
+    let mut line_indent = 0;
+    let mut last_code_token: Option<&Token> = None;
+    let mut prev_end = 0;
+    for (idx, token) in tokens.iter().enumerate() {
+        let gap = &text[prev_end..token.start];
+        let newlines = gap.matches('\n').count();
+        if newlines > 0 || idx == 0 {
+            for _ in 0..newlines {
+                formatted.push('\n');
+            }
+            line_indent = indent_of_line(&indents, last_code_token, token);
+            for _ in 0..line_indent {
+                formatted.push_str(INDENT);
+            }
+        } else {
+            let prev = &tokens[idx - 1];
+            let before_prev = if idx > 1 { tokens.get(idx - 2) } else { None };
+            if needs_space(before_prev, prev, token, !gap.is_empty()) {
+                formatted.push(' ');
+            }
+        }
+        formatted.push_str(token.text);
+
+        if token.is("{") && is_address_block(&tokens[..idx]) {
+            // modules of the address block are not indented, like in the standard library
+            indents.push(line_indent);
+        } else if token.is_opening() {
+            indents.push(line_indent + 1);
+        } else if token.is_closing() {
+            indents.pop();
+        }
+        if token.kind != TokenKind::Comment {
+            last_code_token = Some(token);
+        }
+        prev_end = token.end();
+    }
+    for _ in 0..text[prev_end..].matches('\n').count() {
+        formatted.push('\n');
+    }
+    Ok(formatted)
+}
+
+fn mark_conditions(tokens: &mut [Token]) {
+    // whether the open parentheses start a condition
+    let mut parens = vec![];
+    let mut last_code_token: Option<Token> = None;
+    for token in tokens.iter_mut() {
+        if token.is("(") {
+            parens.push(last_code_token.map_or(false, |last| {
+                last.kind == TokenKind::Word && (last.text == "if" || last.text == "while")
+            }));
+        } else if token.is(")") {
+            token.closes_condition = parens.pop().unwrap_or(false);
+        }
+        if token.kind != TokenKind::Comment {
+            last_code_token = Some(*token);
+        }
+    }
+}
+
+/// Whether the preceding tokens end with the `address 0x1` at the top level.
+fn is_address_block(preceding: &[Token]) -> bool {
+    let mut code_tokens = preceding
+        .iter()
+        .rev()
+        .filter(|token| token.kind != TokenKind::Comment);
+    let is_address = code_tokens
+        .next()
+        .map_or(false, |address| address.kind == TokenKind::Word);
+    let is_keyword = code_tokens.next().map_or(false, |keyword| {
+        keyword.kind == TokenKind::Word && keyword.text == "address"
+    });
+    is_address
+        && is_keyword
+        && code_tokens
+            .next()
+            .map_or(true, |before| before.is("}") || before.is(";"))
+}
+
+fn indent_of_line(indents: &[usize], last_code_token: Option<&Token>, first: &Token) -> usize {
+    let block_indent = indents.last().copied().unwrap_or(0);
+    if first.is_closing() {
+        return block_indent.saturating_sub(1);
+    }
+    let is_continuation = match last_code_token {
+        Some(last) => {
+            !(last.is(";")
+                || last.is(",")
+                || last.is_opening()
+                || last.is_closing()
+                || first.is("{"))
+        }
+        None => false,
+    };
+    if is_continuation {
+        block_indent + 1
+    } else {
+        block_indent
+    }
+}
+
+fn needs_space(before_prev: Option<&Token>, prev: &Token, next: &Token, had_space: bool) -> bool {
+    let is_unary = |token: &Token| {
+        token.is("!")
+            || ((token.is("&") || token.is("*"))
+                && !before_prev.map_or(false, |before| before.is_operand_end()))
+    };
+    let is_binary = |token: &Token, before: Option<&Token>| {
+        (token.kind == TokenKind::Punct && BINARY_OPERATORS.contains(&token.text))
+            || ((token.is("&") || token.is("*"))
+                && before.map_or(false, |before| before.is_operand_end()))
+    };
+
+    let space = if next.kind == TokenKind::Comment {
+        true
+    } else if [",", ";", ")", "]", ".", "::", ":"]
+        .iter()
+        .any(|punct| next.is(punct))
+        || ["(", "[", ".", "::"].iter().any(|punct| prev.is(punct))
+    {
+        false
+    } else if prev.is(",") || prev.is(";") || prev.is(":") {
+        true
+    } else if is_unary(prev) {
+        false
+    } else if is_binary(prev, before_prev) || is_binary(next, Some(prev)) || next.is("{") {
+        true
+    } else {
+        had_space
+    };
+    // tokens must not be glued into the different ones, like `&` and `&x`
+    space || !splits_into(prev, next)
+}
+
+/// Whether `prev` and `next` written without a space are still tokenized as `prev` and `next`.
+fn splits_into(prev: &Token, next: &Token) -> bool {
+    let glued = format!("{}{}", prev.text, next.text);
+    match tokenize(&glued) {
+        Ok(tokens) => tokens.len() == 2 && tokens[0].text == prev.text,
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format;
+
+    fn assert_formatted(source: &str, expected: &str) {
+        assert_eq!(format(source).unwrap(), expected);
+        assert_eq!(format(expected).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_reindent_blocks() {
+        assert_formatted(
+            "module M {\n  fun f(): address acquires T {\nlet a = 1;\n        if (a) {\n  abort 1\n      }\n }\n}\n",
+            "module M {\n    fun f(): address acquires T {\n        let a = 1;\n        if (a) {\n            abort 1\n        }\n    }\n}\n",
+        );
+    }
+
+    #[test]
+    fn test_normalize_spacing() {
+        assert_formatted(
+            "script {\n    fun main(a :u64,b:  &mut u64) {\n        *b=a+1 ;\n        let c = &a ;\n        assert(!( *b==2 &&a>=1), 1);\n    }\n}",
+            "script {\n    fun main(a: u64, b: &mut u64) {\n        *b = a + 1;\n        let c = &a;\n        assert(!(*b == 2 && a >= 1), 1);\n    }\n}",
+        );
+    }
+
+    #[test]
+    fn test_borrow_after_condition() {
+        assert_formatted(
+            "fun f() { let r = if (c) &a else &b; while (c) *x = (a) * b; }",
+            "fun f() { let r = if (c) &a else &b; while (c) *x = (a) * b; }",
+        );
+        assert_formatted(
+            "fun f() { let r = if (c) & a else & b; while (c) * x = (a)*b; }",
+            "fun f() { let r = if (c) &a else &b; while (c) *x = (a) * b; }",
+        );
+    }
+
+    #[test]
+    fn test_generics_keep_spacing() {
+        assert_formatted(
+            "fun f<T: copyable>(v: vector<vector<u8>>): bool { borrow_global<T>(0x1); a < b }",
+            "fun f<T: copyable>(v: vector<vector<u8>>): bool { borrow_global<T>(0x1); a < b }",
+        );
+    }
+
+    #[test]
+    fn test_comments_are_kept() {
+        assert_formatted(
+            "module M {\n// line comment\n  /* block\n       comment */\nfun f() {}   // trailing\n}",
+            "module M {\n    // line comment\n    /* block\n       comment */\n    fun f() {} // trailing\n}",
+        );
+    }
+
+    #[test]
+    fn test_address_literals_are_kept() {
+        assert_formatted(
+            "address wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh {\n  module M {}\n}\nscript {\nfun main() { let _ = 5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY; let _ = {{sender}}::M::f(); }\n}",
+            "address wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh {\nmodule M {}\n}\nscript {\n    fun main() { let _ = 5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY; let _ = {{sender}}::M::f(); }\n}",
+        );
+    }
+
+    #[test]
+    fn test_continuation_lines_are_indented() {
+        assert_formatted(
+            "spec fun f {\naborts_if a &&\nb;\n}",
+            "spec fun f {\n    aborts_if a &&\n        b;\n}",
+        );
+    }
+
+    #[test]
+    fn test_line_count_is_preserved() {
+        let source = "script {\n\n\n  fun main() {}   \n\n}\n\n";
+        let formatted = format(source).unwrap();
+        assert_eq!(formatted, "script {\n\n\n    fun main() {}\n\n}\n\n");
+    }
+
+    #[test]
+    fn test_unterminated_comment_is_an_error() {
+        assert!(format("module M { /* comment }").is_err());
+        assert!(format("script { fun main() { let _ = b\"text; } }").is_err());
+    }
+}
@@ -8,4 +8,5 @@ pub extern crate disassembler;
 pub mod builder;
 pub mod checker;
 pub mod compiler;
+pub mod format;
 pub mod meta_extractor;
@@ -78,7 +78,7 @@ fn make_script_meta(script: Script) -> Meta {
     }
 }
 
-fn extract_type_name(tp: Type) -> String {
+pub fn extract_type_name(tp: Type) -> String {
     match tp.value {
         Type_::Apply(name, types) => {
             let mut tp = match name.value {
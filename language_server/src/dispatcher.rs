@@ -1,14 +1,18 @@
+use std::collections::HashMap;
 use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 use anyhow::Result;
 use crossbeam_channel::Sender;
 use lsp_server::{ErrorCode, Message, Request, RequestId, Response};
+use lsp_types::Url;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use threadpool::ThreadPool;
 
 use crate::global_state::{GlobalState, GlobalStateSnapshot};
-use crate::main_loop::{on_task, LspError, ResponseEvent};
+use crate::main_loop::{on_task, uri_to_str, LspError, ResponseEvent};
 use crate::req;
 
 fn result_to_task<R>(id: RequestId, result: Result<R::Result>) -> ResponseEvent
@@ -34,11 +38,54 @@ where
     ResponseEvent::Respond(response)
 }
 
+/// Requests dispatched onto the thread pool, which are not responded yet.
+#[derive(Debug, Default)]
+pub struct PendingRequests {
+    requests: HashMap<RequestId, Arc<AtomicBool>>,
+}
+
+impl PendingRequests {
+    /// Returns the flag, which is set when the request is canceled.
+    fn add(&mut self, id: RequestId) -> Arc<AtomicBool> {
+        let canceled = Arc::new(AtomicBool::new(false));
+        self.requests.insert(id, Arc::clone(&canceled));
+        canceled
+    }
+
+    /// Returns `false` if the request is already responded.
+    pub fn cancel(&mut self, id: &RequestId) -> bool {
+        match self.requests.remove(id) {
+            Some(canceled) => {
+                canceled.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns `false` if the request is canceled, so the response must be dropped.
+    pub fn complete(&mut self, id: &RequestId) -> bool {
+        self.requests.remove(id).is_some()
+    }
+}
+
+/// Path of the document the request is about, if there is one.
+fn document_path(params: &serde_json::Value) -> Option<String> {
+    let uri = params
+        .pointer("/textDocument/uri")
+        .or_else(|| params.pointer("/uri"))
+        .or_else(|| params.pointer("/item/uri"))
+        .or_else(|| params.pointer("/arguments/0/uri"))?
+        .as_str()?;
+    uri_to_str(Url::parse(uri).ok()?).ok()
+}
+
 pub struct PoolDispatcher<'a> {
     // will be None after first matched on_* method
     req: Option<Request>,
     pool: &'a ThreadPool,
     global_state: &'a mut GlobalState,
+    pending_requests: &'a mut PendingRequests,
     msg_sender: &'a Sender<Message>,
     task_sender: &'a Sender<ResponseEvent>,
 }
@@ -48,6 +95,7 @@ impl<'a> PoolDispatcher<'a> {
         req: Request,
         pool: &'a ThreadPool,
         global_state: &'a mut GlobalState,
+        pending_requests: &'a mut PendingRequests,
         msg_sender: &'a Sender<Message>,
         task_sender: &'a Sender<ResponseEvent>,
     ) -> PoolDispatcher<'a> {
@@ -55,6 +103,7 @@ impl<'a> PoolDispatcher<'a> {
             req: Some(req),
             pool,
             global_state,
+            pending_requests,
             msg_sender,
             task_sender,
         }
@@ -96,6 +145,7 @@ impl<'a> PoolDispatcher<'a> {
         R::Params: DeserializeOwned + Send + 'static,
         R::Result: Serialize + 'static,
     {
+        let fpath = self.req.as_ref().and_then(|req| document_path(&req.params));
         let (id, params) = match self.parse::<R>() {
             Some(it) => it,
             None => {
@@ -104,9 +154,13 @@ impl<'a> PoolDispatcher<'a> {
         };
 
         self.pool.execute({
-            let state_snapshot = self.global_state.snapshot();
+            let state_snapshot = self.snapshot(fpath);
             let sender = self.task_sender.clone();
+            let canceled = self.pending_requests.add(id.clone());
             move || {
+                if canceled.load(Ordering::SeqCst) {
+                    return;
+                }
                 let result = f(state_snapshot, params);
                 let task = result_to_task::<R>(id, result);
                 sender.send(task).unwrap();
@@ -116,6 +170,50 @@ impl<'a> PoolDispatcher<'a> {
         Ok(self)
     }
 
+    /// Dispatches the request onto thread pool, the handler could notify the client
+    /// through the main loop while it's running.
+    pub fn on_with_events<R>(
+        &mut self,
+        f: fn(GlobalStateSnapshot, R::Params, &Sender<ResponseEvent>) -> Result<R::Result>,
+    ) -> Result<&mut Self>
+    where
+        R: req::Request + 'static,
+        R::Params: DeserializeOwned + Send + 'static,
+        R::Result: Serialize + 'static,
+    {
+        let fpath = self.req.as_ref().and_then(|req| document_path(&req.params));
+        let (id, params) = match self.parse::<R>() {
+            Some(it) => it,
+            None => {
+                return Ok(self);
+            }
+        };
+
+        self.pool.execute({
+            let state_snapshot = self.snapshot(fpath);
+            let sender = self.task_sender.clone();
+            let canceled = self.pending_requests.add(id.clone());
+            move || {
+                if canceled.load(Ordering::SeqCst) {
+                    return;
+                }
+                let result = f(state_snapshot, params, &sender);
+                let task = result_to_task::<R>(id, result);
+                sender.send(task).unwrap();
+            }
+        });
+
+        Ok(self)
+    }
+
+    /// Requests about a document are handled with the workspace folder the document belongs to.
+    fn snapshot(&self, fpath: Option<String>) -> GlobalStateSnapshot {
+        match fpath {
+            Some(fpath) => self.global_state.snapshot_for(&fpath),
+            None => self.global_state.snapshot(),
+        }
+    }
+
     pub fn finish(&mut self) {
         match self.req.take() {
             None => (),
@@ -1,82 +1,312 @@
 use crate::main_loop::FileSystemEvent;
 use crate::inner::config::Config;
 use crate::inner::analysis::Analysis;
-use crate::inner::db::RootDatabase;
+use crate::inner::db::{Revision, RootDatabase};
 use crate::inner::change::AnalysisChange;
-use lang::compiler::file;
+use crate::inner::symbol_index::{self, IndexedSymbol};
+use crate::inner::typing::FileTypes;
+use lang::compiler::file::{self, MoveFile};
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 pub struct GlobalStateSnapshot {
     pub config: Config,
     pub analysis: Analysis,
+    /// Analyses of all the workspace folders, for the requests not bound to a document.
+    pub workspaces: Vec<Analysis>,
 }
 
+impl GlobalStateSnapshot {
+    /// Symbols of every workspace folder fuzzy matching `query`, best matches first.
+    pub fn workspace_symbols(&self, query: &str, limit: usize) -> Vec<IndexedSymbol> {
+        let indexes: Vec<_> = self
+            .workspaces
+            .iter()
+            .map(|analysis| &analysis.db().symbol_index)
+            .collect();
+        symbol_index::query_indexes(&indexes, query, limit)
+    }
+
+    /// Analysis of the first workspace folder with the file.
+    pub fn analysis_with(&self, fpath: &str) -> &Analysis {
+        self.workspaces
+            .iter()
+            .find(|analysis| analysis.db().available_files.contains_key(fpath))
+            .unwrap_or(&self.analysis)
+    }
+}
+
+/// Every workspace folder has its own database, checked with the dialect and the dependencies
+/// of its settings and `Dove.toml`. The first one is used for the files out of any folder.
 #[derive(Debug)]
 pub struct GlobalState {
-    db: RootDatabase,
+    workspaces: Vec<Arc<RootDatabase>>,
+    /// Files to recheck after the changes applied since the last check.
+    affected_files: HashSet<String>,
+    /// Edits which could not be applied, to be reported to the user.
+    edit_errors: Vec<String>,
 }
 
 impl GlobalState {
     pub fn new(config: Config, initial_fs_events: Vec<FileSystemEvent>) -> GlobalState {
-        let mut global_state = GlobalState {
-            db: RootDatabase::new(config),
-        };
+        let mut global_state = GlobalState::with_workspaces(vec![config]);
         global_state.update_from_events(initial_fs_events);
         global_state
     }
 
+    pub fn with_workspaces(mut configs: Vec<Config>) -> GlobalState {
+        if configs.is_empty() {
+            configs.push(Config::default());
+        }
+        GlobalState {
+            workspaces: configs
+                .into_iter()
+                .map(|config| Arc::new(RootDatabase::new(config)))
+                .collect(),
+            affected_files: HashSet::new(),
+            edit_errors: vec![],
+        }
+    }
+
+    /// Config of the first workspace folder.
     pub fn config(&self) -> &Config {
-        &self.db.config
+        &self.workspaces[0].config
+    }
+
+    pub fn workspace_roots(&self) -> Vec<Option<PathBuf>> {
+        self.workspaces
+            .iter()
+            .map(|db| db.config.root_path.clone())
+            .collect()
     }
 
+    /// Analysis of the first workspace folder.
     pub fn analysis(&self) -> Analysis {
-        Analysis::new(self.db.clone())
+        Analysis::new(Arc::clone(&self.workspaces[0]))
+    }
+
+    /// Analysis of the workspace folder the file belongs to.
+    pub fn analysis_for(&self, fpath: &str) -> Analysis {
+        Analysis::new(Arc::clone(&self.workspaces[self.owner(fpath)]))
+    }
+
+    /// The innermost folder with the file, otherwise the first one which tracks it.
+    fn owner(&self, fpath: &str) -> usize {
+        let by_root = self
+            .workspaces
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, db)| {
+                let root = db.config.root_path.as_ref()?;
+                if Path::new(fpath).starts_with(root) {
+                    Some((root.components().count(), idx))
+                } else {
+                    None
+                }
+            })
+            .max()
+            .map(|(_, idx)| idx);
+        by_root
+            .or_else(|| {
+                self.workspaces.iter().position(|db| {
+                    db.available_files.contains_key(fpath) || db.is_dependency_file(fpath)
+                })
+            })
+            .unwrap_or(0)
+    }
+
+    /// Dependencies could be shared by the folders, their changes are applied to all of them.
+    fn workspaces_of(&self, fpath: &str) -> Vec<usize> {
+        let owner = self.owner(fpath);
+        (0..self.workspaces.len())
+            .filter(|idx| {
+                *idx == owner || self.workspaces[*idx].available_files.contains_key(fpath)
+            })
+            .collect()
     }
 
     pub fn update_from_events(&mut self, fs_events: Vec<FileSystemEvent>) {
-        let mut change = AnalysisChange::new();
+        let mut changes: HashMap<usize, AnalysisChange> = HashMap::new();
         for fs_event in fs_events {
             match fs_event {
                 FileSystemEvent::AddFile(file) => {
                     let (fpath, text) = file.into();
-                    change.add_file(fpath, text);
+                    for idx in self.workspaces_of(&fpath) {
+                        let change = changes.entry(idx).or_default();
+                        change.add_file(fpath.clone(), text.clone());
+                    }
                 }
                 FileSystemEvent::ChangeFile(file) => {
                     let (fpath, text) = file.into();
-                    change.update_file(fpath, text);
+                    for idx in self.workspaces_of(&fpath) {
+                        let change = changes.entry(idx).or_default();
+                        change.update_file(fpath.clone(), text.clone());
+                    }
+                }
+                FileSystemEvent::EditFile(fpath, edits) => {
+                    for idx in self.workspaces_of(&fpath) {
+                        let change = changes.entry(idx).or_default();
+                        change.edit_file(fpath.clone(), edits.clone());
+                    }
                 }
                 FileSystemEvent::RemoveFile(fpath) => {
-                    change.remove_file(fpath);
+                    for idx in self.workspaces_of(&fpath) {
+                        changes.entry(idx).or_default().remove_file(fpath.clone());
+                    }
                 }
             }
         }
-        log::info!("Applying change to the in-memory files db:\n{:#?}", &change);
-        self.apply_change(change);
+        for (idx, change) in changes {
+            log::info!("Applying change to the in-memory files db:\n{:#?}", &change);
+            self.apply_change_to(idx, change);
+        }
     }
 
+    /// Applies the change to the first workspace folder.
     pub fn apply_change(&mut self, change: AnalysisChange) {
-        self.db.apply_change(change);
+        self.apply_change_to(0, change);
+    }
+
+    fn apply_change_to(&mut self, workspace: usize, change: AnalysisChange) {
+        // snapshots still in use keep the previous version of the database
+        let db = Arc::make_mut(&mut self.workspaces[workspace]);
+        let affected_files = db.apply_change(change);
+        for error in db.take_edit_errors() {
+            // edits of the shared files are applied to every folder
+            if !self.edit_errors.contains(&error) {
+                self.edit_errors.push(error);
+            }
+        }
+        self.affected_files.extend(affected_files);
     }
 
+    pub fn take_affected_files(&mut self) -> HashSet<String> {
+        mem::take(&mut self.affected_files)
+    }
+
+    pub fn take_edit_errors(&mut self) -> Vec<String> {
+        mem::take(&mut self.edit_errors)
+    }
+
+    /// Files with the analysis of the workspace folder they are checked in.
+    pub fn group_by_workspace(&self, files: HashSet<String>) -> Vec<(Analysis, HashSet<String>)> {
+        let mut groups: HashMap<usize, HashSet<String>> = HashMap::new();
+        for fpath in files {
+            groups.entry(self.owner(&fpath)).or_default().insert(fpath);
+        }
+        groups
+            .into_iter()
+            .map(|(idx, files)| (Analysis::new(Arc::clone(&self.workspaces[idx])), files))
+            .collect()
+    }
+
+    /// Types inferred by the check of the database `revision`.
+    pub fn update_file_types(
+        &mut self,
+        revision: Revision,
+        file_types: Vec<(String, FileTypes)>,
+    ) {
+        for (fpath, mut types) in file_types {
+            types.revision = revision;
+            let owner = self.owner(&fpath);
+            let db = &mut self.workspaces[owner];
+            if db.available_files.contains_key(&fpath) {
+                Arc::make_mut(db).file_types.insert(fpath, Arc::new(types));
+            }
+        }
+    }
+
+    pub fn indexed_files_message(&self) -> String {
+        let files: usize = self
+            .workspaces
+            .iter()
+            .map(|db| db.available_files.len())
+            .sum();
+        let modules: usize = self
+            .workspaces
+            .iter()
+            .flat_map(|db| {
+                db.available_files
+                    .keys()
+                    .filter_map(move |fpath| db.file_symbols(fpath))
+            })
+            .map(|symbols| symbols.modules.len())
+            .sum();
+        format!("{} files, {} modules indexed", files, modules)
+    }
+
+    /// Snapshot of the first workspace folder.
     pub fn snapshot(&self) -> GlobalStateSnapshot {
         GlobalStateSnapshot {
             config: self.config().clone(),
             analysis: self.analysis(),
+            workspaces: self.analyses(),
         }
     }
+
+    /// Snapshot of the workspace folder the file belongs to.
+    pub fn snapshot_for(&self, fpath: &str) -> GlobalStateSnapshot {
+        let analysis = self.analysis_for(fpath);
+        GlobalStateSnapshot {
+            config: analysis.db().config.clone(),
+            analysis,
+            workspaces: self.analyses(),
+        }
+    }
+
+    fn analyses(&self) -> Vec<Analysis> {
+        self.workspaces
+            .iter()
+            .map(|db| Analysis::new(Arc::clone(db)))
+            .collect()
+    }
 }
 
+/// Files are parsed in batches, to report the progress in between.
+const INDEXING_BATCH_SIZE: usize = 16;
+
 pub fn initialize_new_global_state(config: Config) -> GlobalState {
-    let mut initial_fs_events = vec![];
-    match &config.stdlib_folder {
-        Some(folder) => {
-            for file in file::load_move_files(&[folder]).unwrap() {
-                initial_fs_events.push(FileSystemEvent::AddFile(file));
+    load_global_state(vec![config], |_, _| {})
+}
+
+/// Loads and parses the stdlib and the module files of every workspace folder,
+/// `on_progress` is called with the numbers of the parsed and of all the files.
+pub fn load_global_state(
+    configs: Vec<Config>,
+    mut on_progress: impl FnMut(usize, usize),
+) -> GlobalState {
+    let mut global_state = GlobalState::with_workspaces(configs);
+    let workspace_files: Vec<_> = global_state
+        .workspaces
+        .iter()
+        .map(|db| dependency_files(&db.config))
+        .collect();
+
+    let total = workspace_files.iter().map(Vec::len).sum();
+    let mut parsed = 0;
+    for (idx, files) in workspace_files.into_iter().enumerate() {
+        let mut files = files.into_iter().peekable();
+        while files.peek().is_some() {
+            let mut change = AnalysisChange::new();
+            for file in files.by_ref().take(INDEXING_BATCH_SIZE) {
+                let (fpath, text) = file.into();
+                change.add_file(fpath, text);
+                parsed += 1;
             }
+            global_state.apply_change_to(idx, change);
+            on_progress(parsed, total);
         }
-        None => {}
     }
-    for file in file::load_move_files(&config.modules_folders).unwrap() {
-        initial_fs_events.push(FileSystemEvent::AddFile(file));
+    global_state
+}
+
+fn dependency_files(config: &Config) -> Vec<MoveFile<'static, 'static>> {
+    let mut files = vec![];
+    if let Some(folder) = &config.stdlib_folder {
+        files.extend(file::load_move_files(&[folder]).unwrap());
     }
-    GlobalState::new(config, initial_fs_events)
+    files.extend(file::load_move_files(&config.modules_folders).unwrap());
+    files
 }
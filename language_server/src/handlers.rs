@@ -0,0 +1,831 @@
+//! Request handlers, executed on the thread pool with a snapshot of the global state.
+
+use anyhow::Result;
+use lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionResponse,
+    CodeLens, CodeLensParams, Command, CompletionItem, CompletionItemKind, CompletionParams,
+    CompletionResponse, DocumentFormattingParams, DocumentHighlight, DocumentHighlightKind,
+    DocumentHighlightParams, DocumentRangeFormattingParams, DocumentSymbol, DocumentSymbolParams,
+    DocumentSymbolResponse, ExecuteCommandParams, FoldingRange, FoldingRangeKind,
+    FoldingRangeParams, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents,
+    HoverParams, Location, MarkupContent, MarkupKind, MessageType, ParameterInformation,
+    ParameterLabel, PrepareRenameResponse, Range, ReferenceParams, RenameParams, SelectionRange,
+    SelectionRangeParams, SemanticTokens, SemanticTokensParams, SemanticTokensRangeParams,
+    SemanticTokensRangeResult, SemanticTokensResult, ShowMessageParams, SignatureHelp,
+    SignatureHelpParams, SignatureInformation, SymbolInformation, SymbolKind,
+    TextDocumentPositionParams, TextEdit, Url, WorkspaceEdit, WorkspaceSymbolParams,
+};
+use lsp_server::ErrorCode;
+use std::collections::HashMap;
+use std::iter;
+use crossbeam_channel::Sender;
+use libra::move_lang::name_pool::ConstPool;
+use move_executor::executor::DRY_RUN_META;
+use move_executor::explain::StepExecutionResult;
+use move_executor::format::format_step_result;
+
+use crate::global_state::GlobalStateSnapshot;
+use crate::inner::call_hierarchy::CallItem;
+use crate::inner::completion::CompletionKind;
+use crate::inner::code_actions::ActionDiagnostic;
+use crate::inner::db::RootDatabase;
+use crate::inner::decompiled;
+use crate::inner::document_highlights::HighlightAccess;
+use crate::inner::file_structure::{StructureNode, StructureNodeKind};
+use crate::inner::folding_ranges::FoldKind;
+use crate::inner::inlay_hints::InlayHintKind;
+use crate::inner::rename::{FileEdit, RenameError};
+use crate::inner::symbols::{ItemKind, Span};
+use crate::main_loop::{uri_to_str, LspError, ResponseEvent};
+use crate::req::{
+    DecompiledSourceParams, ExecuteScriptParams, ExecuteScriptResult, ExecutedStep, InlayHint,
+    InlayHintParams, RunScriptArgs, DRY_RUN_SCRIPT_COMMAND, INLAY_HINT_KIND_PARAMETER,
+    INLAY_HINT_KIND_TYPE, RUN_SCRIPT_COMMAND,
+};
+use crate::semantic_tokens::to_semantic_tokens;
+
+fn unknown_file(fpath: &str) -> anyhow::Error {
+    LspError::new(
+        LspError::UNKNOWN_FILE,
+        format!("File {:?} is not tracked by the server", fpath),
+    )
+    .into()
+}
+
+fn tracked_file(snapshot: &GlobalStateSnapshot, uri: Url) -> Result<String> {
+    let fpath = uri_to_str(uri)?;
+    if snapshot.analysis.db().available_files.contains_key(&fpath) {
+        Ok(fpath)
+    } else {
+        Err(unknown_file(&fpath))
+    }
+}
+
+fn file_position(
+    snapshot: &GlobalStateSnapshot,
+    params: TextDocumentPositionParams,
+) -> Result<(String, usize)> {
+    let fpath = uri_to_str(params.text_document.uri)?;
+    match snapshot.analysis.db().offset(&fpath, params.position) {
+        Some(offset) => Ok((fpath, offset)),
+        None => Err(unknown_file(&fpath)),
+    }
+}
+
+pub fn handle_goto_definition(
+    snapshot: GlobalStateSnapshot,
+    params: GotoDefinitionParams,
+) -> Result<Option<GotoDefinitionResponse>> {
+    let _pool = ConstPool::new();
+
+    let (fpath, offset) = file_position(&snapshot, params.text_document_position_params)?;
+    let target = match snapshot.analysis.goto_definition(&fpath, offset) {
+        Some(target) => target,
+        None => return Ok(None),
+    };
+    let location = location(snapshot.analysis.db(), &target.fpath, target.focus_span)?;
+    Ok(Some(GotoDefinitionResponse::Scalar(location)))
+}
+
+/// Location in the tracked file, or in the decompiled view of the `.mv` file.
+fn location(db: &RootDatabase, fpath: &str, span: Span) -> Result<Location> {
+    if decompiled::is_bytecode_file(fpath) {
+        decompiled::span_to_location(fpath, span)
+    } else {
+        db.span_to_location(fpath, span)
+    }
+}
+
+pub fn handle_hover(snapshot: GlobalStateSnapshot, params: HoverParams) -> Result<Option<Hover>> {
+    let _pool = ConstPool::new();
+
+    let (fpath, offset) = file_position(&snapshot, params.text_document_position_params)?;
+    let hover = match snapshot.analysis.hover(&fpath, offset) {
+        Some(hover) => hover,
+        None => return Ok(None),
+    };
+    let range = snapshot.analysis.db().span_to_range(&fpath, hover.span)?;
+    Ok(Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: hover.markup,
+        }),
+        range: Some(range),
+    }))
+}
+
+pub fn handle_completion(
+    snapshot: GlobalStateSnapshot,
+    params: CompletionParams,
+) -> Result<Option<CompletionResponse>> {
+    let _pool = ConstPool::new();
+
+    let (fpath, offset) = file_position(&snapshot, params.text_document_position)?;
+    let items = snapshot
+        .analysis
+        .completions(&fpath, offset)
+        .into_iter()
+        .map(|item| CompletionItem {
+            label: item.label,
+            kind: Some(completion_item_kind(item.kind)),
+            detail: item.detail,
+            ..CompletionItem::default()
+        })
+        .collect();
+    Ok(Some(CompletionResponse::Array(items)))
+}
+
+fn completion_item_kind(kind: CompletionKind) -> CompletionItemKind {
+    match kind {
+        CompletionKind::Module => CompletionItemKind::Module,
+        CompletionKind::Struct => CompletionItemKind::Struct,
+        CompletionKind::Function | CompletionKind::BuiltinFunction => {
+            CompletionItemKind::Function
+        }
+        CompletionKind::Constant => CompletionItemKind::Constant,
+        CompletionKind::Field => CompletionItemKind::Field,
+        CompletionKind::Local => CompletionItemKind::Variable,
+        CompletionKind::Keyword => CompletionItemKind::Keyword,
+        CompletionKind::BuiltinType => CompletionItemKind::TypeParameter,
+    }
+}
+
+pub fn handle_signature_help(
+    snapshot: GlobalStateSnapshot,
+    params: SignatureHelpParams,
+) -> Result<Option<SignatureHelp>> {
+    let _pool = ConstPool::new();
+
+    let (fpath, offset) = file_position(&snapshot, params.text_document_position_params)?;
+    let signature = match snapshot.analysis.signature_help(&fpath, offset) {
+        Some(signature) => signature,
+        None => return Ok(None),
+    };
+    let parameters = signature
+        .parameters
+        .iter()
+        .map(|(start, end)| ParameterInformation {
+            label: ParameterLabel::LabelOffsets([*start as u32, *end as u32]),
+            documentation: None,
+        })
+        .collect();
+    let active_parameter = signature.active_parameter.map(|idx| idx as u32);
+    Ok(Some(SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label: signature.label,
+            documentation: None,
+            parameters: Some(parameters),
+            active_parameter,
+        }],
+        active_signature: Some(0),
+        active_parameter,
+    }))
+}
+
+pub fn handle_document_symbol(
+    snapshot: GlobalStateSnapshot,
+    params: DocumentSymbolParams,
+) -> Result<Option<DocumentSymbolResponse>> {
+    let _pool = ConstPool::new();
+
+    let fpath = tracked_file(&snapshot, params.text_document.uri)?;
+    let nodes = match snapshot.analysis.file_structure(&fpath) {
+        Some(nodes) => nodes,
+        None => return Ok(None),
+    };
+    let db = snapshot.analysis.db();
+    let symbols = nodes
+        .into_iter()
+        .map(|node| document_symbol(db, &fpath, node))
+        .collect::<Result<_>>()?;
+    Ok(Some(DocumentSymbolResponse::Nested(symbols)))
+}
+
+fn document_symbol(
+    db: &RootDatabase,
+    fpath: &str,
+    node: StructureNode,
+) -> Result<DocumentSymbol> {
+    let children = node
+        .children
+        .into_iter()
+        .map(|child| document_symbol(db, fpath, child))
+        .collect::<Result<Vec<_>>>()?;
+    #[allow(deprecated)]
+    Ok(DocumentSymbol {
+        name: node.label,
+        detail: node.detail,
+        kind: symbol_kind(node.kind),
+        tags: None,
+        deprecated: None,
+        range: db.span_to_range(fpath, node.span)?,
+        selection_range: db.span_to_range(fpath, node.navigation_span)?,
+        children: if children.is_empty() {
+            None
+        } else {
+            Some(children)
+        },
+    })
+}
+
+fn symbol_kind(kind: StructureNodeKind) -> SymbolKind {
+    match kind {
+        StructureNodeKind::Address => SymbolKind::Namespace,
+        StructureNodeKind::Module => SymbolKind::Module,
+        StructureNodeKind::Script => SymbolKind::File,
+        StructureNodeKind::Struct => SymbolKind::Struct,
+        StructureNodeKind::Field => SymbolKind::Field,
+        StructureNodeKind::Function => SymbolKind::Function,
+        StructureNodeKind::Constant => SymbolKind::Constant,
+        StructureNodeKind::Spec => SymbolKind::Object,
+    }
+}
+
+pub fn handle_references(
+    snapshot: GlobalStateSnapshot,
+    params: ReferenceParams,
+) -> Result<Option<Vec<Location>>> {
+    let _pool = ConstPool::new();
+
+    let (fpath, offset) = file_position(&snapshot, params.text_document_position)?;
+    let search_result = match snapshot.analysis.find_all_references(&fpath, offset) {
+        Some(search_result) => search_result,
+        None => return Ok(None),
+    };
+    let db = snapshot.analysis.db();
+    let declaration = search_result
+        .declaration
+        .iter()
+        .filter(|_| params.context.include_declaration);
+    let locations = declaration
+        .chain(search_result.references.iter())
+        .map(|reference| db.span_to_location(&reference.fpath, reference.span))
+        .collect::<Result<_>>()?;
+    Ok(Some(locations))
+}
+
+pub fn handle_prepare_rename(
+    snapshot: GlobalStateSnapshot,
+    params: TextDocumentPositionParams,
+) -> Result<Option<PrepareRenameResponse>> {
+    let _pool = ConstPool::new();
+
+    let (fpath, offset) = file_position(&snapshot, params)?;
+    let span = snapshot
+        .analysis
+        .prepare_rename(&fpath, offset)
+        .map_err(rename_error)?;
+    let range = snapshot.analysis.db().span_to_range(&fpath, span)?;
+    Ok(Some(PrepareRenameResponse::Range(range)))
+}
+
+pub fn handle_rename(
+    snapshot: GlobalStateSnapshot,
+    params: RenameParams,
+) -> Result<Option<WorkspaceEdit>> {
+    let _pool = ConstPool::new();
+
+    let (fpath, offset) = file_position(&snapshot, params.text_document_position)?;
+    let edits = snapshot
+        .analysis
+        .rename(&fpath, offset, &params.new_name)
+        .map_err(rename_error)?;
+    Ok(Some(to_workspace_edit(snapshot.analysis.db(), edits)?))
+}
+
+fn to_workspace_edit(db: &RootDatabase, edits: Vec<FileEdit>) -> Result<WorkspaceEdit> {
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for edit in edits {
+        let location = db.span_to_location(&edit.fpath, edit.span)?;
+        changes
+            .entry(location.uri)
+            .or_default()
+            .push(TextEdit::new(location.range, edit.new_text));
+    }
+    Ok(WorkspaceEdit::new(changes))
+}
+
+/// Quick fixes of the diagnostics passed by the client and of the unused `use`s in the range.
+pub fn handle_code_action(
+    snapshot: GlobalStateSnapshot,
+    params: CodeActionParams,
+) -> Result<Option<CodeActionResponse>> {
+    let _pool = ConstPool::new();
+
+    let fpath = tracked_file(&snapshot, params.text_document.uri)?;
+    let db = snapshot.analysis.db();
+    let range = match (
+        db.offset(&fpath, params.range.start),
+        db.offset(&fpath, params.range.end),
+    ) {
+        (Some(start), Some(end)) => (start, end),
+        _ => return Ok(None),
+    };
+    let (diagnostics, lsp_diagnostics): (Vec<_>, Vec<_>) = params
+        .context
+        .diagnostics
+        .into_iter()
+        .filter_map(|diagnostic| {
+            let start = db.offset(&fpath, diagnostic.range.start)?;
+            let end = db.offset(&fpath, diagnostic.range.end)?;
+            let mut message = diagnostic.message.clone();
+            for related in diagnostic.related_information.iter().flatten() {
+                message.push('\n');
+                message.push_str(&related.message);
+            }
+            let span = (start, end);
+            Some((ActionDiagnostic { span, message }, diagnostic))
+        })
+        .unzip();
+
+    let mut actions = vec![];
+    for action in snapshot.analysis.code_actions(&fpath, range, &diagnostics) {
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: action.title,
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: action
+                .diagnostic
+                .map(|idx| vec![lsp_diagnostics[idx].clone()]),
+            edit: Some(to_workspace_edit(db, action.edits)?),
+            ..CodeAction::default()
+        }));
+    }
+    Ok(Some(actions))
+}
+
+pub fn handle_inlay_hints(
+    snapshot: GlobalStateSnapshot,
+    params: InlayHintParams,
+) -> Result<Option<Vec<InlayHint>>> {
+    let _pool = ConstPool::new();
+
+    let fpath = tracked_file(&snapshot, params.text_document.uri)?;
+    let db = snapshot.analysis.db();
+    let range = match (
+        db.offset(&fpath, params.range.start),
+        db.offset(&fpath, params.range.end),
+    ) {
+        (Some(start), Some(end)) => (start, end),
+        _ => return Ok(None),
+    };
+    let mut hints = vec![];
+    for hint in snapshot.analysis.inlay_hints(&fpath, range) {
+        let is_type = hint.kind == InlayHintKind::Type;
+        hints.push(InlayHint {
+            position: db.span_to_range(&fpath, (hint.offset, hint.offset))?.start,
+            label: hint.label,
+            kind: if is_type {
+                INLAY_HINT_KIND_TYPE
+            } else {
+                INLAY_HINT_KIND_PARAMETER
+            },
+            padding_left: false,
+            padding_right: !is_type,
+        });
+    }
+    Ok(Some(hints))
+}
+
+pub fn handle_call_hierarchy_prepare(
+    snapshot: GlobalStateSnapshot,
+    params: CallHierarchyPrepareParams,
+) -> Result<Option<Vec<CallHierarchyItem>>> {
+    let _pool = ConstPool::new();
+
+    let (fpath, offset) = file_position(&snapshot, params.text_document_position_params)?;
+    match snapshot.analysis.prepare_call_hierarchy(&fpath, offset) {
+        Some(item) => Ok(Some(vec![to_call_hierarchy_item(
+            snapshot.analysis.db(),
+            item,
+        )?])),
+        None => Ok(None),
+    }
+}
+
+pub fn handle_call_hierarchy_incoming(
+    snapshot: GlobalStateSnapshot,
+    params: CallHierarchyIncomingCallsParams,
+) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
+    let _pool = ConstPool::new();
+
+    let (fpath, offset) = call_item_position(&params.item)?;
+    let db = snapshot.analysis.db();
+    let mut calls = vec![];
+    for call in snapshot.analysis.incoming_calls(&fpath, offset) {
+        let mut from_ranges = vec![];
+        for span in call.call_spans {
+            from_ranges.push(location(db, &call.item.fpath, span)?.range);
+        }
+        calls.push(CallHierarchyIncomingCall {
+            from: to_call_hierarchy_item(db, call.item)?,
+            from_ranges,
+        });
+    }
+    Ok(Some(calls))
+}
+
+pub fn handle_call_hierarchy_outgoing(
+    snapshot: GlobalStateSnapshot,
+    params: CallHierarchyOutgoingCallsParams,
+) -> Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+    let _pool = ConstPool::new();
+
+    let (fpath, offset) = call_item_position(&params.item)?;
+    let db = snapshot.analysis.db();
+    let mut calls = vec![];
+    for call in snapshot.analysis.outgoing_calls(&fpath, offset) {
+        // the calls are in the file of the caller
+        let mut from_ranges = vec![];
+        for span in call.call_spans {
+            from_ranges.push(location(db, &fpath, span)?.range);
+        }
+        calls.push(CallHierarchyOutgoingCall {
+            to: to_call_hierarchy_item(db, call.item)?,
+            from_ranges,
+        });
+    }
+    Ok(Some(calls))
+}
+
+/// The item is found again by the `fpath` and the `offset` of the name kept in its `data`.
+fn to_call_hierarchy_item(db: &RootDatabase, item: CallItem) -> Result<CallHierarchyItem> {
+    let full_location = location(db, &item.fpath, item.span)?;
+    let selection_range = location(db, &item.fpath, item.name_span)?.range;
+    Ok(CallHierarchyItem {
+        name: item.name,
+        kind: SymbolKind::Function,
+        tags: None,
+        detail: Some(item.signature),
+        uri: full_location.uri,
+        range: full_location.range,
+        selection_range,
+        data: Some(serde_json::json!({
+            "fpath": item.fpath,
+            "offset": item.name_span.0,
+        })),
+    })
+}
+
+fn call_item_position(item: &CallHierarchyItem) -> Result<(String, usize)> {
+    let data = item.data.as_ref();
+    let fpath = data
+        .and_then(|data| data.get("fpath"))
+        .and_then(|fpath| fpath.as_str());
+    let offset = data
+        .and_then(|data| data.get("offset"))
+        .and_then(|offset| offset.as_u64());
+    match (fpath, offset) {
+        (Some(fpath), Some(offset)) => Ok((fpath.to_owned(), offset as usize)),
+        _ => Err(invalid_params(format!(
+            "Call hierarchy item {:?} has no position of the function",
+            item.name
+        ))),
+    }
+}
+
+pub fn handle_folding_range(
+    snapshot: GlobalStateSnapshot,
+    params: FoldingRangeParams,
+) -> Result<Option<Vec<FoldingRange>>> {
+    let _pool = ConstPool::new();
+
+    let fpath = tracked_file(&snapshot, params.text_document.uri)?;
+    let db = snapshot.analysis.db();
+    let folds = match snapshot.analysis.folding_ranges(&fpath) {
+        Some(folds) => folds,
+        None => return Ok(None),
+    };
+    let mut ranges = vec![];
+    for fold in folds {
+        let range = db.span_to_range(&fpath, fold.span)?;
+        // the closing brace stays visible
+        let end_line = match fold.kind {
+            FoldKind::Region => range.end.line.saturating_sub(1),
+            FoldKind::Imports | FoldKind::Comment => range.end.line,
+        };
+        if end_line <= range.start.line {
+            continue;
+        }
+        ranges.push(FoldingRange {
+            start_line: range.start.line,
+            start_character: None,
+            end_line,
+            end_character: None,
+            kind: Some(match fold.kind {
+                FoldKind::Region => FoldingRangeKind::Region,
+                FoldKind::Imports => FoldingRangeKind::Imports,
+                FoldKind::Comment => FoldingRangeKind::Comment,
+            }),
+        });
+    }
+    Ok(Some(ranges))
+}
+
+pub fn handle_selection_range(
+    snapshot: GlobalStateSnapshot,
+    params: SelectionRangeParams,
+) -> Result<Option<Vec<SelectionRange>>> {
+    let _pool = ConstPool::new();
+
+    let fpath = tracked_file(&snapshot, params.text_document.uri)?;
+    let db = snapshot.analysis.db();
+    let mut selection_ranges = vec![];
+    for position in params.positions {
+        let spans = db
+            .offset(&fpath, position)
+            .and_then(|offset| snapshot.analysis.selection_ranges(&fpath, offset))
+            .unwrap_or_default();
+        let mut selection = None;
+        for span in spans.into_iter().rev() {
+            selection = Some(SelectionRange {
+                range: db.span_to_range(&fpath, span)?,
+                parent: selection.map(Box::new),
+            });
+        }
+        // every position has to be answered, with an empty range if nothing is found
+        selection_ranges.push(selection.unwrap_or(SelectionRange {
+            range: Range::new(position, position),
+            parent: None,
+        }));
+    }
+    Ok(Some(selection_ranges))
+}
+
+pub fn handle_document_highlight(
+    snapshot: GlobalStateSnapshot,
+    params: DocumentHighlightParams,
+) -> Result<Option<Vec<DocumentHighlight>>> {
+    let _pool = ConstPool::new();
+
+    let (fpath, offset) = file_position(&snapshot, params.text_document_position_params)?;
+    let db = snapshot.analysis.db();
+    let mut highlights = vec![];
+    for highlight in snapshot.analysis.document_highlights(&fpath, offset) {
+        highlights.push(DocumentHighlight {
+            range: db.span_to_range(&fpath, highlight.span)?,
+            kind: Some(match highlight.access {
+                HighlightAccess::Text => DocumentHighlightKind::Text,
+                HighlightAccess::Read => DocumentHighlightKind::Read,
+                HighlightAccess::Write => DocumentHighlightKind::Write,
+            }),
+        });
+    }
+    Ok(Some(highlights))
+}
+
+pub fn handle_formatting(
+    snapshot: GlobalStateSnapshot,
+    params: DocumentFormattingParams,
+) -> Result<Option<Vec<TextEdit>>> {
+    let fpath = tracked_file(&snapshot, params.text_document.uri)?;
+    let edit = snapshot.analysis.format(&fpath)?;
+    to_text_edits(snapshot.analysis.db(), edit)
+}
+
+pub fn handle_range_formatting(
+    snapshot: GlobalStateSnapshot,
+    params: DocumentRangeFormattingParams,
+) -> Result<Option<Vec<TextEdit>>> {
+    let fpath = tracked_file(&snapshot, params.text_document.uri)?;
+    let edit = snapshot.analysis.format_lines(
+        &fpath,
+        params.range.start.line as usize,
+        params.range.end.line as usize,
+    )?;
+    to_text_edits(snapshot.analysis.db(), edit)
+}
+
+fn to_text_edits(db: &RootDatabase, edit: Option<FileEdit>) -> Result<Option<Vec<TextEdit>>> {
+    let edits = match edit {
+        Some(edit) => vec![TextEdit::new(
+            db.span_to_range(&edit.fpath, edit.span)?,
+            edit.new_text,
+        )],
+        None => vec![],
+    };
+    Ok(Some(edits))
+}
+
+pub fn handle_semantic_tokens(
+    snapshot: GlobalStateSnapshot,
+    params: SemanticTokensParams,
+) -> Result<Option<SemanticTokensResult>> {
+    let _pool = ConstPool::new();
+
+    let fpath = tracked_file(&snapshot, params.text_document.uri)?;
+    let tokens = semantic_tokens(&snapshot, &fpath, None);
+    Ok(Some(tokens.into()))
+}
+
+pub fn handle_semantic_tokens_range(
+    snapshot: GlobalStateSnapshot,
+    params: SemanticTokensRangeParams,
+) -> Result<Option<SemanticTokensRangeResult>> {
+    let _pool = ConstPool::new();
+
+    let fpath = tracked_file(&snapshot, params.text_document.uri)?;
+    let db = snapshot.analysis.db();
+    let text_len = db.available_files[&fpath].len();
+    let start = db.offset(&fpath, params.range.start).unwrap_or(text_len);
+    let end = db.offset(&fpath, params.range.end).unwrap_or(text_len);
+    let tokens = semantic_tokens(&snapshot, &fpath, Some((start, end)));
+    Ok(Some(tokens.into()))
+}
+
+fn semantic_tokens(
+    snapshot: &GlobalStateSnapshot,
+    fpath: &str,
+    range: Option<Span>,
+) -> SemanticTokens {
+    let ranges = snapshot.analysis.highlight(fpath, range);
+    let text = &snapshot.analysis.db().available_files[fpath];
+    SemanticTokens {
+        result_id: None,
+        data: to_semantic_tokens(text, &ranges),
+    }
+}
+
+pub fn handle_code_lens(
+    snapshot: GlobalStateSnapshot,
+    params: CodeLensParams,
+) -> Result<Option<Vec<CodeLens>>> {
+    let uri = params.text_document.uri;
+    let fpath = tracked_file(&snapshot, uri.clone())?;
+    let db = snapshot.analysis.db();
+
+    let mut lenses = vec![];
+    for runnable in snapshot.analysis.runnables(&fpath) {
+        let start = db.span_to_range(&fpath, runnable.span)?.start;
+        let args = RunScriptArgs {
+            uri: uri.clone(),
+            script: runnable.script,
+        };
+        for (title, command) in &[
+            ("Run", RUN_SCRIPT_COMMAND),
+            ("Dry run", DRY_RUN_SCRIPT_COMMAND),
+        ] {
+            lenses.push(CodeLens {
+                range: Range::new(start, start),
+                command: Some(Command {
+                    title: title.to_string(),
+                    command: command.to_string(),
+                    arguments: Some(vec![serde_json::to_value(&args)?]),
+                }),
+                data: None,
+            });
+        }
+    }
+    Ok(Some(lenses))
+}
+
+/// Runs the script, the result is sent back as a message.
+pub fn handle_execute_command(
+    snapshot: GlobalStateSnapshot,
+    params: ExecuteCommandParams,
+    events: &Sender<ResponseEvent>,
+) -> Result<Option<serde_json::Value>> {
+    let _pool = ConstPool::new();
+
+    let meta = match params.command.as_str() {
+        RUN_SCRIPT_COMMAND => vec![],
+        DRY_RUN_SCRIPT_COMMAND => vec![DRY_RUN_META.to_owned()],
+        _ => {
+            return Err(invalid_params(format!(
+                "Unknown command {:?}",
+                params.command
+            )))
+        }
+    };
+    let args = match params.arguments.into_iter().next() {
+        Some(args) => serde_json::from_value::<RunScriptArgs>(args)?,
+        None => {
+            return Err(invalid_params(format!(
+                "No script passed to {:?}",
+                params.command
+            )))
+        }
+    };
+    let fpath = tracked_file(&snapshot, args.uri)?;
+
+    let (typ, message) = match snapshot.analysis.run(&fpath, args.script, vec![], &meta) {
+        Ok(result) => {
+            let is_failed = result
+                .step_results
+                .iter()
+                .any(|(_, _, _, step)| matches!(step, StepExecutionResult::Error(_)));
+            let message = result
+                .step_results
+                .into_iter()
+                .map(|step| format_step_result(step, true, true))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let typ = if is_failed {
+                MessageType::Error
+            } else {
+                MessageType::Info
+            };
+            (typ, message)
+        }
+        Err(error) => (MessageType::Error, error.to_string()),
+    };
+    events
+        .send(ResponseEvent::ShowMessage(ShowMessageParams {
+            typ,
+            message: message.clone(),
+        }))
+        .unwrap();
+    Ok(Some(serde_json::Value::String(message)))
+}
+
+pub fn handle_execute_script(
+    snapshot: GlobalStateSnapshot,
+    params: ExecuteScriptParams,
+) -> Result<ExecuteScriptResult> {
+    let _pool = ConstPool::new();
+
+    let fpath = tracked_file(&snapshot, params.uri)?;
+    let result = snapshot
+        .analysis
+        .run(&fpath, params.script, params.args, &params.meta);
+    Ok(match result {
+        Ok(result) => ExecuteScriptResult {
+            steps: result
+                .step_results
+                .into_iter()
+                .map(|(name, gas, write_set_size, result)| ExecutedStep {
+                    name,
+                    gas,
+                    write_set_size,
+                    result,
+                })
+                .collect(),
+            error: None,
+        },
+        Err(error) => ExecuteScriptResult {
+            steps: vec![],
+            error: Some(error.to_string()),
+        },
+    })
+}
+
+pub fn handle_decompiled_source(
+    snapshot: GlobalStateSnapshot,
+    params: DecompiledSourceParams,
+) -> Result<String> {
+    // modules are loaded from the chain of the first folder which has the `blockchain_api`
+    let config = iter::once(&snapshot.config)
+        .chain(
+            snapshot
+                .workspaces
+                .iter()
+                .map(|analysis| &analysis.db().config),
+        )
+        .find(|config| config.blockchain_api.is_some())
+        .unwrap_or(&snapshot.config);
+    decompiled::decompiled_source(config, &params.uri)
+        .map_err(|error| invalid_params(error.to_string()))
+}
+
+fn rename_error(error: RenameError) -> anyhow::Error {
+    invalid_params(error.to_string())
+}
+
+fn invalid_params(message: String) -> anyhow::Error {
+    LspError::new(ErrorCode::InvalidParams as i32, message).into()
+}
+
+const WORKSPACE_SYMBOLS_LIMIT: usize = 128;
+
+pub fn handle_workspace_symbol(
+    snapshot: GlobalStateSnapshot,
+    params: WorkspaceSymbolParams,
+) -> Result<Option<Vec<SymbolInformation>>> {
+    let symbols = snapshot
+        .workspace_symbols(&params.query, WORKSPACE_SYMBOLS_LIMIT)
+        .into_iter()
+        .map(|symbol| {
+            let db = snapshot.analysis_with(&symbol.fpath).db();
+            #[allow(deprecated)]
+            Ok(SymbolInformation {
+                location: db.span_to_location(&symbol.fpath, symbol.name_span)?,
+                name: symbol.name,
+                kind: item_symbol_kind(symbol.kind),
+                tags: None,
+                deprecated: None,
+                container_name: Some(symbol.container),
+            })
+        })
+        .collect::<Result<_>>()?;
+    Ok(Some(symbols))
+}
+
+fn item_symbol_kind(kind: ItemKind) -> SymbolKind {
+    match kind {
+        ItemKind::Module => SymbolKind::Module,
+        ItemKind::Struct => SymbolKind::Struct,
+        ItemKind::Function => SymbolKind::Function,
+        ItemKind::Constant => SymbolKind::Constant,
+    }
+}
@@ -1,47 +1,226 @@
-use crate::inner::db::{RootDatabase, FileDiagnostic};
+use crate::inner::call_hierarchy::{self, Call, CallItem};
+use crate::inner::code_actions::{self, ActionDiagnostic, CodeAction};
+use crate::inner::completion::{self, CompletionItem};
+use crate::inner::db::{self, RootDatabase, FileDiagnostic};
+use crate::inner::document_highlights::{self, DocumentHighlight};
+use crate::inner::file_structure::{self, StructureNode};
+use crate::inner::folding_ranges::{self, Fold};
+use crate::inner::formatting;
+use crate::inner::goto_definition::{self, NavigationTarget};
+use crate::inner::highlighting::{self, HighlightedRange};
+use crate::inner::hover::{self, HoverResult};
+use crate::inner::inlay_hints::{self, InlayHint};
+use crate::inner::references::{self, ReferenceSearchResult};
+use crate::inner::rename::{self, FileEdit, RenameError};
+use crate::inner::runnables::{self, Runnable};
+use crate::inner::selection_ranges;
+use crate::inner::signature_help::{self, SignatureHelpResult};
+use crate::inner::symbol_index::IndexedSymbol;
+use crate::inner::symbols::{FileSymbols, ItemPath, Span};
+use crate::inner::typing::{FileTypes, TypesExtractor};
 use lang::compiler::file::MoveFile;
-use lang::compiler::file;
-use lang::checker::MoveChecker;
+use move_executor::explain::PipelineExecutionResult;
+use lang::compiler;
+use std::sync::Arc;
 
+/// Immutable snapshot of the database, cheap to create and to send to the worker thread.
 #[derive(Debug)]
 pub struct Analysis {
-    db: RootDatabase,
+    db: Arc<RootDatabase>,
 }
 
 impl Analysis {
-    pub fn new(db: RootDatabase) -> Analysis {
-        Analysis { db }
+    pub fn new(db: impl Into<Arc<RootDatabase>>) -> Analysis {
+        Analysis { db: db.into() }
     }
 
     pub fn db(&self) -> &RootDatabase {
         &self.db
     }
 
-    pub fn check_file(&self, file: MoveFile) -> Option<FileDiagnostic> {
-        match self.check_file_inner(file) {
-            Ok(_) => None,
-            Err(mut ds) => Some(ds.remove(0)),
+    pub fn file_symbols(&self, fpath: &str) -> Option<Arc<FileSymbols>> {
+        self.db.file_symbols(fpath)
+    }
+
+    /// Symbols of all the files which could be parsed.
+    pub fn parsed_files(&self) -> Vec<Arc<FileSymbols>> {
+        self.db
+            .available_files
+            .keys()
+            .filter_map(|fpath| self.db.file_symbols(fpath))
+            .collect()
+    }
+
+    /// Symbols of the file which defines the module `path`, `current_file` is checked first.
+    pub fn module_file(
+        &self,
+        current_file: &Arc<FileSymbols>,
+        path: &ItemPath,
+    ) -> Option<Arc<FileSymbols>> {
+        if current_file.module(path).is_some() {
+            return Some(current_file.clone());
         }
+        self.db
+            .available_files
+            .keys()
+            .filter(|fpath| fpath.as_str() != current_file.fpath)
+            .filter_map(|fpath| self.db.file_symbols(fpath))
+            .find(|symbols| symbols.module(path).is_some())
+    }
+
+    pub fn file_structure(&self, fpath: &str) -> Option<Vec<StructureNode>> {
+        self.file_symbols(fpath)
+            .map(|symbols| file_structure::file_structure(&symbols))
+    }
+
+    pub fn goto_definition(&self, fpath: &str, offset: usize) -> Option<NavigationTarget> {
+        goto_definition::goto_definition(self, fpath, offset)
+    }
+
+    pub fn completions(&self, fpath: &str, offset: usize) -> Vec<CompletionItem> {
+        completion::completions(self, fpath, offset)
+    }
+
+    pub fn hover(&self, fpath: &str, offset: usize) -> Option<HoverResult> {
+        hover::hover(self, fpath, offset)
+    }
+
+    pub fn signature_help(&self, fpath: &str, offset: usize) -> Option<SignatureHelpResult> {
+        signature_help::signature_help(self, fpath, offset)
+    }
+
+    pub fn find_all_references(
+        &self,
+        fpath: &str,
+        offset: usize,
+    ) -> Option<ReferenceSearchResult> {
+        references::find_all_references(self, fpath, offset)
+    }
+
+    pub fn prepare_rename(&self, fpath: &str, offset: usize) -> Result<Span, RenameError> {
+        rename::prepare_rename(self, fpath, offset)
+    }
+
+    pub fn rename(
+        &self,
+        fpath: &str,
+        offset: usize,
+        new_name: &str,
+    ) -> Result<Vec<FileEdit>, RenameError> {
+        rename::rename(self, fpath, offset, new_name)
+    }
+
+    pub fn format(&self, fpath: &str) -> anyhow::Result<Option<FileEdit>> {
+        formatting::format_file(self, fpath)
+    }
+
+    pub fn format_lines(
+        &self,
+        fpath: &str,
+        start_line: usize,
+        end_line: usize,
+    ) -> anyhow::Result<Option<FileEdit>> {
+        formatting::format_lines(self, fpath, start_line, end_line)
+    }
+
+    pub fn prepare_call_hierarchy(&self, fpath: &str, offset: usize) -> Option<CallItem> {
+        call_hierarchy::prepare(self, fpath, offset)
+    }
+
+    /// Callers of the function with the name at `offset`, `fpath` could be a `.mv` file.
+    pub fn incoming_calls(&self, fpath: &str, offset: usize) -> Vec<Call> {
+        call_hierarchy::incoming_calls(self, fpath, offset)
     }
 
-    fn check_file_inner(&self, current_file: MoveFile) -> Result<(), Vec<FileDiagnostic>> {
+    /// Callees of the function with the name at `offset`, `fpath` could be a `.mv` file.
+    pub fn outgoing_calls(&self, fpath: &str, offset: usize) -> Vec<Call> {
+        call_hierarchy::outgoing_calls(self, fpath, offset)
+    }
+
+    pub fn folding_ranges(&self, fpath: &str) -> Option<Vec<Fold>> {
+        let text = self.db.available_files.get(fpath)?;
+        let symbols = self.file_symbols(fpath)?;
+        Some(folding_ranges::folding_ranges(text, &symbols))
+    }
+
+    /// Spans around the `offset`, the innermost first.
+    pub fn selection_ranges(&self, fpath: &str, offset: usize) -> Option<Vec<Span>> {
+        let text = self.db.available_files.get(fpath)?;
+        selection_ranges::selection_ranges(&self.db.config, fpath, text, offset)
+    }
+
+    pub fn document_highlights(&self, fpath: &str, offset: usize) -> Vec<DocumentHighlight> {
+        self.file_symbols(fpath)
+            .map(|symbols| document_highlights::document_highlights(&symbols, offset))
+            .unwrap_or_default()
+    }
+
+    pub fn highlight(&self, fpath: &str, range: Option<Span>) -> Vec<HighlightedRange> {
+        highlighting::highlight(self, fpath, range)
+    }
+
+    pub fn runnables(&self, fpath: &str) -> Vec<Runnable> {
+        runnables::runnables(self, fpath)
+    }
+
+    pub fn run(
+        &self,
+        fpath: &str,
+        script: Option<usize>,
+        args: Vec<String>,
+        meta: &[String],
+    ) -> anyhow::Result<PipelineExecutionResult> {
+        runnables::run(self, fpath, script, args, meta)
+    }
+
+    pub fn code_actions(
+        &self,
+        fpath: &str,
+        range: Span,
+        diagnostics: &[ActionDiagnostic],
+    ) -> Vec<CodeAction> {
+        code_actions::code_actions(self, fpath, range, diagnostics)
+    }
+
+    pub fn inlay_hints(&self, fpath: &str, range: Span) -> Vec<InlayHint> {
+        inlay_hints::inlay_hints(self, fpath, range)
+    }
+
+    pub fn workspace_symbols(&self, query: &str, limit: usize) -> Vec<IndexedSymbol> {
+        self.db.symbol_index.query(query, limit)
+    }
+
+    /// Compiler errors of the file grouped per file they point to, empty if the check succeeded.
+    pub fn check_file(&self, file: MoveFile) -> Vec<FileDiagnostic> {
+        self.check_file_with_types(file).err().unwrap_or_default()
+    }
+
+    pub fn check_file_with_types(
+        &self,
+        file: MoveFile,
+    ) -> Result<FileTypes, Vec<FileDiagnostic>> {
+        self.check_file_inner(file).map_err(db::group_by_file)
+    }
+
+    fn check_file_inner(&self, current_file: MoveFile) -> Result<FileTypes, Vec<FileDiagnostic>> {
+        let fname = current_file.name().to_owned();
         let deps: Vec<MoveFile> = self
-            .read_stdlib_files()
+            .db
+            .dependencies(&fname, current_file.content())
             .into_iter()
-            .chain(
-                self.db
-                    .module_files()
-                    .into_iter()
-                    .map(|(name, text)| MoveFile::with_content(name, text)),
-            )
-            .filter(|file| file.name() != current_file.name())
+            .filter_map(|fpath| {
+                let text = self.db.available_files.get(&fpath)?;
+                Some(MoveFile::with_content(fpath, &**text))
+            })
             .collect();
 
-        MoveChecker::new(
+        compiler::compile(
             self.db.config.dialect().as_ref(),
+            &[current_file],
+            &deps,
             Some(self.db.config.sender()),
+            TypesExtractor::new(&fname),
         )
-        .check(&[current_file], &deps)
         .map_err(|errors| {
             errors
                 .into_iter()
@@ -56,13 +235,4 @@ impl Analysis {
                 .collect()
         })
     }
-
-    fn read_stdlib_files(&self) -> Vec<MoveFile> {
-        self.db
-            .config
-            .stdlib_folder
-            .as_ref()
-            .map(|folder| file::load_move_files(&[folder]).unwrap_or_default())
-            .unwrap_or_default()
-    }
 }
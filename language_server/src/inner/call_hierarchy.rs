@@ -0,0 +1,180 @@
+//! Callers and callees of the functions, across the tracked files and the `.mv` dependencies.
+//! Calls of the compiled modules are found in their decompiled sources.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::inner::analysis::Analysis;
+use crate::inner::decompiled;
+use crate::inner::symbols::{span_contains, FileSymbols, FunctionSymbol, ItemKind, ItemPath, Span};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallItem {
+    pub name: String,
+    /// `None` for the script function, which can't be called.
+    pub path: Option<ItemPath>,
+    pub signature: String,
+    pub fpath: String,
+    pub span: Span,
+    pub name_span: Span,
+}
+
+impl CallItem {
+    fn new(symbols: &FileSymbols, function: &FunctionSymbol) -> CallItem {
+        let path = symbols.module_at(function.span.0).map(|module| {
+            ItemPath::member(module.address, module.name.clone(), function.name.clone())
+        });
+        CallItem {
+            name: function.name.clone(),
+            path,
+            signature: function.signature(),
+            fpath: symbols.fpath.clone(),
+            span: function.span,
+            name_span: function.name_span,
+        }
+    }
+}
+
+/// Caller or callee with the spans of the calls, in the file of the caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    pub item: CallItem,
+    pub call_spans: Vec<Span>,
+}
+
+/// Function defined or called at the `offset`.
+pub fn prepare(analysis: &Analysis, fpath: &str, offset: usize) -> Option<CallItem> {
+    let symbols = analysis.file_symbols(fpath)?;
+    if let Some(reference) = symbols.reference_at(offset) {
+        if reference.kind == ItemKind::Function {
+            return resolve(analysis, &symbols, &reference.path);
+        }
+        return None;
+    }
+    function_at_name(&symbols, offset)
+}
+
+/// Functions which call the function with the name at `offset`.
+pub fn incoming_calls(analysis: &Analysis, fpath: &str, offset: usize) -> Vec<Call> {
+    let target = file_symbols(analysis, fpath)
+        .and_then(|symbols| function_at_name(&symbols, offset))
+        .and_then(|item| item.path);
+    let target = match target {
+        Some(path) => path,
+        None => return vec![],
+    };
+
+    let sources = analysis.parsed_files();
+    let defined_modules: HashSet<ItemPath> = sources
+        .iter()
+        .flat_map(|symbols| symbols.defined_modules())
+        .collect();
+    // the modules compiled from the tracked sources are checked once
+    let bytecode = decompiled::bytecode_modules(analysis.db())
+        .into_iter()
+        .filter(|symbols| {
+            symbols
+                .defined_modules()
+                .any(|module| !defined_modules.contains(&module))
+        });
+
+    let mut calls = vec![];
+    for symbols in sources.into_iter().chain(bytecode) {
+        for function in symbols.functions() {
+            let call_spans = calls_within(&symbols, function.span)
+                .filter(|(path, _)| *path == &target)
+                .map(|(_, span)| span)
+                .collect::<Vec<_>>();
+            if !call_spans.is_empty() {
+                calls.push(Call {
+                    item: CallItem::new(&symbols, function),
+                    call_spans,
+                });
+            }
+        }
+    }
+    calls
+}
+
+/// Functions called by the function with the name at `offset`.
+pub fn outgoing_calls(analysis: &Analysis, fpath: &str, offset: usize) -> Vec<Call> {
+    let symbols = match file_symbols(analysis, fpath) {
+        Some(symbols) => symbols,
+        None => return vec![],
+    };
+    let function = match symbols
+        .functions()
+        .find(|function| span_contains(function.name_span, offset))
+    {
+        Some(function) => function,
+        None => return vec![],
+    };
+
+    let mut callees: Vec<(&ItemPath, Vec<Span>)> = vec![];
+    for (path, span) in calls_within(&symbols, function.span) {
+        match callees.iter_mut().find(|(callee, _)| *callee == path) {
+            Some((_, spans)) => spans.push(span),
+            None => callees.push((path, vec![span])),
+        }
+    }
+    callees
+        .into_iter()
+        .filter_map(|(path, call_spans)| {
+            Some(Call {
+                item: resolve(analysis, &symbols, path)?,
+                call_spans,
+            })
+        })
+        .collect()
+}
+
+/// Paths and spans of the functions called within the `span`.
+fn calls_within(symbols: &FileSymbols, span: Span) -> impl Iterator<Item = (&ItemPath, Span)> {
+    symbols
+        .references
+        .iter()
+        .filter(move |reference| {
+            reference.kind == ItemKind::Function
+                && span.0 <= reference.span.0
+                && reference.span.1 <= span.1
+        })
+        .map(|reference| (&reference.path, reference.span))
+}
+
+fn function_at_name(symbols: &FileSymbols, offset: usize) -> Option<CallItem> {
+    symbols
+        .functions()
+        .find(|function| span_contains(function.name_span, offset))
+        .map(|function| CallItem::new(symbols, function))
+}
+
+/// Source file, or the decompiled `.mv` file of the dependency.
+fn file_symbols(analysis: &Analysis, fpath: &str) -> Option<Arc<FileSymbols>> {
+    if decompiled::is_bytecode_file(fpath) {
+        analysis
+            .db()
+            .decompiled
+            .symbols(&analysis.db().config, fpath)
+    } else {
+        analysis.file_symbols(fpath)
+    }
+}
+
+/// Definition of the called function, the module could exist only as a bytecode.
+fn resolve(
+    analysis: &Analysis,
+    current_file: &Arc<FileSymbols>,
+    path: &ItemPath,
+) -> Option<CallItem> {
+    let module_file = match analysis.module_file(current_file, path) {
+        Some(module_file) => module_file,
+        None => decompiled::find_module_file(analysis.db(), path)?,
+    };
+    let name = path.member.as_ref()?;
+    let function = module_file
+        .module(path)?
+        .functions
+        .iter()
+        .find(|function| &function.name == name)?;
+    Some(CallItem::new(&module_file, function))
+}
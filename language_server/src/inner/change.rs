@@ -2,11 +2,26 @@ use core::fmt;
 use serde::export::Formatter;
 use std::cmp::min;
 use crate::inner::config::Config;
+use anyhow::Result;
+use lsp_types::TextDocumentContentChangeEvent;
+use lang::compiler::location::File;
 
 pub enum RootChange {
-    AddFile { path: String, text: String },
-    ChangeFile { path: String, text: String },
-    RemoveFile { path: String },
+    AddFile {
+        path: String,
+        text: String,
+    },
+    ChangeFile {
+        path: String,
+        text: String,
+    },
+    EditFile {
+        path: String,
+        changes: Vec<TextDocumentContentChangeEvent>,
+    },
+    RemoveFile {
+        path: String,
+    },
 }
 
 impl fmt::Debug for RootChange {
@@ -24,6 +39,10 @@ impl fmt::Debug for RootChange {
                 .field("fpath", path)
                 .field("type", &String::from("ChangeFile"))
                 .field("text", &text[0..min(text.len(), 55)].to_owned()),
+            RootChange::EditFile { path, changes } => s
+                .field("fpath", path)
+                .field("type", &String::from("EditFile"))
+                .field("changes", &changes.len()),
         };
         s.finish()
     }
@@ -50,6 +69,13 @@ impl AnalysisChange {
             .push(RootChange::ChangeFile { path: fname, text });
     }
 
+    pub fn edit_file(&mut self, fname: String, changes: Vec<TextDocumentContentChangeEvent>) {
+        self.tracked_files_changed.push(RootChange::EditFile {
+            path: fname,
+            changes,
+        });
+    }
+
     pub fn remove_file(&mut self, fname: String) {
         self.tracked_files_changed
             .push(RootChange::RemoveFile { path: fname })
@@ -59,3 +85,31 @@ impl AnalysisChange {
         self.config_changed = Some(config);
     }
 }
+
+/// Applies changes from the `textDocument/didChange` notification one after another,
+/// ranges of every change are relative to the text after the previous one.
+pub fn apply_text_changes(
+    text: &mut String,
+    changes: &[TextDocumentContentChangeEvent],
+) -> Result<()> {
+    for change in changes {
+        let range = match change.range {
+            Some(range) => range,
+            None => {
+                *text = change.text.clone();
+                continue;
+            }
+        };
+        let (start, end) = {
+            let file = File::new(text.as_str());
+            let start = file.offset(range.start);
+            let end = file.offset(range.end);
+            match (start, end) {
+                (Some(start), Some(end)) if start <= end => (start.to_usize(), end.to_usize()),
+                _ => anyhow::bail!("Invalid range of the change: {:?}", range),
+            }
+        };
+        text.replace_range(start..end, &change.text);
+    }
+    Ok(())
+}
@@ -0,0 +1,458 @@
+//! Quick fixes of the compiler errors: imports of the unbound modules and names,
+//! missing `acquires` annotations and removal of the unused `use` declarations.
+//! Address literals could be rewritten into hex or into the own address format of the dialect.
+
+use lang::compiler::address::AddressFormats;
+use lang::compiler::dialects::DialectName;
+
+use crate::inner::analysis::Analysis;
+use crate::inner::rename::FileEdit;
+use crate::inner::symbol_index::IndexedSymbol;
+use crate::inner::symbols::{
+    identifier_at, identifier_start, span_contains, FileSymbols, ItemKind, ItemPath, Span,
+    UseKind, UseMember, UseSymbol,
+};
+
+/// Compiler error of the file, `message` includes the messages of its related information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionDiagnostic {
+    pub span: Span,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeAction {
+    pub title: String,
+    /// Index of the fixed diagnostic.
+    pub diagnostic: Option<usize>,
+    pub edits: Vec<FileEdit>,
+}
+
+/// Fixes of the `diagnostics`, of the unused `use` declarations within `range`
+/// and conversions of the address literal at its start.
+pub fn code_actions(
+    analysis: &Analysis,
+    fpath: &str,
+    range: Span,
+    diagnostics: &[ActionDiagnostic],
+) -> Vec<CodeAction> {
+    let text = match analysis.db().available_files.get(fpath) {
+        Some(text) => text,
+        None => return vec![],
+    };
+    let symbols = match analysis.file_symbols(fpath) {
+        Some(symbols) => symbols,
+        None => return vec![],
+    };
+
+    let mut actions = vec![];
+    for (idx, diagnostic) in diagnostics.iter().enumerate() {
+        let message = &diagnostic.message;
+        let fixes = if message.contains("'acquires'") || message.contains("acquires list") {
+            add_acquires(text, &symbols, diagnostic)
+        } else if message.contains("Unbound module '") || message.contains("Unbound module: '") {
+            fix_module_address(analysis, fpath, text, diagnostic)
+        } else if [
+            "Unbound module alias",
+            "Unbound function",
+            "Unbound type",
+            "Unbound struct",
+        ]
+        .iter()
+        .any(|unbound| message.contains(unbound))
+        {
+            import_fixes(analysis, text, &symbols, diagnostic)
+        } else {
+            vec![]
+        };
+        actions.extend(fixes.into_iter().map(|(title, edits)| CodeAction {
+            title,
+            diagnostic: Some(idx),
+            edits,
+        }));
+    }
+    actions.extend(remove_unused_uses(text, &symbols, range, diagnostics));
+    let dialect = &analysis.db().config.dialect_name;
+    actions.extend(convert_address(fpath, text, range.0, dialect));
+    actions
+}
+
+type Fix = (String, Vec<FileEdit>);
+
+fn edit(fpath: &str, span: Span, new_text: String) -> FileEdit {
+    FileEdit {
+        fpath: fpath.to_owned(),
+        span,
+        new_text,
+    }
+}
+
+/// `use` of a module, `Module::name` or of a module member, `name`.
+fn import_fixes(
+    analysis: &Analysis,
+    text: &str,
+    symbols: &FileSymbols,
+    diagnostic: &ActionDiagnostic,
+) -> Vec<Fix> {
+    let name_span = match identifier_at(text, diagnostic.span.0) {
+        Some(span) => span,
+        None => return vec![],
+    };
+    let name = &text[name_span.0..name_span.1];
+    let fpath = &symbols.fpath;
+
+    if text[name_span.1..].starts_with("::") {
+        return import_candidates(analysis, name, ItemKind::Module)
+            .into_iter()
+            .filter_map(|module| {
+                let declaration = format!("use {};", module.qualified_name());
+                let edit = add_use(fpath, text, symbols, name_span.0, &declaration)?;
+                Some((format!("Import `{}`", module.qualified_name()), vec![edit]))
+            })
+            .collect();
+    }
+
+    let current_module = symbols.module_at(name_span.0).map(|module| module.path());
+    let mut fixes = vec![];
+    for member in import_candidates(analysis, name, ItemKind::Function)
+        .into_iter()
+        .chain(import_candidates(analysis, name, ItemKind::Struct))
+        .filter(|member| Some(&member.path) != current_module.as_ref())
+    {
+        let declaration = format!("use {};", member.qualified_name());
+        if let Some(edit) = add_use(fpath, text, symbols, name_span.0, &declaration) {
+            fixes.push((format!("Import `{}`", member.qualified_name()), vec![edit]));
+        }
+
+        // or the module, with the name qualified by its alias
+        let imported_alias = symbols
+            .uses_at(name_span.0)
+            .iter()
+            .filter(|use_| {
+                use_.address == member.path.address && use_.module == member.path.module
+            })
+            .find_map(|use_| use_.module_alias());
+        let qualified = |alias: &str| edit(fpath, name_span, format!("{}::{}", alias, name));
+        match imported_alias {
+            Some(alias) => fixes.push((
+                format!("Qualify as `{}::{}`", alias, name),
+                vec![qualified(alias)],
+            )),
+            None => {
+                let declaration = format!("use {};", member.container);
+                if let Some(edit) = add_use(fpath, text, symbols, name_span.0, &declaration) {
+                    let module = &member.path.module;
+                    fixes.push((
+                        format!(
+                            "Import `{}` and qualify as `{}::{}`",
+                            member.container, module, name
+                        ),
+                        vec![edit, qualified(module)],
+                    ));
+                }
+            }
+        }
+    }
+    fixes
+}
+
+/// Modules of the stdlib and the modules folders, public functions and structs of them.
+fn import_candidates(analysis: &Analysis, name: &str, kind: ItemKind) -> Vec<IndexedSymbol> {
+    let db = analysis.db();
+    db.symbol_index
+        .find(name)
+        .into_iter()
+        .filter(|symbol| symbol.kind == kind && db.is_dependency_file(&symbol.fpath))
+        .filter(|symbol| kind != ItemKind::Function || is_public_function(analysis, symbol))
+        .collect()
+}
+
+fn is_public_function(analysis: &Analysis, symbol: &IndexedSymbol) -> bool {
+    analysis
+        .file_symbols(&symbol.fpath)
+        .and_then(|symbols| {
+            let module = symbols.module(&symbol.path)?;
+            let function = module.functions.iter().find(|f| f.name == symbol.name)?;
+            Some(function.is_public)
+        })
+        .unwrap_or(false)
+}
+
+/// `0x0::Module` with the address of the known module of the same name.
+fn fix_module_address(
+    analysis: &Analysis,
+    fpath: &str,
+    text: &str,
+    diagnostic: &ActionDiagnostic,
+) -> Vec<Fix> {
+    let (start, end) = diagnostic.span;
+    let source = match text.get(start..end) {
+        Some(source) => source,
+        None => return vec![],
+    };
+    let message = &diagnostic.message;
+    let module_name = message[message.find("Unbound module").unwrap_or(0)..]
+        .split('\'')
+        .nth(1)
+        .and_then(|path| path.rsplit("::").next())
+        .unwrap_or_default();
+    let separator = match source.find(&format!("::{}", module_name)) {
+        Some(idx) if !module_name.is_empty() => start + idx,
+        _ => return vec![],
+    };
+    let address_span = (identifier_start(text, separator), separator);
+    let address = &text[address_span.0..address_span.1];
+
+    import_candidates(analysis, module_name, ItemKind::Module)
+        .into_iter()
+        .filter(|module| module.container != address)
+        .map(|module| {
+            (
+                format!("Change address to `{}`", module.container),
+                vec![edit(fpath, address_span, module.container.clone())],
+            )
+        })
+        .collect()
+}
+
+/// Edit, which adds the `declaration` after the `use`s of the module or script with `offset`.
+fn add_use(
+    fpath: &str,
+    text: &str,
+    symbols: &FileSymbols,
+    offset: usize,
+    declaration: &str,
+) -> Option<FileEdit> {
+    let (body_search_start, uses) = match symbols.module_at(offset) {
+        Some(module) => (module.name_span.1, &module.uses),
+        None => {
+            let script = symbols
+                .scripts
+                .iter()
+                .find(|script| span_contains(script.span, offset))?;
+            (script.span.0, &script.uses)
+        }
+    };
+    match uses.last() {
+        Some(last) => {
+            let (start, end) = last.span;
+            let new_text = format!("\n{}{}", line_indent(text, start), declaration);
+            Some(edit(fpath, (end, end), new_text))
+        }
+        None => {
+            let brace = body_search_start + text[body_search_start..].find('{')?;
+            let indent = format!("{}    ", line_indent(text, brace));
+            let new_text = format!("\n{}{}\n", indent, declaration);
+            Some(edit(fpath, (brace + 1, brace + 1), new_text))
+        }
+    }
+}
+
+fn line_indent(text: &str, offset: usize) -> &str {
+    let line_start = text[..offset].rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+    let line = &text[line_start..];
+    &line[..line.len()
+        - line
+            .trim_start_matches(|ch: char| ch == ' ' || ch == '\t')
+            .len()]
+}
+
+/// Adds the resources of the current module, which are acquired but not annotated.
+fn add_acquires(text: &str, symbols: &FileSymbols, diagnostic: &ActionDiagnostic) -> Vec<Fix> {
+    let offset = diagnostic.span.0;
+    let (module, function) = match (symbols.module_at(offset), symbols.function_at(offset)) {
+        (Some(module), Some(function)) => (module, function),
+        _ => return vec![],
+    };
+    let annotated: Vec<&str> = function
+        .acquires
+        .iter()
+        .map(|name| name.rsplit("::").next().unwrap_or(name))
+        .collect();
+    let resources: Vec<&str> = module
+        .structs
+        .iter()
+        .filter(|s| s.is_resource && !annotated.contains(&s.name.as_str()))
+        .map(|s| s.name.as_str())
+        .filter(|name| {
+            let message = &diagnostic.message;
+            message.contains(&format!("::{}'", name)) || message.contains(&format!("'{}'", name))
+        })
+        .collect();
+    if resources.is_empty() {
+        return vec![];
+    }
+
+    let body = match text[function.name_span.1..].find('{') {
+        Some(idx) => function.name_span.1 + idx,
+        None => return vec![],
+    };
+    let end = text[..body].trim_end().len();
+    let list = resources.join(", ");
+    let new_text = if function.acquires.is_empty() {
+        format!(" acquires {}", list)
+    } else {
+        format!(", {}", list)
+    };
+    vec![(
+        format!("Add `acquires {}` to `{}`", list, function.name),
+        vec![edit(&symbols.fpath, (end, end), new_text)],
+    )]
+}
+
+fn remove_unused_uses(
+    text: &str,
+    symbols: &FileSymbols,
+    range: Span,
+    diagnostics: &[ActionDiagnostic],
+) -> Vec<CodeAction> {
+    let containers = symbols
+        .modules
+        .iter()
+        .map(|module| (module.span, &module.uses))
+        .chain(
+            symbols
+                .scripts
+                .iter()
+                .map(|script| (script.span, &script.uses)),
+        );
+
+    let mut actions = vec![];
+    for (container_span, uses) in containers {
+        for use_ in uses.iter() {
+            let declaration = use_.span;
+            if range.1 < declaration.0 || declaration.1 < range.0 {
+                continue;
+            }
+            // usages of the name outside of the declaration itself
+            let is_used = |path: ItemPath, name: &str| {
+                symbols.references.iter().any(|reference| {
+                    reference.path == path
+                        && span_contains(container_span, reference.span.0)
+                        && !span_contains(declaration, reference.span.0)
+                        && &text[reference.span.0..reference.span.1] == name
+                })
+            };
+            let unused: Vec<&UseMember> = match &use_.kind {
+                UseKind::Module { .. } => {
+                    let path = ItemPath::module(use_.address, use_.module.clone());
+                    if is_used(path, use_.module_alias().unwrap_or_default()) {
+                        continue;
+                    }
+                    vec![]
+                }
+                UseKind::Members(members) => {
+                    let unused: Vec<_> = members
+                        .iter()
+                        .filter(|member| {
+                            let path = ItemPath::member(
+                                use_.address,
+                                use_.module.clone(),
+                                member.name.clone(),
+                            );
+                            !is_used(path, member.alias.as_deref().unwrap_or(&member.name))
+                        })
+                        .collect();
+                    if unused.is_empty() {
+                        continue;
+                    }
+                    if unused.len() < members.len() {
+                        unused
+                    } else {
+                        vec![]
+                    }
+                }
+            };
+
+            let diagnostic = diagnostics.iter().position(|diagnostic| {
+                diagnostic.message.contains("Unused")
+                    && span_contains(declaration, diagnostic.span.0)
+            });
+            let (title, edit) = if unused.is_empty() {
+                let source = &text[declaration.0..declaration.1 - 1];
+                (
+                    format!("Remove unused `{}`", source),
+                    remove_declaration(&symbols.fpath, text, declaration),
+                )
+            } else {
+                let names: Vec<_> = unused.iter().map(|member| member.name.as_str()).collect();
+                (
+                    format!("Remove unused `{}`", names.join("`, `")),
+                    without_members(&symbols.fpath, text, use_, &unused),
+                )
+            };
+            actions.push(CodeAction {
+                title,
+                diagnostic,
+                edits: vec![edit],
+            });
+        }
+    }
+    actions
+}
+
+/// Rewrites of the address literal at `offset` into hex and into the format of the dialect.
+fn convert_address(
+    fpath: &str,
+    text: &str,
+    offset: usize,
+    dialect: &DialectName,
+) -> Vec<CodeAction> {
+    let span = match identifier_at(text, offset) {
+        Some(span) => span,
+        None => return vec![],
+    };
+    let literal = &text[span.0..span.1];
+    let formats = match AddressFormats::of(literal) {
+        Some(formats) => formats,
+        None => return vec![],
+    };
+    formats
+        .of_dialect(dialect)
+        .into_iter()
+        .filter_map(|(format, address)| Some((format, address?)))
+        .filter(|(_, address)| *address != literal)
+        .map(|(format, address)| CodeAction {
+            title: format!("Convert to {} `{}`", format, address),
+            diagnostic: None,
+            edits: vec![edit(fpath, span, address.to_owned())],
+        })
+        .collect()
+}
+
+/// Removes the whole line, if there's nothing else on it.
+fn remove_declaration(fpath: &str, text: &str, (start, end): Span) -> FileEdit {
+    let line_start = text[..start].rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+    let rest = &text[end..];
+    let trailing = rest.len()
+        - rest
+            .trim_start_matches(|ch: char| ch == ' ' || ch == '\t')
+            .len();
+    if text[line_start..start].trim().is_empty() && rest[trailing..].starts_with('\n') {
+        edit(fpath, (line_start, end + trailing + 1), String::new())
+    } else {
+        edit(fpath, (start, end), String::new())
+    }
+}
+
+/// Declaration of the used members only.
+fn without_members(fpath: &str, text: &str, use_: &UseSymbol, unused: &[&UseMember]) -> FileEdit {
+    let members: Vec<String> = match &use_.kind {
+        UseKind::Members(members) => members
+            .iter()
+            .filter(|member| !unused.iter().any(|unused| unused.span == member.span))
+            .map(|member| match &member.alias {
+                Some(alias) => format!("{} as {}", member.name, alias),
+                None => member.name.clone(),
+            })
+            .collect(),
+        UseKind::Module { .. } => vec![],
+    };
+    let prefix = &text[use_.span.0..use_.module_span.1];
+    let new_text = if members.len() == 1 {
+        format!("{}::{};", prefix, members[0])
+    } else {
+        format!("{}::{{{}}};", prefix, members.join(", "))
+    };
+    edit(fpath, use_.span, new_text)
+}
@@ -0,0 +1,507 @@
+use std::sync::Arc;
+
+use libra::move_lang::shared::Address;
+
+use crate::inner::analysis::Analysis;
+use crate::inner::symbols::{
+    identifier_start, span_contains, FileSymbols, ItemPath, LocalSymbol, ModuleSymbol,
+    StructSymbol, UseKind, UseSymbol,
+};
+
+pub const KEYWORDS: &[&str] = &[
+    "abort", "acquires", "address", "as", "break", "const", "continue", "copy", "copyable",
+    "else", "false", "fun", "if", "let", "loop", "module", "move", "mut", "native", "public",
+    "resource", "return", "script", "spec", "struct", "true", "use", "while",
+];
+
+pub const BUILTIN_FUNCTIONS: &[&str] = &[
+    "assert",
+    "borrow_global",
+    "borrow_global_mut",
+    "exists",
+    "freeze",
+    "move_from",
+    "move_to",
+];
+
+pub const PRIMITIVE_TYPES: &[&str] =
+    &["address", "bool", "signer", "u128", "u64", "u8", "vector"];
+
+/// Inserted at the cursor to make the incomplete code parseable.
+const PLACEHOLDER: &str = "__completion_placeholder";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CompletionKind {
+    Module,
+    Struct,
+    Function,
+    Constant,
+    Field,
+    Local,
+    Keyword,
+    BuiltinFunction,
+    BuiltinType,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletionItem {
+    pub label: String,
+    pub kind: CompletionKind,
+    pub detail: Option<String>,
+}
+
+impl CompletionItem {
+    fn new(label: &str, kind: CompletionKind, detail: Option<String>) -> CompletionItem {
+        CompletionItem {
+            label: label.to_owned(),
+            kind,
+            detail,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum CompletionContext {
+    /// `0x1::|`
+    Modules(Address),
+    /// `Module::|` or `0x1::Module::|`
+    ModuleMembers {
+        address: Option<Address>,
+        module: String,
+    },
+    /// `a.b.|`
+    Fields(Vec<String>),
+    Scope,
+}
+
+pub fn completions(analysis: &Analysis, fpath: &str, offset: usize) -> Vec<CompletionItem> {
+    let text = match analysis.db().available_files.get(fpath) {
+        Some(text) if offset <= text.len() && text.is_char_boundary(offset) => text,
+        _ => return vec![],
+    };
+    let prefix_start = identifier_start(text, offset);
+    let prefix = &text[prefix_start..offset];
+    let context = completion_context(analysis, text, prefix_start);
+
+    let current_file = parse_incomplete(analysis, fpath, text, offset);
+    let parsed_files = analysis.parsed_files();
+    let modules = all_modules(&parsed_files, current_file.as_ref());
+    let completer = Completer {
+        analysis,
+        fpath,
+        offset,
+        current_file: current_file.as_ref(),
+        modules: &modules,
+    };
+
+    let mut items = match context {
+        CompletionContext::Modules(address) => completer.address_modules(address),
+        CompletionContext::ModuleMembers { address, module } => {
+            completer.module_members(address, &module)
+        }
+        CompletionContext::Fields(receiver) => completer.fields(&receiver),
+        CompletionContext::Scope => completer.scope(),
+    };
+    items.retain(|item| item.label.starts_with(prefix) && item.label != PLACEHOLDER);
+    items.sort_by(|a, b| (&a.label, a.kind).cmp(&(&b.label, b.kind)));
+    items.dedup_by(|a, b| a.label == b.label && a.kind == b.kind);
+    items
+}
+
+pub fn parse_address(analysis: &Analysis, address: &str) -> Option<Address> {
+    analysis
+        .db()
+        .config
+        .dialect()
+        .normalize_account_address(address)
+        .ok()
+        .map(|address| address.as_address())
+}
+
+/// Determines the context from the text before the identifier the cursor is at.
+fn completion_context(analysis: &Analysis, text: &str, prefix_start: usize) -> CompletionContext {
+    let before = &text[..prefix_start];
+    if before.ends_with("::") {
+        let qualifier_end = prefix_start - 2;
+        let qualifier_start = identifier_start(text, qualifier_end);
+        let qualifier = &text[qualifier_start..qualifier_end];
+        if qualifier.is_empty() {
+            return CompletionContext::Scope;
+        }
+        if let Some(address) = parse_address(analysis, qualifier) {
+            return CompletionContext::Modules(address);
+        }
+        let mut address = None;
+        if text[..qualifier_start].ends_with("::") {
+            let address_end = qualifier_start - 2;
+            let address_start = identifier_start(text, address_end);
+            address = parse_address(analysis, &text[address_start..address_end]);
+        }
+        return CompletionContext::ModuleMembers {
+            address,
+            module: qualifier.to_owned(),
+        };
+    }
+
+    let mut receiver = vec![];
+    let mut end = prefix_start;
+    while text[..end].ends_with('.') {
+        let name_end = end - 1;
+        let name_start = identifier_start(text, name_end);
+        let name = &text[name_start..name_end];
+        if name.is_empty() || name.starts_with(|ch: char| ch.is_ascii_digit()) {
+            break;
+        }
+        receiver.insert(0, name.to_owned());
+        end = name_start;
+    }
+    if receiver.is_empty() {
+        CompletionContext::Scope
+    } else {
+        CompletionContext::Fields(receiver)
+    }
+}
+
+/// Parses the file, falling back to the text with a placeholder expression at the cursor.
+fn parse_incomplete(
+    analysis: &Analysis,
+    fpath: &str,
+    text: &str,
+    offset: usize,
+) -> Option<FileSymbols> {
+    let config = &analysis.db().config;
+    if let Some(symbols) = FileSymbols::parse(config, fpath, text) {
+        return Some(symbols);
+    }
+    [PLACEHOLDER.to_owned(), format!("{};", PLACEHOLDER)]
+        .iter()
+        .find_map(|insertion| {
+            let patched = format!("{}{}{}", &text[..offset], insertion, &text[offset..]);
+            FileSymbols::parse(config, fpath, &patched)
+        })
+}
+
+/// Modules of the current file and of the other parsed files, borrowed from their symbols.
+fn all_modules<'a>(
+    parsed_files: &'a [Arc<FileSymbols>],
+    current_file: Option<&'a FileSymbols>,
+) -> Vec<&'a ModuleSymbol> {
+    let mut modules: Vec<&ModuleSymbol> = current_file
+        .map(|symbols| symbols.modules.iter().collect())
+        .unwrap_or_default();
+    for symbols in parsed_files {
+        if current_file.map_or(false, |current| current.fpath == symbols.fpath) {
+            continue;
+        }
+        modules.extend(symbols.modules.iter());
+    }
+    modules
+}
+
+struct Completer<'a> {
+    analysis: &'a Analysis,
+    fpath: &'a str,
+    offset: usize,
+    current_file: Option<&'a FileSymbols>,
+    modules: &'a [&'a ModuleSymbol],
+}
+
+impl<'a> Completer<'a> {
+    fn module(&self, path: &ItemPath) -> Option<&'a ModuleSymbol> {
+        self.modules
+            .iter()
+            .copied()
+            .find(|module| module.address == path.address && module.name == path.module)
+    }
+
+    fn current_module(&self) -> Option<&'a ModuleSymbol> {
+        self.current_file?.module_at(self.offset)
+    }
+
+    fn uses(&self) -> &'a [UseSymbol] {
+        self.current_file
+            .map(|symbols| symbols.uses_at(self.offset))
+            .unwrap_or_default()
+    }
+
+    fn address_modules(&self, address: Address) -> Vec<CompletionItem> {
+        self.modules
+            .iter()
+            .filter(|module| module.address == address)
+            .map(|module| {
+                let detail = format!("{}::{}", module.address, module.name);
+                CompletionItem::new(&module.name, CompletionKind::Module, Some(detail))
+            })
+            .collect()
+    }
+
+    fn module_members(&self, address: Option<Address>, module_name: &str) -> Vec<CompletionItem> {
+        let path = match address {
+            Some(address) => ItemPath::module(address, module_name.to_owned()),
+            None => match self.module_path_by_alias(module_name) {
+                Some(path) => path,
+                None => return vec![],
+            },
+        };
+        let module = match self.module(&path) {
+            Some(module) => module,
+            None => return vec![],
+        };
+        let is_inside = self
+            .current_module()
+            .map_or(false, |current| current.path() == path);
+
+        let mut items = vec![];
+        for function in &module.functions {
+            if function.is_public || is_inside {
+                items.push(CompletionItem::new(
+                    &function.name,
+                    CompletionKind::Function,
+                    Some(function.signature()),
+                ));
+            }
+        }
+        for s in &module.structs {
+            items.push(CompletionItem::new(
+                &s.name,
+                CompletionKind::Struct,
+                Some(s.signature()),
+            ));
+        }
+        if is_inside {
+            for constant in &module.constants {
+                items.push(CompletionItem::new(
+                    &constant.name,
+                    CompletionKind::Constant,
+                    Some(constant.signature()),
+                ));
+            }
+        }
+        items
+    }
+
+    fn module_path_by_alias(&self, alias: &str) -> Option<ItemPath> {
+        let imported = self
+            .uses()
+            .iter()
+            .find(|use_| use_.module_alias() == Some(alias))
+            .map(|use_| ItemPath::module(use_.address, use_.module.clone()));
+        if imported.is_some() {
+            return imported;
+        }
+        self.current_module()
+            .filter(|module| module.name == alias)
+            .map(ModuleSymbol::path)
+    }
+
+    fn fields(&self, receiver: &[String]) -> Vec<CompletionItem> {
+        let (first, rest) = match receiver.split_first() {
+            Some(split) => split,
+            None => return vec![],
+        };
+        let ty = match self.local_type(first) {
+            Some(ty) => ty,
+            None => return vec![],
+        };
+        let mut resolved = self.resolve_struct(self.uses(), self.current_module(), &ty);
+        for field_name in rest {
+            resolved = resolved.and_then(|(module, s)| {
+                let field = s.fields.iter().find(|field| &field.name == field_name)?;
+                self.resolve_struct(&module.uses, Some(module), &field.ty)
+            });
+        }
+        match resolved {
+            Some((_, s)) => s
+                .fields
+                .iter()
+                .map(|field| {
+                    CompletionItem::new(
+                        &field.name,
+                        CompletionKind::Field,
+                        Some(field.ty.clone()),
+                    )
+                })
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    fn locals(&self) -> Vec<&'a LocalSymbol> {
+        let function = match self.current_file.and_then(|f| f.function_at(self.offset)) {
+            Some(function) => function,
+            None => return vec![],
+        };
+        let mut locals: Vec<&LocalSymbol> = vec![];
+        for local in &function.locals {
+            if local.name_span.1 > self.offset {
+                break;
+            }
+            locals.retain(|declared| declared.name != local.name);
+            locals.push(local);
+        }
+        locals
+    }
+
+    /// Declared type of the local, or the type inferred by the last successful check.
+    fn local_type(&self, name: &str) -> Option<String> {
+        if let Some(local) = self.locals().into_iter().find(|local| local.name == name) {
+            if local.ty.is_some() {
+                return local.ty.clone();
+            }
+        }
+        let scope = self
+            .current_file
+            .and_then(|symbols| symbols.function_at(self.offset))
+            .map(|function| function.span)
+            .unwrap_or((0, self.offset));
+        self.analysis
+            .db()
+            .file_types
+            .get(self.fpath)?
+            .local_at(name, self.offset, scope)
+            .map(|local| local.ty.clone())
+    }
+
+    /// Finds the struct definition of the type `ty`, names are resolved within `uses` and `module`.
+    fn resolve_struct(
+        &self,
+        uses: &[UseSymbol],
+        module: Option<&ModuleSymbol>,
+        ty: &str,
+    ) -> Option<(&'a ModuleSymbol, &'a StructSymbol)> {
+        let name = ty
+            .trim_start_matches('&')
+            .trim_start_matches("mut ")
+            .split('<')
+            .next()?
+            .trim();
+        let segments: Vec<&str> = name.split("::").collect();
+        let path = match segments.as_slice() {
+            [struct_name] => uses
+                .iter()
+                .find_map(|use_| {
+                    use_.member_by_alias(struct_name).map(|member| {
+                        ItemPath::member(use_.address, use_.module.clone(), member.to_owned())
+                    })
+                })
+                .or_else(|| {
+                    module.map(|module| {
+                        ItemPath::member(
+                            module.address,
+                            module.name.clone(),
+                            (*struct_name).to_owned(),
+                        )
+                    })
+                })?,
+            [module_name, struct_name] => uses
+                .iter()
+                .find(|use_| use_.module_alias() == Some(*module_name))
+                .map(|use_| (use_.address, use_.module.clone()))
+                .or_else(|| {
+                    // inferred types contain module names instead of aliases
+                    self.modules
+                        .iter()
+                        .find(|module| module.name == *module_name)
+                        .map(|module| (module.address, module.name.clone()))
+                })
+                .map(|(address, module)| {
+                    ItemPath::member(address, module, (*struct_name).to_owned())
+                })?,
+            [address, module_name, struct_name] => ItemPath::member(
+                parse_address(self.analysis, address)?,
+                (*module_name).to_owned(),
+                (*struct_name).to_owned(),
+            ),
+            _ => return None,
+        };
+        let module = self.module(&path)?;
+        let member = path.member.as_ref()?;
+        let s = module.structs.iter().find(|s| &s.name == member)?;
+        Some((module, s))
+    }
+
+    fn scope(&self) -> Vec<CompletionItem> {
+        let mut items: Vec<CompletionItem> = self
+            .locals()
+            .into_iter()
+            .map(|local| {
+                CompletionItem::new(&local.name, CompletionKind::Local, local.ty.clone())
+            })
+            .collect();
+
+        if let Some(module) = self.current_module() {
+            items.extend(module.functions.iter().map(|f| {
+                CompletionItem::new(&f.name, CompletionKind::Function, Some(f.signature()))
+            }));
+            items.extend(module.structs.iter().map(|s| {
+                CompletionItem::new(&s.name, CompletionKind::Struct, Some(s.signature()))
+            }));
+            items.extend(module.constants.iter().map(|c| {
+                CompletionItem::new(&c.name, CompletionKind::Constant, Some(c.signature()))
+            }));
+        } else if let Some(script) = self.current_file.and_then(|symbols| {
+            symbols
+                .scripts
+                .iter()
+                .find(|script| span_contains(script.span, self.offset))
+        }) {
+            items.extend(script.constants.iter().map(|c| {
+                CompletionItem::new(&c.name, CompletionKind::Constant, Some(c.signature()))
+            }));
+        }
+
+        for use_ in self.uses() {
+            items.extend(self.imported_items(use_));
+        }
+
+        items.extend(
+            KEYWORDS
+                .iter()
+                .map(|keyword| CompletionItem::new(keyword, CompletionKind::Keyword, None)),
+        );
+        items.extend(
+            BUILTIN_FUNCTIONS
+                .iter()
+                .map(|name| CompletionItem::new(name, CompletionKind::BuiltinFunction, None)),
+        );
+        items.extend(
+            PRIMITIVE_TYPES
+                .iter()
+                .map(|name| CompletionItem::new(name, CompletionKind::BuiltinType, None)),
+        );
+        items
+    }
+
+    fn imported_items(&self, use_: &UseSymbol) -> Vec<CompletionItem> {
+        let module_path = ItemPath::module(use_.address, use_.module.clone());
+        let members = match &use_.kind {
+            UseKind::Module { .. } => {
+                let alias = use_.module_alias().unwrap_or(&use_.module);
+                let detail = format!("{}::{}", use_.address, use_.module);
+                return vec![CompletionItem::new(
+                    alias,
+                    CompletionKind::Module,
+                    Some(detail),
+                )];
+            }
+            UseKind::Members(members) => members,
+        };
+        let module = self.module(&module_path);
+        members
+            .iter()
+            .map(|member| {
+                let alias = member.alias.as_deref().unwrap_or(&member.name);
+                let s = module.and_then(|m| m.structs.iter().find(|s| s.name == member.name));
+                if let Some(s) = s {
+                    return CompletionItem::new(
+                        alias,
+                        CompletionKind::Struct,
+                        Some(s.signature()),
+                    );
+                }
+                let f = module.and_then(|m| m.functions.iter().find(|f| f.name == member.name));
+                CompletionItem::new(alias, CompletionKind::Function, f.map(|f| f.signature()))
+            })
+            .collect()
+    }
+}
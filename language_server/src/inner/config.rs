@@ -1,4 +1,5 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use core::fmt;
 use serde::export::fmt::Debug;
@@ -7,6 +8,10 @@ use serde::Deserialize;
 use lang::compiler::dialects::{DialectName, Dialect};
 use lang::compiler::address::ProvidedAccountAddress;
 use lang::compiler::file::find_move_files;
+use dove::manifest::{read_manifest, MANIFEST};
+
+/// Number of threads to check the files and to serve the requests with, if not specified.
+pub const DEFAULT_WORKER_THREADS: usize = 2;
 
 #[derive(Clone)]
 pub struct Config {
@@ -14,6 +19,17 @@ pub struct Config {
     pub stdlib_folder: Option<PathBuf>,
     pub modules_folders: Vec<PathBuf>,
     pub sender_address: ProvidedAccountAddress,
+    /// Workspace root, where `Dove.toml` is looked for.
+    pub root_path: Option<PathBuf>,
+    /// Dependencies fetched by `dove` into `target/.external`.
+    pub external_deps_folder: Option<PathBuf>,
+    /// Tests of the `dove` project, each file is a pipeline of scripts.
+    pub tests_folder: Option<PathBuf>,
+    /// Node to load the on-chain modules from.
+    pub blockchain_api: Option<String>,
+    pub worker_threads: usize,
+    /// Whether the client shows the `window/workDoneProgress` of the server.
+    pub work_done_progress: bool,
 }
 
 impl Debug for Config {
@@ -23,6 +39,12 @@ impl Debug for Config {
             .field("stdlib_folder", &self.stdlib_folder)
             .field("module_folders", &self.modules_folders)
             .field("sender_address", &self.sender_address)
+            .field("root_path", &self.root_path)
+            .field("external_deps_folder", &self.external_deps_folder)
+            .field("tests_folder", &self.tests_folder)
+            .field("blockchain_api", &self.blockchain_api)
+            .field("worker_threads", &self.worker_threads)
+            .field("work_done_progress", &self.work_done_progress)
             .finish()
     }
 }
@@ -37,6 +59,12 @@ impl Default for Config {
                 .get_dialect()
                 .normalize_account_address("0x1")
                 .unwrap(),
+            root_path: None,
+            external_deps_folder: None,
+            tests_folder: None,
+            blockchain_api: None,
+            worker_threads: DEFAULT_WORKER_THREADS,
+            work_done_progress: false,
         }
     }
 }
@@ -68,6 +96,76 @@ impl Config {
         &self.sender_address
     }
 
+    /// Files of the dependency folders, which must not be modified by the server.
+    pub fn is_read_only(&self, fpath: &str) -> bool {
+        self.stdlib_folder
+            .iter()
+            .chain(self.external_deps_folder.iter())
+            .any(|folder| Path::new(fpath).starts_with(folder))
+    }
+
+    pub fn is_test_file(&self, fpath: &str) -> bool {
+        self.tests_folder
+            .as_ref()
+            .map_or(false, |folder| Path::new(fpath).starts_with(folder))
+    }
+
+    /// Takes the dialect, the sender address and the module folders from the `Dove.toml`
+    /// of the workspace root, if there's one. Returns whether the sender address is taken from it.
+    pub fn load_manifest(&mut self) -> bool {
+        let root_path = match &self.root_path {
+            Some(root_path) => root_path.clone(),
+            None => return false,
+        };
+        let manifest_path = root_path.join(MANIFEST);
+        if !manifest_path.exists() {
+            return false;
+        }
+        let manifest = match read_manifest(&manifest_path) {
+            Ok(manifest) => manifest,
+            Err(error) => {
+                log::error!("Cannot read {:?}: {:?}", manifest_path, error);
+                return false;
+            }
+        };
+        log::info!("Loaded {:?} = {:#?}", manifest_path, manifest);
+
+        if let Some(dialect) = &manifest.package.dialect {
+            match DialectName::from_str(dialect) {
+                Ok(dialect_name) => self.dialect_name = dialect_name,
+                Err(error) => log::error!("Invalid dialect in {:?}: {:?}", manifest_path, error),
+            }
+        }
+        let mut has_sender = false;
+        if let Some(address) = &manifest.package.account_address {
+            match self.dialect().normalize_account_address(address) {
+                Ok(provided_address) => {
+                    self.sender_address = provided_address;
+                    has_sender = true;
+                }
+                Err(error) => {
+                    log::error!(
+                        "Invalid account_address in {:?}: {:?}",
+                        manifest_path,
+                        error
+                    )
+                }
+            }
+        }
+
+        let module_dir = root_path.join(&manifest.layout.module_dir);
+        let external_deps_folder = root_path.join(&manifest.layout.target_deps);
+        for folder in &[module_dir, external_deps_folder.clone()] {
+            if folder.exists() && !self.modules_folders.contains(folder) {
+                self.modules_folders.push(folder.clone());
+            }
+        }
+        self.external_deps_folder = Some(external_deps_folder);
+        self.tests_folder = Some(root_path.join(&manifest.layout.tests_dir));
+        self.blockchain_api = manifest.package.blockchain_api.clone();
+        has_sender
+    }
+
     pub fn update(&mut self, value: &serde_json::Value) {
         log::info!("Passed configuration = {:#}", value);
 
@@ -106,20 +204,30 @@ impl Config {
                 })
                 .collect(),
         };
-        self.sender_address = match get(value, "/sender_address") {
-            None => {
-                log::info!("Using default account address 0x0");
-                ProvidedAccountAddress::default()
-            }
-            Some(address) => match self.dialect().normalize_account_address(address) {
-                Ok(provided_address) => provided_address,
-                Err(error) => {
-                    log::error!("Invalid sender_address string: {:?}", error);
+        self.worker_threads = get::<usize>(value, "/worker_threads")
+            .filter(|threads| *threads > 0)
+            .unwrap_or(DEFAULT_WORKER_THREADS);
+
+        self.external_deps_folder = None;
+        self.tests_folder = None;
+        self.blockchain_api = None;
+        if !self.load_manifest() {
+            // normalized with the dialect of the manifest
+            self.sender_address = match get(value, "/sender_address") {
+                None => {
                     log::info!("Using default account address 0x0");
                     ProvidedAccountAddress::default()
                 }
-            },
-        };
+                Some(address) => match self.dialect().normalize_account_address(address) {
+                    Ok(provided_address) => provided_address,
+                    Err(error) => {
+                        log::error!("Invalid sender_address string: {:?}", error);
+                        log::info!("Using default account address 0x0");
+                        ProvidedAccountAddress::default()
+                    }
+                },
+            };
+        }
 
         log::info!("Config updated to = {:#?}", self);
         self.log_available_module_files();
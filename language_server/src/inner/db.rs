@@ -1,42 +1,48 @@
 use anyhow::Result;
-use lsp_types::{Diagnostic, DiagnosticRelatedInformation, Location, Range, Url};
+use lsp_types::{Diagnostic, DiagnosticRelatedInformation, Location, Position, Range, Url};
 use libra::move_lang::errors::Error;
 use libra::move_ir_types::location::Loc;
+use codespan::ByteIndex;
+use libra::move_lang::name_pool::ConstPool;
 
 use serde::export::fmt::Debug;
 use serde::export::Formatter;
 use std::fmt;
+use std::mem;
 use crate::inner::config::Config;
-use crate::inner::change::{AnalysisChange, RootChange};
-use std::collections::HashMap;
+use crate::inner::change::{self, AnalysisChange, RootChange};
+use crate::inner::decompiled::DecompiledCache;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use lang::compiler::location::File;
+use crate::inner::symbol_index::SymbolIndex;
+use crate::inner::symbols::{FileSymbols, ItemPath, Span};
+use crate::inner::typing::FileTypes;
 
 pub struct FileDiagnostic {
     pub fpath: String,
-    pub diagnostic: Option<Diagnostic>,
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 impl Debug for FileDiagnostic {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        let mut debug_struct = f.debug_struct("FileDiagnostic");
-
-        debug_struct.field("fpath", &self.fpath.to_string());
-        if self.diagnostic.is_some() {
-            let Diagnostic { range, message, .. } = self.diagnostic.as_ref().unwrap();
-            debug_struct
-                .field(
-                    "range",
-                    &format!(
-                        "({}, {}) -> ({}, {})",
-                        range.start.line,
-                        range.start.character,
-                        range.end.line,
-                        range.end.character,
-                    ),
-                )
-                .field("message", message);
-        }
-        debug_struct.finish()
+        let mut debug_list = f.debug_list();
+        for Diagnostic { range, message, .. } in &self.diagnostics {
+            debug_list.entry(&format!(
+                "{}: ({}, {}) -> ({}, {}): {}",
+                self.fpath,
+                range.start.line,
+                range.start.character,
+                range.end.line,
+                range.end.character,
+                message
+            ));
+        }
+        if self.diagnostics.is_empty() {
+            debug_list.entry(&format!("{}: no diagnostics", self.fpath));
+        }
+        debug_list.finish()
     }
 }
 
@@ -44,27 +50,81 @@ impl FileDiagnostic {
     pub fn new(fpath: String, diagnostic: Diagnostic) -> FileDiagnostic {
         FileDiagnostic {
             fpath,
-            diagnostic: Some(diagnostic),
+            diagnostics: vec![diagnostic],
         }
     }
 
     pub fn new_empty(fpath: &str) -> FileDiagnostic {
         FileDiagnostic {
             fpath: fpath.to_owned(),
-            diagnostic: None,
+            diagnostics: vec![],
         }
     }
 }
 
+/// Merges diagnostics of the same file, preserving the order of first appearance of files.
+/// Duplicate diagnostics (e.g. an error in the dependency reported by every dependent) are removed.
+pub fn group_by_file(
+    file_diags: impl IntoIterator<Item = FileDiagnostic>,
+) -> Vec<FileDiagnostic> {
+    let mut grouped: Vec<FileDiagnostic> = vec![];
+    for file_diag in file_diags {
+        let group = match grouped.iter_mut().find(|g| g.fpath == file_diag.fpath) {
+            Some(group) => group,
+            None => {
+                grouped.push(FileDiagnostic::new_empty(&file_diag.fpath));
+                grouped.last_mut().unwrap()
+            }
+        };
+        for diagnostic in file_diag.diagnostics {
+            if !group.diagnostics.contains(&diagnostic) {
+                group.diagnostics.push(diagnostic);
+            }
+        }
+    }
+    grouped
+}
+
 pub struct FilePosition {
     pub fpath: &'static str,
     pub pos: (usize, usize),
 }
 
+/// Parse results of the file, recomputed only when its text changes.
+#[derive(Debug, Default, Clone)]
+pub struct ParsedFile {
+    /// Symbols of the current text, `None` if it contains syntax errors.
+    pub symbols: Option<Arc<FileSymbols>>,
+    /// Symbols of the last version without syntax errors, used to track module dependencies.
+    pub last_valid: Option<Arc<FileSymbols>>,
+}
+
+/// Number of the change applied to the database.
+pub type Revision = u64;
+
+/// Revisions are unique across all the databases, so that the results computed
+/// for a database replaced on the config change are outdated too.
+static LAST_REVISION: AtomicU64 = AtomicU64::new(0);
+
+/// Texts, parse results and types are shared behind `Arc`s, so that the database cloned
+/// on a change, while its snapshots are still in use, doesn't copy them.
 #[derive(Debug, Default, Clone)]
 pub struct RootDatabase {
     pub config: Config,
-    pub available_files: HashMap<String, String>,
+    pub available_files: HashMap<String, Arc<str>>,
+    pub parsed_files: HashMap<String, ParsedFile>,
+    /// Types from the last successful check of the file.
+    pub file_types: HashMap<String, Arc<FileTypes>>,
+    pub symbol_index: SymbolIndex,
+    /// Symbols of the `.mv` files of the modules folders, parsed with the `config`.
+    pub decompiled: DecompiledCache,
+    pub revision: Revision,
+    /// Last revision, which could affect the diagnostics of the file.
+    file_revisions: HashMap<String, Revision>,
+    /// Revision of the last change of the file text.
+    text_revisions: HashMap<String, Revision>,
+    /// Errors of the edits which could not be applied, the texts of their files are kept.
+    edit_errors: Vec<String>,
 }
 
 impl RootDatabase {
@@ -72,57 +132,354 @@ impl RootDatabase {
         RootDatabase {
             config,
             available_files: Default::default(),
+            parsed_files: Default::default(),
+            file_types: Default::default(),
+            symbol_index: Default::default(),
+            decompiled: Default::default(),
+            revision: 0,
+            file_revisions: Default::default(),
+            text_revisions: Default::default(),
+            edit_errors: vec![],
         }
     }
 
+    /// Whether the file is changed since the `revision`, together with its dependencies.
+    pub fn is_outdated(&self, fpath: &str, revision: Revision) -> bool {
+        self.file_revisions
+            .get(fpath)
+            .map_or(false, |file_revision| *file_revision > revision)
+    }
+
     pub fn module_files(&self) -> HashMap<String, String> {
         self.available_files
-            .clone()
-            .into_iter()
+            .iter()
             .filter(|(f, _)| self.is_fpath_for_a_module(f))
+            .map(|(f, text)| (f.clone(), text.to_string()))
             .collect()
     }
 
-    pub fn apply_change(&mut self, change: AnalysisChange) {
+    /// Errors of the edits rejected since the last call.
+    pub fn take_edit_errors(&mut self) -> Vec<String> {
+        mem::take(&mut self.edit_errors)
+    }
+
+    /// Applies the change and returns the files, whose diagnostics could be affected by it.
+    pub fn apply_change(&mut self, change: AnalysisChange) -> HashSet<String> {
+        self.revision = LAST_REVISION.fetch_add(1, Ordering::SeqCst) + 1;
+        let affected_files = self.apply_change_to_files(change);
+        for fpath in &affected_files {
+            self.file_revisions.insert(fpath.clone(), self.revision);
+        }
+        affected_files
+    }
+
+    fn apply_change_to_files(&mut self, change: AnalysisChange) -> HashSet<String> {
+        let config_changed = change.config_changed.is_some();
         if let Some(config) = change.config_changed {
             self.config = config;
+            self.decompiled = DecompiledCache::default();
         }
+        let mut touched_files = HashSet::new();
+        // previous versions of the files with changed texts, `None` for the new files
+        let mut old_files = HashMap::new();
         for root_change in change.tracked_files_changed {
             match root_change {
-                RootChange::AddFile { path, text } => {
-                    self.available_files.insert(path, text);
+                RootChange::AddFile { path, text } | RootChange::ChangeFile { path, text } => {
+                    touched_files.insert(path.clone());
+                    self.set_file_text(path, text, &mut old_files);
                 }
-                RootChange::ChangeFile { path, text } => {
-                    self.available_files.insert(path, text);
+                RootChange::EditFile { path, changes } => {
+                    let mut text = match self.available_files.get(&path) {
+                        Some(text) => text.to_string(),
+                        None => {
+                            log::warn!("EditFile: file {:?} does not exist", path);
+                            continue;
+                        }
+                    };
+                    if let Err(err) = change::apply_text_changes(&mut text, &changes) {
+                        log::error!("EditFile: cannot edit file {:?}: {:?}", path, err);
+                        self.edit_errors
+                            .push(format!("Cannot edit file {:?}: {}", path, err));
+                        continue;
+                    }
+                    touched_files.insert(path.clone());
+                    self.set_file_text(path, text, &mut old_files);
                 }
                 RootChange::RemoveFile { path } => {
                     if !self.available_files.contains_key(&path) {
                         log::warn!("RemoveFile: file {:?} does not exist", path);
+                        continue;
                     }
+                    let parsed = self.parsed_files.remove(&path);
+                    old_files
+                        .entry(path.clone())
+                        .or_insert_with(|| Some(parsed.unwrap_or_default()));
                     self.available_files.remove(&path);
+                    self.file_types.remove(&path);
+                    self.text_revisions.remove(&path);
+                    self.symbol_index.remove_file(&path);
+                }
+            }
+        }
+        if config_changed {
+            // addresses are parsed and formatted according to the dialect
+            self.reparse_all();
+            let all_files: HashSet<String> = self.available_files.keys().cloned().collect();
+            self.resolve_imported_kinds(&all_files);
+            return all_files;
+        }
+        let mut affected_files = self.dependent_files(old_files);
+        affected_files.extend(
+            touched_files
+                .into_iter()
+                .filter(|fpath| self.available_files.contains_key(fpath)),
+        );
+        // files importing the changed modules are among the affected ones
+        self.resolve_imported_kinds(&affected_files);
+        affected_files
+    }
+
+    /// Kinds of the imported members are taken from the symbol index, as the importing file
+    /// could be parsed before the imported module.
+    fn resolve_imported_kinds(&mut self, fpaths: &HashSet<String>) {
+        let symbol_index = &self.symbol_index;
+        for fpath in fpaths {
+            let file = match self.parsed_files.get_mut(fpath) {
+                Some(file) => file,
+                None => continue,
+            };
+            let resolved = file.symbols.as_ref().and_then(|symbols| {
+                symbols.resolve_imported_kinds(|path| symbol_index.member_kind(path))
+            });
+            if let Some(resolved) = resolved {
+                file.symbols = Some(Arc::new(resolved));
+            }
+        }
+    }
+
+    /// Parse results are reused if the text is not changed.
+    fn set_file_text(
+        &mut self,
+        fpath: String,
+        text: String,
+        old_files: &mut HashMap<String, Option<ParsedFile>>,
+    ) {
+        if self.available_files.get(&fpath).map(|old_text| &**old_text) == Some(text.as_str()) {
+            return;
+        }
+        let old_file = self
+            .available_files
+            .get(&fpath)
+            .map(|_| self.parsed_files.get(&fpath).cloned().unwrap_or_default());
+        old_files.entry(fpath.clone()).or_insert(old_file);
+
+        self.parse_file(&fpath, &text);
+        self.text_revisions.insert(fpath.clone(), self.revision);
+        self.available_files.insert(fpath, text.into());
+    }
+
+    /// Types of the file, `None` if its text is changed since the check they are inferred by,
+    /// as the spans of the locals are shifted.
+    pub fn current_file_types(&self, fpath: &str) -> Option<&FileTypes> {
+        let types = self.file_types.get(fpath)?;
+        let text_revision = self.text_revisions.get(fpath).copied().unwrap_or_default();
+        if types.revision >= text_revision {
+            Some(&**types)
+        } else {
+            None
+        }
+    }
+
+    fn parse_file(&mut self, fpath: &str, text: &str) {
+        let _pool = ConstPool::new();
+
+        let symbols = FileSymbols::parse(&self.config, fpath, text).map(Arc::new);
+        let parsed = self.parsed_files.entry(fpath.to_owned()).or_default();
+        match &symbols {
+            Some(symbols) => {
+                self.symbol_index.update_file(&self.config, symbols);
+                parsed.last_valid = Some(symbols.clone());
+            }
+            None => log::info!(
+                "Cannot parse {:?}, keeping symbols of the last version",
+                fpath
+            ),
+        }
+        parsed.symbols = symbols;
+    }
+
+    fn reparse_all(&mut self) {
+        self.parsed_files.clear();
+        self.symbol_index.clear();
+        let files: Vec<(String, Arc<str>)> = self
+            .available_files
+            .iter()
+            .map(|(fpath, text)| (fpath.clone(), text.clone()))
+            .collect();
+        for (fpath, text) in files {
+            self.parse_file(&fpath, &text);
+        }
+    }
+
+    /// Changed files and the files which use modules of the changed files, transitively.
+    fn dependent_files(&self, old_files: HashMap<String, Option<ParsedFile>>) -> HashSet<String> {
+        let mut changed_modules = HashSet::new();
+        for (fpath, old_file) in &old_files {
+            let old_file = old_file.as_ref();
+            let new_file = self.parsed_files.get(fpath);
+            let never_parsed =
+                |file: Option<&ParsedFile>| file.map_or(false, |file| file.last_valid.is_none());
+            if self.is_dependency_file(fpath)
+                && (never_parsed(old_file) || never_parsed(new_file))
+            {
+                // modules of such a file are unknown, so it is a dependency of every file
+                return self.available_files.keys().cloned().collect();
+            }
+            let modules = old_file
+                .into_iter()
+                .chain(new_file)
+                .filter_map(|file| file.last_valid.as_ref())
+                .flat_map(|symbols| symbols.defined_modules());
+            changed_modules.extend(modules);
+        }
+
+        let mut affected: HashSet<String> = old_files
+            .into_iter()
+            .map(|(fpath, _)| fpath)
+            .filter(|fpath| self.available_files.contains_key(fpath))
+            .collect();
+        loop {
+            let dependents: Vec<&Arc<FileSymbols>> = self
+                .parsed_files
+                .iter()
+                .filter(|(fpath, _)| !affected.contains(fpath.as_str()))
+                .filter_map(|(_, file)| file.last_valid.as_ref())
+                .filter(|symbols| {
+                    symbols
+                        .used_modules()
+                        .iter()
+                        .any(|module| changed_modules.contains(module))
+                })
+                .collect();
+            if dependents.is_empty() {
+                break;
+            }
+            for symbols in dependents {
+                changed_modules.extend(symbols.defined_modules());
+                affected.insert(symbols.fpath.clone());
+            }
+        }
+        affected
+    }
+
+    /// Symbols of the current text of the file, `None` if it cannot be parsed.
+    pub fn file_symbols(&self, fpath: &str) -> Option<Arc<FileSymbols>> {
+        match self.parsed_files.get(fpath) {
+            Some(file) => file.symbols.clone(),
+            None => {
+                let text = self.available_files.get(fpath)?;
+                FileSymbols::parse(&self.config, fpath, text).map(Arc::new)
+            }
+        }
+    }
+
+    /// Dependency files required to compile the `text` of the file `fpath`:
+    /// files defining the used modules, transitively, and the dependency files
+    /// without a single parsed version, as their modules are unknown.
+    pub fn dependencies(&self, fpath: &str, text: &str) -> Vec<String> {
+        let symbols = if self.available_files.get(fpath).map(|file| &**file) == Some(text) {
+            self.file_symbols(fpath)
+        } else {
+            FileSymbols::parse(&self.config, fpath, text).map(Arc::new)
+        };
+        let symbols = symbols.or_else(|| {
+            self.parsed_files
+                .get(fpath)
+                .and_then(|file| file.last_valid.clone())
+        });
+
+        let mut deps = vec![];
+        let mut module_files: HashMap<ItemPath, Vec<&str>> = HashMap::new();
+        for dep_fpath in self.available_files.keys() {
+            if dep_fpath == fpath || !self.is_dependency_file(dep_fpath) {
+                continue;
+            }
+            match self.last_valid_symbols(dep_fpath) {
+                Some(dep_symbols) => {
+                    for module in dep_symbols.defined_modules() {
+                        module_files.entry(module).or_default().push(dep_fpath);
+                    }
                 }
+                None => deps.push(dep_fpath.clone()),
             }
         }
+
+        let mut queue: Vec<ItemPath> = symbols
+            .map(|symbols| symbols.used_modules().into_iter().collect())
+            .unwrap_or_default();
+        let mut visited = HashSet::new();
+        while let Some(module) = queue.pop() {
+            if !visited.insert(module.clone()) {
+                continue;
+            }
+            for dep_fpath in module_files.get(&module).into_iter().flatten() {
+                if deps.iter().any(|dep| dep.as_str() == *dep_fpath) {
+                    continue;
+                }
+                deps.push(dep_fpath.to_string());
+                if let Some(dep_symbols) = self.last_valid_symbols(dep_fpath) {
+                    queue.extend(dep_symbols.used_modules());
+                }
+            }
+        }
+        deps
+    }
+
+    /// Symbols of the last version of the file without syntax errors.
+    pub fn last_valid_symbols(&self, fpath: &str) -> Option<Arc<FileSymbols>> {
+        match self.parsed_files.get(fpath) {
+            Some(file) => file.last_valid.clone(),
+            // the database is filled without parsing, in tests
+            None => self.file_symbols(fpath),
+        }
     }
 
     fn loc_to_range(&self, loc: &Loc) -> Result<Range> {
-        let file = loc.file();
-        let text = match self.available_files.get(file) {
+        let span = loc.span();
+        self.span_to_range(loc.file(), (span.start().to_usize(), span.end().to_usize()))
+    }
+
+    pub fn span_to_range(&self, fpath: &str, span: Span) -> Result<Range> {
+        let text = match self.available_files.get(fpath) {
             Some(text) => text.clone(),
             None => {
                 anyhow::bail!(
                     "File {:?} is not present in the available files {:#?}",
-                    file,
+                    fpath,
                     &self.available_files.keys()
                 );
             }
         };
         let file = File::new(text);
-        let start_pos = file.position(loc.span().start())?;
-        let end_pos = file.position(loc.span().end())?;
+        let start_pos = file.position(ByteIndex(span.0 as u32))?;
+        let end_pos = file.position(ByteIndex(span.1 as u32))?;
         Ok(Range::new(start_pos, end_pos))
     }
 
+    pub fn span_to_location(&self, fpath: &str, span: Span) -> Result<Location> {
+        let range = self.span_to_range(fpath, span)?;
+        let uri = Url::from_file_path(fpath)
+            .map_err(|_| anyhow::anyhow!("Cannot build Url from path {:?}", fpath))?;
+        Ok(Location::new(uri, range))
+    }
+
+    pub fn offset(&self, fpath: &str, position: Position) -> Option<usize> {
+        let text = self.available_files.get(fpath)?;
+        File::new(text)
+            .offset(position)
+            .map(|offset| offset.to_usize())
+    }
+
     pub fn make_diagnostic(&self, error: Error) -> Result<FileDiagnostic> {
         assert!(!error.is_empty(), "No parts in CompilerError");
 
@@ -154,14 +511,23 @@ impl RootDatabase {
     }
 
     fn is_fpath_for_a_module(&self, fpath: &str) -> bool {
-        for module_folder in self.config.modules_folders.iter() {
-            if let Some(m_folder) = module_folder.to_str() {
-                if fpath.starts_with(m_folder) {
-                    return true;
-                }
-            }
+        if self.is_module_file(fpath) {
+            return true;
         }
         log::info!("{:?} is not a module file (not relative to any module folder), skipping from dependencies", fpath);
         false
     }
+
+    pub fn is_module_file(&self, fpath: &str) -> bool {
+        self.config
+            .modules_folders
+            .iter()
+            .filter_map(|folder| folder.to_str())
+            .any(|folder| fpath.starts_with(folder))
+    }
+
+    /// Files of the stdlib and modules folders, which could be used by other files.
+    pub fn is_dependency_file(&self, fpath: &str) -> bool {
+        self.config.is_read_only(fpath) || self.is_module_file(fpath)
+    }
 }
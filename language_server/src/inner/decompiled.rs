@@ -0,0 +1,194 @@
+//! Read-only Move sources of the compiled modules and scripts, shown as virtual documents.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Result};
+use codespan::ByteIndex;
+use dove::index::resolver::chain::loader::{BytecodeLoader, RestBytecodeLoader};
+use dove::manifest::MANIFEST;
+use lang::compat;
+use lang::compiler::location::File;
+use lang::disassembler::unit::CompiledUnit;
+use lang::disassembler::{Config as DisassemblerConfig, Disassembler};
+use libra::prelude::{Identifier, ModuleId};
+use lsp_types::{Location, Range, Url};
+
+use crate::inner::config::Config;
+use crate::inner::db::RootDatabase;
+use crate::inner::symbols::{FileSymbols, ItemPath, Span};
+
+pub const DECOMPILED_SCHEME: &str = "move-decompiled";
+/// Host of the on-chain modules uris, like `move-decompiled://chain/0x1/Account`.
+pub const CHAIN_HOST: &str = "chain";
+
+pub fn is_bytecode_file(fpath: &str) -> bool {
+    Path::new(fpath)
+        .extension()
+        .map_or(false, |extension| extension == "mv")
+}
+
+/// Full Move source of the compiled unit, bytecode of the Libra format is adapted first.
+pub fn decompile(bytecode: &[u8]) -> Result<String> {
+    let unit = match CompiledUnit::new(bytecode) {
+        Ok(unit) => unit,
+        Err(_) => {
+            let mut adapted = bytecode.to_vec();
+            compat::adapt(&mut adapted)?;
+            CompiledUnit::new(&adapted)?
+        }
+    };
+    let disassembler = Disassembler::new(
+        &unit,
+        DisassemblerConfig {
+            light_version: false,
+        },
+    );
+    let source_unit = disassembler.make_source_unit();
+    let source = source_unit.code_string()?;
+    Ok(source)
+}
+
+pub fn decompile_file(fpath: &str) -> Result<String> {
+    let bytecode = fs::read(fpath).map_err(|err| anyhow!("Cannot read {:?}: {}", fpath, err))?;
+    decompile(&bytecode)
+}
+
+/// Loads the module from the `blockchain_api` of the manifest.
+pub fn decompile_chain_module(config: &Config, address: &str, name: &str) -> Result<String> {
+    let blockchain_api = config
+        .blockchain_api
+        .as_ref()
+        .ok_or_else(|| anyhow!("`blockchain_api` is not specified in the {}", MANIFEST))?;
+    let address = config
+        .dialect()
+        .normalize_account_address(address)?
+        .as_account_address();
+    let module_id = ModuleId::new(address, Identifier::new(name)?);
+    let bytecode = RestBytecodeLoader::new(blockchain_api.parse()?).load(&module_id)?;
+    decompile(&bytecode)
+}
+
+/// Text of the virtual document, `.mv` files could be referenced with the `file` uris too.
+pub fn decompiled_source(config: &Config, uri: &Url) -> Result<String> {
+    if uri.scheme() == DECOMPILED_SCHEME && uri.host_str() == Some(CHAIN_HOST) {
+        let segments: Vec<_> = uri
+            .path_segments()
+            .map(|segments| segments.collect())
+            .unwrap_or_default();
+        return match segments.as_slice() {
+            [address, name] => decompile_chain_module(config, address, name),
+            _ => Err(anyhow!(
+                "Invalid uri {}, expected {}://{}/<address>/<module>",
+                uri,
+                DECOMPILED_SCHEME,
+                CHAIN_HOST
+            )),
+        };
+    }
+    decompile_file(&bytecode_file_path(uri)?)
+}
+
+pub fn decompiled_uri(fpath: &str) -> Result<Url> {
+    let file_uri = Url::from_file_path(fpath)
+        .map_err(|_| anyhow!("Cannot build Url from path {:?}", fpath))?;
+    Ok(Url::parse(&format!(
+        "{}://{}",
+        DECOMPILED_SCHEME,
+        file_uri.path()
+    ))?)
+}
+
+fn bytecode_file_path(uri: &Url) -> Result<String> {
+    let file_uri = match uri.scheme() {
+        "file" => uri.clone(),
+        DECOMPILED_SCHEME => Url::parse(&format!("file://{}", uri.path()))?,
+        scheme => return Err(anyhow!("Unsupported uri scheme {:?}", scheme)),
+    };
+    let path = file_uri
+        .to_file_path()
+        .map_err(|_| anyhow!("Invalid uri: {}", uri))?;
+    path.to_str()
+        .map(|fpath| fpath.to_owned())
+        .ok_or_else(|| anyhow!("Failed to convert path: {:?}", path))
+}
+
+/// Symbols of the decompiled `.mv` files, shared by the clones of the database.
+/// The file is decompiled again once its modification time changes.
+#[derive(Debug, Default, Clone)]
+pub struct DecompiledCache {
+    files: Arc<Mutex<HashMap<String, (SystemTime, Option<Arc<FileSymbols>>)>>>,
+}
+
+impl DecompiledCache {
+    pub fn symbols(&self, config: &Config, fpath: &str) -> Option<Arc<FileSymbols>> {
+        let modified = fs::metadata(fpath)
+            .and_then(|metadata| metadata.modified())
+            .ok()?;
+        if let Some((cached_modified, symbols)) = self.files.lock().unwrap().get(fpath) {
+            if *cached_modified == modified {
+                return symbols.clone();
+            }
+        }
+        let symbols = decompiled_symbols(config, fpath).map(Arc::new);
+        self.files
+            .lock()
+            .unwrap()
+            .insert(fpath.to_owned(), (modified, symbols.clone()));
+        symbols
+    }
+}
+
+/// Symbols of the decompiled `.mv` file of the module folders, which defines the module.
+pub fn find_module_file(db: &RootDatabase, path: &ItemPath) -> Option<Arc<FileSymbols>> {
+    db.config
+        .modules_folders
+        .iter()
+        .flat_map(|folder| bytecode_files(folder))
+        .find_map(|fpath| {
+            let symbols = db.decompiled.symbols(&db.config, &fpath)?;
+            symbols.module(path)?;
+            Some(symbols)
+        })
+}
+
+/// Symbols of all the decompiled `.mv` files of the module folders.
+pub fn bytecode_modules(db: &RootDatabase) -> Vec<Arc<FileSymbols>> {
+    db.config
+        .modules_folders
+        .iter()
+        .flat_map(|folder| bytecode_files(folder))
+        .filter_map(|fpath| db.decompiled.symbols(&db.config, &fpath))
+        .collect()
+}
+
+pub fn decompiled_symbols(config: &Config, fpath: &str) -> Option<FileSymbols> {
+    let text = decompile_file(fpath)
+        .map_err(|err| log::warn!("Cannot decompile {:?}: {:?}", fpath, err))
+        .ok()?;
+    FileSymbols::parse(config, fpath, &text)
+}
+
+fn bytecode_files(folder: &Path) -> Vec<String> {
+    walkdir::WalkDir::new(folder)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.into_path().into_os_string().into_string().ok())
+        .filter(|fpath| is_bytecode_file(fpath))
+        .collect()
+}
+
+/// Location in the virtual document of the decompiled `.mv` file.
+pub fn span_to_location(fpath: &str, span: Span) -> Result<Location> {
+    let file = File::new(decompile_file(fpath)?);
+    let start_pos = file.position(ByteIndex(span.0 as u32))?;
+    let end_pos = file.position(ByteIndex(span.1 as u32))?;
+    Ok(Location::new(
+        decompiled_uri(fpath)?,
+        Range::new(start_pos, end_pos),
+    ))
+}
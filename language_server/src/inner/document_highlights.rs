@@ -0,0 +1,65 @@
+//! Occurrences of the local or of the struct field at the cursor within the file.
+
+use crate::inner::symbols::{FileSymbols, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HighlightAccess {
+    /// Field definition.
+    Text,
+    Read,
+    /// Declaration of the local, assignment or field of the packed struct.
+    Write,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentHighlight {
+    pub span: Span,
+    pub access: HighlightAccess,
+}
+
+impl DocumentHighlight {
+    fn new(span: Span, is_write: bool) -> DocumentHighlight {
+        let access = if is_write {
+            HighlightAccess::Write
+        } else {
+            HighlightAccess::Read
+        };
+        DocumentHighlight { span, access }
+    }
+}
+
+pub fn document_highlights(symbols: &FileSymbols, offset: usize) -> Vec<DocumentHighlight> {
+    let mut highlights = if let Some(definition) = symbols.local_at(offset) {
+        let usages = symbols
+            .local_references
+            .iter()
+            .filter(|reference| reference.definition == definition)
+            .map(|reference| DocumentHighlight::new(reference.span, reference.is_write));
+        let mut highlights = vec![DocumentHighlight::new(definition, true)];
+        highlights.extend(usages);
+        highlights
+    } else if let Some((struct_path, field)) = symbols.field_at(offset) {
+        let definition = symbols
+            .module(&struct_path)
+            .and_then(|module| {
+                let name = struct_path.member.as_ref()?;
+                module.structs.iter().find(|s| &s.name == name)
+            })
+            .and_then(|s| s.fields.iter().find(|f| f.name == field))
+            .map(|f| DocumentHighlight {
+                span: f.name_span,
+                access: HighlightAccess::Text,
+            });
+        let usages = symbols
+            .field_references
+            .iter()
+            .filter(|reference| reference.struct_path == struct_path && reference.field == field)
+            .map(|reference| DocumentHighlight::new(reference.span, reference.is_write));
+        definition.into_iter().chain(usages).collect()
+    } else {
+        vec![]
+    };
+    highlights.sort_by_key(|highlight| highlight.span);
+    highlights.dedup_by_key(|highlight| highlight.span);
+    highlights
+}
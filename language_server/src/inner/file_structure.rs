@@ -0,0 +1,151 @@
+use crate::inner::symbols::{
+    ConstantSymbol, FileSymbols, FunctionSymbol, ModuleSymbol, ScriptSymbol, Span, SpecSymbol,
+    StructSymbol,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructureNodeKind {
+    Address,
+    Module,
+    Script,
+    Struct,
+    Field,
+    Function,
+    Constant,
+    Spec,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructureNode {
+    pub label: String,
+    pub detail: Option<String>,
+    pub kind: StructureNodeKind,
+    /// Span of the whole definition.
+    pub span: Span,
+    /// Span of the definition name.
+    pub navigation_span: Span,
+    pub children: Vec<StructureNode>,
+}
+
+impl StructureNode {
+    fn new(kind: StructureNodeKind, label: &str, span: Span, navigation_span: Span) -> Self {
+        StructureNode {
+            label: label.to_owned(),
+            detail: None,
+            kind,
+            span,
+            navigation_span,
+            children: vec![],
+        }
+    }
+
+    fn with_detail(mut self, detail: String) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+}
+
+/// Outline of the file: address blocks with modules, scripts and their members.
+pub fn file_structure(symbols: &FileSymbols) -> Vec<StructureNode> {
+    let mut nodes: Vec<StructureNode> = symbols
+        .addresses
+        .iter()
+        .map(|address| {
+            let mut node = StructureNode::new(
+                StructureNodeKind::Address,
+                &address.name,
+                address.span,
+                address.name_span,
+            );
+            node.children = symbols
+                .modules
+                .iter()
+                .filter(|module| contains(address.span, module.span))
+                .map(module_node)
+                .collect();
+            node
+        })
+        .collect();
+
+    let top_level_modules = symbols.modules.iter().filter(|module| {
+        !symbols
+            .addresses
+            .iter()
+            .any(|address| contains(address.span, module.span))
+    });
+    nodes.extend(top_level_modules.map(module_node));
+    nodes.extend(symbols.scripts.iter().map(script_node));
+    nodes.sort_by_key(|node| node.span.0);
+    nodes
+}
+
+fn contains(outer: Span, inner: Span) -> bool {
+    outer.0 <= inner.0 && inner.1 <= outer.1
+}
+
+fn module_node(module: &ModuleSymbol) -> StructureNode {
+    let mut node = StructureNode::new(
+        StructureNodeKind::Module,
+        &module.name,
+        module.span,
+        module.name_span,
+    );
+    node.children.extend(module.structs.iter().map(struct_node));
+    node.children
+        .extend(module.functions.iter().map(function_node));
+    node.children
+        .extend(module.constants.iter().map(constant_node));
+    node.children.extend(module.specs.iter().map(spec_node));
+    node.children.sort_by_key(|node| node.span.0);
+    node
+}
+
+fn script_node(script: &ScriptSymbol) -> StructureNode {
+    let mut node = StructureNode::new(
+        StructureNodeKind::Script,
+        "script",
+        script.span,
+        script.function.name_span,
+    );
+    node.children
+        .extend(script.constants.iter().map(constant_node));
+    node.children.push(function_node(&script.function));
+    node.children.extend(script.specs.iter().map(spec_node));
+    node.children.sort_by_key(|node| node.span.0);
+    node
+}
+
+fn struct_node(s: &StructSymbol) -> StructureNode {
+    let mut node = StructureNode::new(StructureNodeKind::Struct, &s.name, s.span, s.name_span);
+    node.children = s
+        .fields
+        .iter()
+        .map(|field| {
+            StructureNode::new(
+                StructureNodeKind::Field,
+                &field.name,
+                field.span,
+                field.name_span,
+            )
+            .with_detail(field.ty.clone())
+        })
+        .collect();
+    if s.is_resource {
+        node = node.with_detail("resource".to_owned());
+    }
+    node
+}
+
+fn function_node(f: &FunctionSymbol) -> StructureNode {
+    StructureNode::new(StructureNodeKind::Function, &f.name, f.span, f.name_span)
+        .with_detail(f.signature())
+}
+
+fn constant_node(c: &ConstantSymbol) -> StructureNode {
+    StructureNode::new(StructureNodeKind::Constant, &c.name, c.span, c.name_span)
+        .with_detail(c.ty.clone())
+}
+
+fn spec_node(spec: &SpecSymbol) -> StructureNode {
+    StructureNode::new(StructureNodeKind::Spec, &spec.name, spec.span, spec.span)
+}
@@ -0,0 +1,106 @@
+//! Foldable regions: items with their bodies, groups of `use` declarations and comments.
+
+use crate::inner::highlighting::comments_and_strings;
+use crate::inner::symbols::{FileSymbols, Span, UseSymbol};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoldKind {
+    Region,
+    Imports,
+    Comment,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fold {
+    pub span: Span,
+    pub kind: FoldKind,
+}
+
+pub fn folding_ranges(text: &str, symbols: &FileSymbols) -> Vec<Fold> {
+    let mut folds = vec![];
+    let mut region = |span: Span| {
+        folds.push(Fold {
+            span,
+            kind: FoldKind::Region,
+        })
+    };
+    for module in &symbols.modules {
+        region(module.span);
+        for s in &module.structs {
+            region(s.span);
+        }
+        for function in &module.functions {
+            region(function.span);
+        }
+        for spec in &module.specs {
+            region(spec.span);
+        }
+    }
+    for script in &symbols.scripts {
+        region(script.span);
+        region(script.function.span);
+        for spec in &script.specs {
+            region(spec.span);
+        }
+    }
+
+    let use_groups = symbols
+        .modules
+        .iter()
+        .map(|module| &module.uses)
+        .chain(symbols.scripts.iter().map(|script| &script.uses));
+    for uses in use_groups {
+        folds.extend(use_folds(text, uses));
+    }
+    folds.extend(comment_folds(text));
+
+    folds.retain(|fold| text[fold.span.0..fold.span.1].contains('\n'));
+    folds.sort_by_key(|fold| fold.span);
+    folds
+}
+
+/// Runs of the `use` declarations on the adjacent lines.
+fn use_folds(text: &str, uses: &[UseSymbol]) -> Vec<Fold> {
+    let mut spans: Vec<Span> = uses.iter().map(|use_| use_.span).collect();
+    spans.sort_unstable();
+
+    let mut folds: Vec<Fold> = vec![];
+    for span in spans {
+        match folds.last_mut() {
+            Some(fold) if text[fold.span.1..span.0].matches('\n').count() <= 1 => {
+                fold.span.1 = span.1
+            }
+            _ => folds.push(Fold {
+                span,
+                kind: FoldKind::Imports,
+            }),
+        }
+    }
+    folds
+}
+
+/// Block comments and runs of the line comments.
+fn comment_folds(text: &str) -> Vec<Fold> {
+    let mut folds: Vec<Fold> = vec![];
+    let comments = comments_and_strings(text)
+        .into_iter()
+        .filter(|(start, _)| text[*start..].starts_with('/'));
+    for (start, end) in comments {
+        let is_line_comment = text[start..].starts_with("//");
+        match folds.last_mut() {
+            Some(fold)
+                if is_line_comment
+                    && text[fold.span.0..].starts_with("//")
+                    && text[fold.span.1..start].trim().is_empty()
+                    && text[fold.span.1..start].matches('\n').count() == 1 =>
+            {
+                fold.span.1 = end
+            }
+            _ => folds.push(Fold {
+                span: (start, end),
+                kind: FoldKind::Comment,
+            }),
+        }
+    }
+    folds
+}
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use lang::format;
+
+use crate::inner::analysis::Analysis;
+use crate::inner::rename::FileEdit;
+use crate::inner::symbols::Span;
+
+/// Edit, which replaces the whole file with the formatted text, `None` if it's already formatted.
+pub fn format_file(analysis: &Analysis, fpath: &str) -> Result<Option<FileEdit>> {
+    format_lines(analysis, fpath, 0, usize::MAX)
+}
+
+/// Formats lines `start_line..=end_line`, the rest of the file is kept as is.
+pub fn format_lines(
+    analysis: &Analysis,
+    fpath: &str,
+    start_line: usize,
+    end_line: usize,
+) -> Result<Option<FileEdit>> {
+    let text = analysis
+        .db()
+        .available_files
+        .get(fpath)
+        .ok_or_else(|| anyhow::anyhow!("File {:?} is not tracked by the server", fpath))?;
+    // indentation depends on the preceding lines, so the whole file is formatted,
+    // and as the line breaks are kept, the same lines are taken from the result
+    let formatted = format::format(text)?;
+
+    let span = lines_span(text, start_line, end_line);
+    let formatted_span = lines_span(&formatted, start_line, end_line);
+    let new_text = &formatted[formatted_span.0..formatted_span.1];
+    if new_text == &text[span.0..span.1] {
+        return Ok(None);
+    }
+    Ok(Some(FileEdit {
+        fpath: fpath.to_owned(),
+        span,
+        new_text: new_text.to_owned(),
+    }))
+}
+
+fn lines_span(text: &str, start_line: usize, end_line: usize) -> Span {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(idx, _)| idx + 1))
+        .collect();
+    let start = line_starts.get(start_line).copied().unwrap_or(text.len());
+    let end = line_starts
+        .get(end_line.saturating_add(1))
+        .map_or(text.len(), |next_line_start| next_line_start - 1);
+    (start, end.max(start))
+}
@@ -0,0 +1,46 @@
+use crate::inner::analysis::Analysis;
+use crate::inner::decompiled;
+use crate::inner::symbols::{FileSymbols, ItemKind, ItemPath, Span};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NavigationTarget {
+    pub fpath: String,
+    /// Span of the definition name.
+    pub focus_span: Span,
+    /// Span of the whole definition.
+    pub full_span: Span,
+}
+
+pub fn goto_definition(
+    analysis: &Analysis,
+    fpath: &str,
+    offset: usize,
+) -> Option<NavigationTarget> {
+    let current_file = analysis.file_symbols(fpath)?;
+    let reference = current_file.reference_at(offset)?;
+    match analysis.module_file(&current_file, &reference.path) {
+        Some(module_file) => find_definition(&module_file, reference.kind, &reference.path),
+        None => {
+            // the module could exist only as a bytecode of the dependency
+            let module_file = decompiled::find_module_file(analysis.db(), &reference.path)?;
+            find_definition(&module_file, reference.kind, &reference.path)
+        }
+    }
+}
+
+fn find_definition(
+    symbols: &FileSymbols,
+    kind: ItemKind,
+    path: &ItemPath,
+) -> Option<NavigationTarget> {
+    let module = symbols.module(path)?;
+    let (focus_span, full_span) = match &path.member {
+        None => (module.name_span, module.span),
+        Some(member) => module.member_spans(kind, member)?,
+    };
+    Some(NavigationTarget {
+        fpath: symbols.fpath.clone(),
+        focus_span,
+        full_span,
+    })
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::inner::analysis::Analysis;
+use crate::inner::symbols::{is_identifier_char, FileSymbols, ItemKind, ItemPath, Span};
+
+const SENDER_PLACEHOLDERS: &[&str] = &["{{sender}}", "{{ sender }}"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HighlightKind {
+    Module,
+    Struct,
+    Resource,
+    Function,
+    TypeParameter,
+    Local,
+    Constant,
+    /// Address literal in any dialect, or the `{{sender}}` placeholder.
+    Address,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HighlightedRange {
+    pub span: Span,
+    pub kind: HighlightKind,
+    /// Whether the name is defined here.
+    pub is_declaration: bool,
+}
+
+impl HighlightedRange {
+    fn new(span: Span, kind: HighlightKind, is_declaration: bool) -> HighlightedRange {
+        HighlightedRange {
+            span,
+            kind,
+            is_declaration,
+        }
+    }
+}
+
+/// Classified names and address literals of the file, ordered by their position.
+/// Names are classified only if the file could be parsed, address literals always.
+pub fn highlight(analysis: &Analysis, fpath: &str, range: Option<Span>) -> Vec<HighlightedRange> {
+    let text = match analysis.db().available_files.get(fpath) {
+        Some(text) => text,
+        None => return vec![],
+    };
+    let mut ranges = address_literals(analysis, text);
+    if let Some(symbols) = analysis.file_symbols(fpath) {
+        let mut highlighter = Highlighter {
+            analysis,
+            symbols,
+            resources: HashMap::new(),
+        };
+        ranges.extend(highlighter.names());
+    }
+    if let Some((start, end)) = range {
+        ranges.retain(|range| range.span.0 < end && start < range.span.1);
+    }
+    ranges.sort_by_key(|range| range.span);
+    ranges.dedup_by_key(|range| range.span.0);
+    ranges
+}
+
+/// Address literals outside of the comments and strings. Dialect specific ones are found
+/// by the same rules the compiler uses to replace them.
+fn address_literals(analysis: &Analysis, text: &str) -> Vec<HighlightedRange> {
+    let mut spans = analysis.db().config.dialect().find_addresses(text);
+    spans.extend(hex_addresses(text));
+    for placeholder in SENDER_PLACEHOLDERS {
+        spans.extend(
+            text.match_indices(placeholder)
+                .map(|(idx, _)| (idx, idx + placeholder.len())),
+        );
+    }
+
+    let skipped = comments_and_strings(text);
+    spans
+        .into_iter()
+        .filter(|span| {
+            !skipped
+                .iter()
+                .any(|skipped| skipped.0 <= span.0 && span.0 < skipped.1)
+        })
+        .map(|span| HighlightedRange::new(span, HighlightKind::Address, false))
+        .collect()
+}
+
+fn hex_addresses(text: &str) -> Vec<Span> {
+    text.match_indices("0x")
+        .filter(|(idx, _)| !text[..*idx].ends_with(is_identifier_char))
+        .filter_map(|(idx, _)| {
+            let digits = &text[idx + 2..];
+            let len = digits
+                .find(|ch: char| !ch.is_ascii_hexdigit())
+                .unwrap_or(digits.len());
+            let is_literal = len > 0 && !digits[len..].starts_with(is_identifier_char);
+            if is_literal {
+                Some((idx, idx + 2 + len))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn comments_and_strings(text: &str) -> Vec<Span> {
+    let mut spans = vec![];
+    let mut idx = 0;
+    while let Some(ch) = text[idx..].chars().next() {
+        let rest = &text[idx..];
+        let len = if rest.starts_with("//") {
+            rest.find('\n').unwrap_or(rest.len())
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            comment.find("*/").map_or(rest.len(), |end| end + 4)
+        } else if ch == '"' {
+            string_len(rest)
+        } else {
+            idx += ch.len_utf8();
+            continue;
+        };
+        spans.push((idx, idx + len));
+        idx += len;
+    }
+    spans
+}
+
+/// Length of the string literal at the start of `text`, up to the end of text if it's unclosed.
+fn string_len(text: &str) -> usize {
+    let mut escaped = false;
+    for (idx, ch) in text.char_indices().skip(1) {
+        match ch {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return idx + 1,
+            _ => escaped = false,
+        }
+    }
+    text.len()
+}
+
+struct Highlighter<'a> {
+    analysis: &'a Analysis,
+    symbols: Arc<FileSymbols>,
+    /// Whether the referenced struct is a resource.
+    resources: HashMap<ItemPath, bool>,
+}
+
+impl<'a> Highlighter<'a> {
+    fn names(&mut self) -> Vec<HighlightedRange> {
+        let symbols = self.symbols.clone();
+        let mut ranges = vec![];
+        for module in &symbols.modules {
+            ranges.push(HighlightedRange::new(
+                module.name_span,
+                HighlightKind::Module,
+                true,
+            ));
+            for s in &module.structs {
+                let kind = if s.is_resource {
+                    HighlightKind::Resource
+                } else {
+                    HighlightKind::Struct
+                };
+                ranges.push(HighlightedRange::new(s.name_span, kind, true));
+            }
+            for constant in &module.constants {
+                ranges.push(HighlightedRange::new(
+                    constant.name_span,
+                    HighlightKind::Constant,
+                    true,
+                ));
+            }
+        }
+        for script in &symbols.scripts {
+            for constant in &script.constants {
+                ranges.push(HighlightedRange::new(
+                    constant.name_span,
+                    HighlightKind::Constant,
+                    true,
+                ));
+            }
+        }
+        for function in symbols.functions() {
+            ranges.push(HighlightedRange::new(
+                function.name_span,
+                HighlightKind::Function,
+                true,
+            ));
+            for local in &function.locals {
+                ranges.push(HighlightedRange::new(
+                    local.name_span,
+                    HighlightKind::Local,
+                    true,
+                ));
+            }
+        }
+
+        for reference in &symbols.references {
+            let kind = match reference.kind {
+                ItemKind::Module => HighlightKind::Module,
+                ItemKind::Function => HighlightKind::Function,
+                ItemKind::Constant => HighlightKind::Constant,
+                ItemKind::Struct if self.is_resource(&reference.path) => HighlightKind::Resource,
+                ItemKind::Struct => HighlightKind::Struct,
+            };
+            ranges.push(HighlightedRange::new(reference.span, kind, false));
+        }
+        ranges.extend(
+            symbols.local_references.iter().map(|reference| {
+                HighlightedRange::new(reference.span, HighlightKind::Local, false)
+            }),
+        );
+        ranges.extend(
+            symbols
+                .type_parameter_references
+                .iter()
+                .map(|span| HighlightedRange::new(*span, HighlightKind::TypeParameter, false)),
+        );
+        ranges
+    }
+
+    fn is_resource(&mut self, path: &ItemPath) -> bool {
+        if let Some(is_resource) = self.resources.get(path) {
+            return *is_resource;
+        }
+        let is_resource = self
+            .analysis
+            .module_file(&self.symbols, path)
+            .and_then(|module_file| {
+                let module = module_file.module(path)?;
+                let name = path.member.as_ref()?;
+                module
+                    .structs
+                    .iter()
+                    .find(|s| &s.name == name)
+                    .map(|s| s.is_resource)
+            })
+            .unwrap_or(false);
+        self.resources.insert(path.clone(), is_resource);
+        is_resource
+    }
+}
@@ -0,0 +1,128 @@
+use lang::compiler::address::AddressFormats;
+
+use crate::inner::analysis::Analysis;
+use crate::inner::symbols::{identifier_at, span_contains, ItemKind, ModuleSymbol, Span};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HoverResult {
+    /// Span of the hovered identifier.
+    pub span: Span,
+    /// Markdown contents.
+    pub markup: String,
+}
+
+pub fn hover(analysis: &Analysis, fpath: &str, offset: usize) -> Option<HoverResult> {
+    let current_file = analysis.file_symbols(fpath)?;
+
+    if let Some(reference) = current_file.reference_at(offset) {
+        let module_file = analysis.module_file(&current_file, &reference.path)?;
+        let module = module_file.module(&reference.path)?;
+        let markup = describe(module, reference.kind, reference.path.member.as_deref())?;
+        return Some(HoverResult {
+            span: reference.span,
+            markup,
+        });
+    }
+
+    for module in &current_file.modules {
+        if span_contains(module.name_span, offset) {
+            return Some(HoverResult {
+                span: module.name_span,
+                markup: describe(module, ItemKind::Module, None)?,
+            });
+        }
+        if let Some((kind, name, span)) = definition_at(module, offset) {
+            return Some(HoverResult {
+                span,
+                markup: describe(module, kind, Some(&name))?,
+            });
+        }
+    }
+
+    let text = analysis.db().available_files.get(fpath)?;
+    let span = identifier_at(text, offset)?;
+    if let Some(markup) = describe_address(analysis, &text[span.0..span.1]) {
+        return Some(HoverResult { span, markup });
+    }
+    let function = current_file.function_at(offset)?;
+    let file_types = analysis.db().current_file_types(fpath)?;
+    let local = file_types.local_at(&text[span.0..span.1], offset, function.span)?;
+    Some(HoverResult {
+        span,
+        markup: code_block(&format!("{}: {}", local.name, local.ty)),
+    })
+}
+
+fn definition_at(module: &ModuleSymbol, offset: usize) -> Option<(ItemKind, String, Span)> {
+    let structs = module
+        .structs
+        .iter()
+        .map(|s| (ItemKind::Struct, &s.name, s.name_span));
+    let functions = module
+        .functions
+        .iter()
+        .map(|f| (ItemKind::Function, &f.name, f.name_span));
+    let constants = module
+        .constants
+        .iter()
+        .map(|c| (ItemKind::Constant, &c.name, c.name_span));
+    structs
+        .chain(functions)
+        .chain(constants)
+        .find(|(_, _, span)| span_contains(*span, offset))
+        .map(|(kind, name, span)| (kind, name.clone(), span))
+}
+
+fn describe(module: &ModuleSymbol, kind: ItemKind, member: Option<&str>) -> Option<String> {
+    let module_path = format!("{}::{}", module.address, module.name);
+    let signature = match (kind, member) {
+        (ItemKind::Module, _) => return Some(code_block(&format!("module {}", module_path))),
+        (ItemKind::Struct, Some(name)) => {
+            module.structs.iter().find(|s| s.name == name)?.signature()
+        }
+        (ItemKind::Function, Some(name)) => module
+            .functions
+            .iter()
+            .find(|f| f.name == name)?
+            .signature(),
+        (ItemKind::Constant, Some(name)) => module
+            .constants
+            .iter()
+            .find(|c| c.name == name)?
+            .signature(),
+        (_, None) => return None,
+    };
+    Some(format!(
+        "{}\n\n{}",
+        code_block(&module_path),
+        code_block(&signature)
+    ))
+}
+
+/// Forms of the address literal and the address the compiler sees with the current dialect.
+fn describe_address(analysis: &Analysis, literal: &str) -> Option<String> {
+    let formats = AddressFormats::of(literal)?;
+    let unavailable = || "-".to_string();
+    let dialect = analysis.db().config.dialect();
+    let compiled = match dialect.normalize_account_address(literal) {
+        Ok(address) => format!("`{}`", address.lowered()),
+        Err(_) => format!("not valid in {} dialect", dialect.name()),
+    };
+    Some(format!(
+        "hex: `{}`  \nbech32: {}  \nss58: {}  \ncompiled: {}",
+        formats.hex,
+        formats
+            .bech32
+            .map(|address| format!("`{}`", address))
+            .unwrap_or_else(unavailable),
+        formats
+            .ss58
+            .map(|address| format!("`{}`", address))
+            .unwrap_or_else(unavailable),
+        compiled
+    ))
+}
+
+fn code_block(code: &str) -> String {
+    format!("```move\n{}\n```", code)
+}
@@ -0,0 +1,209 @@
+//! Inferred types of the `let` bindings and parameter names of the literal call arguments.
+
+use std::sync::Arc;
+
+use lang::compiler::address::AddressFormats;
+
+use crate::inner::analysis::Analysis;
+use crate::inner::symbols::{FileSymbols, FunctionSymbol, ItemKind, Span};
+use crate::inner::typing::FileTypes;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InlayHintKind {
+    Type,
+    Parameter,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlayHint {
+    /// Offset the hint is shown at.
+    pub offset: usize,
+    pub label: String,
+    pub kind: InlayHintKind,
+}
+
+/// Hints within the `range`. Types are known only after the file is checked.
+pub fn inlay_hints(analysis: &Analysis, fpath: &str, range: Span) -> Vec<InlayHint> {
+    let text = match analysis.db().available_files.get(fpath) {
+        Some(text) => text,
+        None => return vec![],
+    };
+    let symbols = match analysis.file_symbols(fpath) {
+        Some(symbols) => symbols,
+        None => return vec![],
+    };
+
+    let mut hints = vec![];
+    if let Some(file_types) = analysis.db().current_file_types(fpath) {
+        let functions = symbols
+            .modules
+            .iter()
+            .flat_map(|module| module.functions.iter())
+            .chain(symbols.scripts.iter().map(|script| &script.function));
+        for function in functions {
+            hints.extend(type_hints(function, file_types, range));
+        }
+    }
+    hints.extend(parameter_hints(analysis, text, &symbols, range));
+    hints.sort_by_key(|hint| hint.offset);
+    hints
+}
+
+fn in_range(range: Span, offset: usize) -> bool {
+    range.0 <= offset && offset <= range.1
+}
+
+/// `let` bindings without the type annotation, the fields of unpacked structs are skipped.
+fn type_hints(function: &FunctionSymbol, file_types: &FileTypes, range: Span) -> Vec<InlayHint> {
+    function
+        .locals
+        .iter()
+        .filter(|local| local.is_let && local.ty.is_none() && in_range(range, local.name_span.1))
+        .filter_map(|local| {
+            let local_type =
+                file_types.local_at(&local.name, local.name_span.0, function.span)?;
+            if local_type.span.0 != local.name_span.0 || local_type.ty == "_" {
+                return None;
+            }
+            Some(InlayHint {
+                offset: local.name_span.1,
+                label: format!(": {}", local_type.ty),
+                kind: InlayHintKind::Type,
+            })
+        })
+        .collect()
+}
+
+/// Names of the parameters before the literal arguments, if there are several of them in a call.
+fn parameter_hints(
+    analysis: &Analysis,
+    text: &str,
+    symbols: &Arc<FileSymbols>,
+    range: Span,
+) -> Vec<InlayHint> {
+    let mut hints = vec![];
+    for reference in &symbols.references {
+        if reference.kind != ItemKind::Function || !in_range(range, reference.span.1) {
+            continue;
+        }
+        let arguments = match call_arguments(text, reference.span.1) {
+            Some(arguments) => arguments,
+            None => continue,
+        };
+        let literals: Vec<_> = arguments
+            .iter()
+            .enumerate()
+            .filter(|(_, span)| is_literal(&text[span.0..span.1]))
+            .collect();
+        if literals.len() < 2 {
+            continue;
+        }
+        let name = match &reference.path.member {
+            Some(name) => name,
+            None => continue,
+        };
+        let function = analysis
+            .module_file(symbols, &reference.path)
+            .and_then(|module_file| {
+                module_file
+                    .module(&reference.path)?
+                    .functions
+                    .iter()
+                    .find(|function| &function.name == name)
+                    .cloned()
+            });
+        let function = match function {
+            Some(function) => function,
+            None => continue,
+        };
+        for (idx, span) in literals {
+            if let Some((parameter, _)) = function.parameters.get(idx) {
+                hints.push(InlayHint {
+                    offset: span.0,
+                    label: format!("{}:", parameter),
+                    kind: InlayHintKind::Parameter,
+                });
+            }
+        }
+    }
+    hints
+}
+
+/// Spans of the arguments of the call `f<T>(a, b)`, where `callee_end` is the end of `f`.
+fn call_arguments(text: &str, callee_end: usize) -> Option<Vec<Span>> {
+    let mut start = callee_end;
+    if text[start..].starts_with('<') {
+        let mut angle_depth = 0;
+        for (idx, ch) in text[start..].char_indices() {
+            match ch {
+                '<' => angle_depth += 1,
+                '>' => angle_depth -= 1,
+                _ => {}
+            }
+            if angle_depth == 0 {
+                start += idx + 1;
+                break;
+            }
+        }
+    }
+    if !text[start..].starts_with('(') {
+        return None;
+    }
+    start += 1;
+
+    let mut arguments = vec![];
+    let mut argument_start = start;
+    let mut depth = 0;
+    let mut in_string = false;
+    for (idx, ch) in text[start..].char_indices() {
+        let offset = start + idx;
+        match ch {
+            '"' => in_string = !in_string,
+            _ if in_string => {}
+            '(' | '[' | '{' => depth += 1,
+            ')' if depth == 0 => {
+                arguments.push((argument_start, offset));
+                return Some(
+                    arguments
+                        .into_iter()
+                        .filter_map(|span| trimmed(text, span))
+                        .collect(),
+                );
+            }
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                arguments.push((argument_start, offset));
+                argument_start = offset + 1;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn trimmed(text: &str, (start, end): Span) -> Option<Span> {
+    let argument = &text[start..end];
+    let trimmed = argument.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let start = start + (argument.len() - argument.trim_start().len());
+    Some((start, start + trimmed.len()))
+}
+
+fn is_literal(argument: &str) -> bool {
+    if argument == "true" || argument == "false" {
+        return true;
+    }
+    if (argument.starts_with("b\"") || argument.starts_with("x\"")) && argument.ends_with('"') {
+        return true;
+    }
+    let number = argument
+        .trim_end_matches("u128")
+        .trim_end_matches("u64")
+        .trim_end_matches("u8");
+    if !number.is_empty() && number.chars().all(|ch| ch.is_ascii_digit()) {
+        return true;
+    }
+    AddressFormats::of(argument).is_some()
+}
@@ -1,4 +1,24 @@
 pub mod analysis;
+pub mod call_hierarchy;
 pub mod change;
+pub mod code_actions;
+pub mod completion;
 pub mod config;
 pub mod db;
+pub mod decompiled;
+pub mod document_highlights;
+pub mod file_structure;
+pub mod folding_ranges;
+pub mod formatting;
+pub mod goto_definition;
+pub mod highlighting;
+pub mod hover;
+pub mod inlay_hints;
+pub mod references;
+pub mod rename;
+pub mod runnables;
+pub mod selection_ranges;
+pub mod signature_help;
+pub mod symbol_index;
+pub mod symbols;
+pub mod typing;
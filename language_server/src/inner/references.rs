@@ -0,0 +1,204 @@
+use crate::inner::analysis::Analysis;
+use crate::inner::symbols::{span_contains, FileSymbols, ItemKind, ItemPath, ModuleSymbol, Span};
+
+/// Anything, which could be referenced by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Definition {
+    Item {
+        kind: ItemKind,
+        path: ItemPath,
+    },
+    Field {
+        struct_path: ItemPath,
+        name: String,
+    },
+    /// Function local, identified by the name span of its declaration.
+    Local {
+        fpath: String,
+        name_span: Span,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReference {
+    pub fpath: String,
+    pub span: Span,
+}
+
+impl FileReference {
+    fn new(fpath: &str, span: Span) -> FileReference {
+        FileReference {
+            fpath: fpath.to_owned(),
+            span,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReferenceSearchResult {
+    pub definition: Definition,
+    /// Name of the definition, `None` if it's not in the tracked files.
+    pub declaration: Option<FileReference>,
+    pub references: Vec<FileReference>,
+}
+
+impl ReferenceSearchResult {
+    pub fn all(&self) -> impl Iterator<Item = &FileReference> {
+        self.declaration.iter().chain(self.references.iter())
+    }
+}
+
+/// Definition, which is declared or referenced at `offset`.
+pub fn definition_at(symbols: &FileSymbols, offset: usize) -> Option<Definition> {
+    if let Some(reference) = symbols.reference_at(offset) {
+        return Some(Definition::Item {
+            kind: reference.kind,
+            path: reference.path.clone(),
+        });
+    }
+    if let Some((struct_path, name)) = symbols.field_at(offset) {
+        return Some(Definition::Field { struct_path, name });
+    }
+    if let Some(name_span) = symbols.local_at(offset) {
+        return Some(Definition::Local {
+            fpath: symbols.fpath.clone(),
+            name_span,
+        });
+    }
+    symbols.modules.iter().find_map(|module| {
+        if span_contains(module.name_span, offset) {
+            return Some(Definition::Item {
+                kind: ItemKind::Module,
+                path: module.path(),
+            });
+        }
+        let spans = module
+            .structs
+            .iter()
+            .map(|s| (ItemKind::Struct, &s.name, s.name_span))
+            .chain(
+                module
+                    .functions
+                    .iter()
+                    .map(|f| (ItemKind::Function, &f.name, f.name_span)),
+            )
+            .chain(
+                module
+                    .constants
+                    .iter()
+                    .map(|c| (ItemKind::Constant, &c.name, c.name_span)),
+            );
+        for (kind, name, name_span) in spans {
+            if span_contains(name_span, offset) {
+                return Some(Definition::Item {
+                    kind,
+                    path: ItemPath::member(module.address, module.name.clone(), name.clone()),
+                });
+            }
+        }
+        None
+    })
+}
+
+pub fn find_all_references(
+    analysis: &Analysis,
+    fpath: &str,
+    offset: usize,
+) -> Option<ReferenceSearchResult> {
+    let current_file = analysis.file_symbols(fpath)?;
+    let definition = definition_at(&current_file, offset)?;
+    let result = match definition {
+        Definition::Item { kind, path } => {
+            let declaration =
+                analysis
+                    .module_file(&current_file, &path)
+                    .and_then(|module_file| {
+                        let module = module_file.module(&path)?;
+                        let (kind, span) = item_name_span(module, kind, &path)?;
+                        Some((kind, FileReference::new(&module_file.fpath, span)))
+                    });
+            // kind of the imported member is only guessed, prefer the declared one
+            let kind = declaration.as_ref().map_or(kind, |(kind, _)| *kind);
+            let references = analysis
+                .parsed_files()
+                .iter()
+                .flat_map(|symbols| {
+                    symbols
+                        .references
+                        .iter()
+                        .filter(|reference| reference.path == path)
+                        .map(move |reference| FileReference::new(&symbols.fpath, reference.span))
+                })
+                .collect();
+            ReferenceSearchResult {
+                definition: Definition::Item { kind, path },
+                declaration: declaration.map(|(_, declaration)| declaration),
+                references,
+            }
+        }
+        Definition::Field { struct_path, name } => {
+            // fields are accessible only inside of the module which declares the struct
+            let module_file = analysis.module_file(&current_file, &struct_path)?;
+            let declaration = module_file
+                .module(&struct_path)
+                .and_then(|module| {
+                    let s = module
+                        .structs
+                        .iter()
+                        .find(|s| Some(&s.name) == struct_path.member.as_ref())?;
+                    s.fields.iter().find(|field| field.name == name)
+                })
+                .map(|field| FileReference::new(&module_file.fpath, field.name_span));
+            let references = module_file
+                .field_references
+                .iter()
+                .filter(|reference| {
+                    reference.struct_path == struct_path && reference.field == name
+                })
+                .map(|reference| FileReference::new(&module_file.fpath, reference.span))
+                .collect();
+            ReferenceSearchResult {
+                definition: Definition::Field { struct_path, name },
+                declaration,
+                references,
+            }
+        }
+        Definition::Local { fpath, name_span } => {
+            let references = current_file
+                .local_references
+                .iter()
+                .filter(|reference| reference.definition == name_span)
+                .map(|reference| FileReference::new(&fpath, reference.span))
+                .collect();
+            ReferenceSearchResult {
+                declaration: Some(FileReference::new(&fpath, name_span)),
+                definition: Definition::Local { fpath, name_span },
+                references,
+            }
+        }
+    };
+    Some(result)
+}
+
+/// Kind and name span of the module or of the member with the `path`.
+fn item_name_span(
+    module: &ModuleSymbol,
+    kind: ItemKind,
+    path: &ItemPath,
+) -> Option<(ItemKind, Span)> {
+    let member = match &path.member {
+        None => return Some((ItemKind::Module, module.name_span)),
+        Some(member) => member,
+    };
+    let kinds = [
+        kind,
+        ItemKind::Struct,
+        ItemKind::Function,
+        ItemKind::Constant,
+    ];
+    kinds.iter().find_map(|kind| {
+        module
+            .member_spans(*kind, member)
+            .map(|(name_span, _)| (*kind, name_span))
+    })
+}
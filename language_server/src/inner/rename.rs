@@ -0,0 +1,163 @@
+use std::fmt;
+
+use crate::inner::analysis::Analysis;
+use crate::inner::completion::{BUILTIN_FUNCTIONS, KEYWORDS, PRIMITIVE_TYPES};
+use crate::inner::references::{self, Definition, ReferenceSearchResult};
+use crate::inner::symbols::{identifier_at, is_identifier_char, Span};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameError(pub String);
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEdit {
+    pub fpath: String,
+    pub span: Span,
+    pub new_text: String,
+}
+
+/// Span of the identifier at `offset`, if it could be renamed.
+pub fn prepare_rename(
+    analysis: &Analysis,
+    fpath: &str,
+    offset: usize,
+) -> Result<Span, RenameError> {
+    find_renameable(analysis, fpath, offset).map(|(span, _)| span)
+}
+
+pub fn rename(
+    analysis: &Analysis,
+    fpath: &str,
+    offset: usize,
+    new_name: &str,
+) -> Result<Vec<FileEdit>, RenameError> {
+    check_identifier(new_name)?;
+    let (span, search_result) = find_renameable(analysis, fpath, offset)?;
+    let old_name = &analysis.db().available_files[fpath][span.0..span.1];
+
+    let shorthands = field_shorthands(analysis, &search_result);
+    let mut edits = vec![];
+    for reference in search_result.all() {
+        let text = match analysis.db().available_files.get(&reference.fpath) {
+            Some(text) => text,
+            None => continue,
+        };
+        // aliases introduced with `use ... as ...` keep their names
+        if &text[reference.span.0..reference.span.1] != old_name {
+            continue;
+        }
+        let new_text = if !shorthands.contains(&reference.span) {
+            new_name.to_owned()
+        } else if let Definition::Field { .. } = search_result.definition {
+            format!("{}: {}", new_name, old_name)
+        } else {
+            format!("{}: {}", old_name, new_name)
+        };
+        edits.push(FileEdit {
+            fpath: reference.fpath.clone(),
+            span: reference.span,
+            new_text,
+        });
+    }
+    edits.sort_by(|l, r| l.fpath.cmp(&r.fpath).then(l.span.cmp(&r.span)));
+    edits.dedup();
+    Ok(edits)
+}
+
+fn find_renameable(
+    analysis: &Analysis,
+    fpath: &str,
+    offset: usize,
+) -> Result<(Span, ReferenceSearchResult), RenameError> {
+    let text =
+        analysis.db().available_files.get(fpath).ok_or_else(|| {
+            RenameError(format!("File {:?} is not tracked by the server", fpath))
+        })?;
+    let span = identifier_at(text, offset)
+        .ok_or_else(|| RenameError("No identifier at the cursor".to_owned()))?;
+    let name = &text[span.0..span.1];
+
+    let search_result = match references::find_all_references(analysis, fpath, offset) {
+        Some(result) => result,
+        None if is_builtin(name) => {
+            return Err(RenameError(format!("Builtin `{}` cannot be renamed", name)));
+        }
+        None => return Err(RenameError(format!("Cannot find definition of `{}`", name))),
+    };
+    let declaration = match &search_result.declaration {
+        Some(declaration) => declaration,
+        None => {
+            return Err(RenameError(format!(
+                "Definition of `{}` is not in the workspace",
+                name
+            )))
+        }
+    };
+    if analysis.db().config.is_read_only(&declaration.fpath) {
+        return Err(RenameError(format!(
+            "`{}` is defined in the read-only dependency {:?}",
+            name, declaration.fpath
+        )));
+    }
+    let declared_name = analysis
+        .db()
+        .available_files
+        .get(&declaration.fpath)
+        .map(|text| &text[declaration.span.0..declaration.span.1]);
+    if declared_name != Some(name) {
+        return Err(RenameError(format!("Alias `{}` cannot be renamed", name)));
+    }
+    Ok((span, search_result))
+}
+
+/// Spans of the field shorthands, like `Coin { value }`, where the name is both the field
+/// and the local. These are expanded on rename, so that the other name is kept.
+fn field_shorthands(analysis: &Analysis, search_result: &ReferenceSearchResult) -> Vec<Span> {
+    let fpath = match &search_result.declaration {
+        Some(declaration) => &declaration.fpath,
+        None => return vec![],
+    };
+    let symbols = match analysis.file_symbols(fpath) {
+        Some(symbols) => symbols,
+        None => return vec![],
+    };
+    match search_result.definition {
+        Definition::Item { .. } => vec![],
+        Definition::Field { .. } => symbols
+            .local_references
+            .iter()
+            .map(|reference| reference.span)
+            .chain(
+                symbols
+                    .functions()
+                    .flat_map(|function| function.locals.iter().map(|local| local.name_span)),
+            )
+            .collect(),
+        Definition::Local { .. } => symbols
+            .field_references
+            .iter()
+            .map(|reference| reference.span)
+            .collect(),
+    }
+}
+
+fn check_identifier(name: &str) -> Result<(), RenameError> {
+    let is_valid = name.chars().next().map_or(false, |ch| !ch.is_ascii_digit())
+        && name.chars().all(is_identifier_char)
+        && !KEYWORDS.contains(&name)
+        && !is_builtin(name);
+    if is_valid {
+        Ok(())
+    } else {
+        Err(RenameError(format!("`{}` is not a valid identifier", name)))
+    }
+}
+
+fn is_builtin(name: &str) -> bool {
+    BUILTIN_FUNCTIONS.contains(&name) || PRIMITIVE_TYPES.contains(&name)
+}
@@ -0,0 +1,121 @@
+use std::iter;
+
+use anyhow::{anyhow, Result};
+use lang::compiler::error::CompilerError;
+use lang::compiler::file::MoveFile;
+use libra::move_lang::errors::report_errors_to_buffer;
+use move_executor::executor::Executor;
+use move_executor::explain::PipelineExecutionResult;
+
+use crate::inner::analysis::Analysis;
+use crate::inner::symbols::{FileSymbols, Span};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Runnable {
+    /// Span of the `script` block to show the runnable at.
+    pub span: Span,
+    /// Index of the script in the file, `None` to run all the scripts one after another.
+    pub script: Option<usize>,
+}
+
+/// Every script of the file could be run on its own, test files are run as a whole.
+pub fn runnables(analysis: &Analysis, fpath: &str) -> Vec<Runnable> {
+    let symbols = match analysis.file_symbols(fpath) {
+        Some(symbols) => symbols,
+        None => return vec![],
+    };
+    if analysis.db().config.is_test_file(fpath) {
+        return symbols
+            .scripts
+            .first()
+            .map(|script| Runnable {
+                span: script.span,
+                script: None,
+            })
+            .into_iter()
+            .collect();
+    }
+    symbols
+        .scripts
+        .iter()
+        .enumerate()
+        .map(|(idx, script)| Runnable {
+            span: script.span,
+            script: Some(idx),
+        })
+        .collect()
+}
+
+/// Executes the scripts of the file with the dependencies it's checked with,
+/// `meta` comments are applied to every script.
+pub fn run(
+    analysis: &Analysis,
+    fpath: &str,
+    script: Option<usize>,
+    args: Vec<String>,
+    meta: &[String],
+) -> Result<PipelineExecutionResult> {
+    let db = analysis.db();
+    let text = db
+        .available_files
+        .get(fpath)
+        .ok_or_else(|| anyhow!("File {:?} is not tracked by the server", fpath))?;
+    let text = match script {
+        Some(idx) => {
+            let symbols = analysis
+                .file_symbols(fpath)
+                .ok_or_else(|| anyhow!("File {:?} could not be parsed", fpath))?;
+            without_other_scripts(text, &symbols, idx)?
+        }
+        None => text.to_string(),
+    };
+
+    let deps = db
+        .dependencies(fpath, &text)
+        .into_iter()
+        .filter_map(|dep_fpath| {
+            let dep_text = db.available_files.get(&dep_fpath)?;
+            Some(MoveFile::with_content(dep_fpath, &**dep_text))
+        })
+        .collect();
+    let dialect = db.config.dialect();
+    let executor = Executor::new(dialect.as_ref(), db.config.sender().clone(), deps);
+
+    let file = MoveFile::with_content(fpath, text);
+    let result = executor.execute_script_with_meta(file, args, meta);
+    result.map_err(|err| match err.downcast::<CompilerError>() {
+        Ok(CompilerError { source_map, errors }) => {
+            let report = report_errors_to_buffer(source_map, errors);
+            anyhow!("{}", String::from_utf8_lossy(&report))
+        }
+        Err(err) => err,
+    })
+}
+
+/// Text of the file with the other scripts replaced by whitespaces,
+/// so that the positions in the compiler errors stay the same.
+fn without_other_scripts(text: &str, symbols: &FileSymbols, script: usize) -> Result<String> {
+    if script >= symbols.scripts.len() {
+        return Err(anyhow!(
+            "There is no script #{} in {:?}",
+            script,
+            symbols.fpath
+        ));
+    }
+    let mut text = text.to_owned();
+    for (idx, other) in symbols.scripts.iter().enumerate() {
+        if idx == script {
+            continue;
+        }
+        let (start, end) = other.span;
+        let blank: String = text[start..end]
+            .chars()
+            .flat_map(|ch| {
+                let filler = if ch == '\n' { ch } else { ' ' };
+                iter::repeat(filler).take(ch.len_utf8())
+            })
+            .collect();
+        text.replace_range(start..end, &blank);
+    }
+    Ok(text)
+}
@@ -0,0 +1,258 @@
+//! Nested syntax nodes around the cursor, from the identifier up to the address block.
+
+use std::collections::HashMap;
+
+use libra::move_ir_types::location::Loc;
+use libra::move_lang::parser::ast::{
+    Bind, Bind_, Definition, Exp, Exp_, Function, FunctionBody_, ModuleDefinition, ModuleMember,
+    Sequence, SequenceItem_, StructFields, Type, Type_,
+};
+use libra::move_lang::name_pool::ConstPool;
+use lang::compiler::parser::parse_file;
+use lang::compiler::source_map::FileOffsetMap;
+
+use crate::inner::config::Config;
+use crate::inner::symbols::{identifier_at, span_contains, Span};
+
+/// Spans of the nodes containing the `offset`, the innermost first.
+/// Returns `None` if the file contains syntax errors.
+pub fn selection_ranges(
+    config: &Config,
+    fpath: &str,
+    text: &str,
+    offset: usize,
+) -> Option<Vec<Span>> {
+    let fname = ConstPool::push(fpath);
+    let (defs, _, errors, offsets) = parse_file(
+        config.dialect().as_ref(),
+        &mut HashMap::new(),
+        fname,
+        text,
+        Some(config.sender()),
+    );
+    if !errors.is_empty() {
+        return None;
+    }
+
+    let mut collector = SpanCollector {
+        offsets: &offsets,
+        offset,
+        spans: vec![],
+    };
+    for def in &defs {
+        match def {
+            Definition::Address(loc, _, modules) => {
+                if collector.push(*loc) {
+                    for module in modules {
+                        collector.module(module);
+                    }
+                }
+            }
+            Definition::Module(module) => collector.module(module),
+            Definition::Script(script) => {
+                if collector.push(script.loc) {
+                    for constant in &script.constants {
+                        if collector.push(constant.loc) {
+                            collector.type_(&constant.signature);
+                            collector.exp(&constant.value);
+                        }
+                    }
+                    collector.function(&script.function);
+                    for spec in &script.specs {
+                        collector.push(spec.loc);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut spans = collector.spans;
+    spans.extend(identifier_at(text, offset));
+    spans.sort_by_key(|(start, end)| (end - start, *start));
+    spans.dedup();
+    Some(spans)
+}
+
+struct SpanCollector<'a> {
+    offsets: &'a FileOffsetMap,
+    offset: usize,
+    spans: Vec<Span>,
+}
+
+impl<'a> SpanCollector<'a> {
+    /// Records the node span, returns `false` if it doesn't contain the offset.
+    fn push(&mut self, loc: Loc) -> bool {
+        let span = self.offsets.translate_span(loc.span());
+        let span = (span.start().to_usize(), span.end().to_usize());
+        if span_contains(span, self.offset) {
+            self.spans.push(span);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn module(&mut self, module: &ModuleDefinition) {
+        if !self.push(module.loc) {
+            return;
+        }
+        for member in &module.members {
+            match member {
+                ModuleMember::Function(f) => self.function(f),
+                ModuleMember::Struct(s) => {
+                    if self.push(s.loc) {
+                        if let StructFields::Defined(fields) = &s.fields {
+                            for (_, ty) in fields {
+                                self.type_(ty);
+                            }
+                        }
+                    }
+                }
+                ModuleMember::Constant(c) => {
+                    if self.push(c.loc) {
+                        self.type_(&c.signature);
+                        self.exp(&c.value);
+                    }
+                }
+                ModuleMember::Spec(spec) => {
+                    self.push(spec.loc);
+                }
+                ModuleMember::Use(_) => {}
+            }
+        }
+    }
+
+    fn function(&mut self, f: &Function) {
+        if !self.push(f.loc) {
+            return;
+        }
+        for (_, ty) in &f.signature.parameters {
+            self.type_(ty);
+        }
+        self.type_(&f.signature.return_type);
+        if let FunctionBody_::Defined(seq) = &f.body.value {
+            if self.push(f.body.loc) {
+                self.sequence(seq);
+            }
+        }
+    }
+
+    fn sequence(&mut self, (_, items, _, exp): &Sequence) {
+        for item in items {
+            if !self.push(item.loc) {
+                continue;
+            }
+            match &item.value {
+                SequenceItem_::Seq(exp) => self.exp(exp),
+                SequenceItem_::Declare(binds, ty) => {
+                    if self.push(binds.loc) {
+                        binds.value.iter().for_each(|bind| self.bind(bind));
+                    }
+                    if let Some(ty) = ty {
+                        self.type_(ty);
+                    }
+                }
+                SequenceItem_::Bind(binds, ty, exp) => {
+                    if self.push(binds.loc) {
+                        binds.value.iter().for_each(|bind| self.bind(bind));
+                    }
+                    if let Some(ty) = ty {
+                        self.type_(ty);
+                    }
+                    self.exp(exp);
+                }
+            }
+        }
+        if let Some(exp) = exp.as_ref() {
+            self.exp(exp);
+        }
+    }
+
+    fn bind(&mut self, bind: &Bind) {
+        if !self.push(bind.loc) {
+            return;
+        }
+        if let Bind_::Unpack(_, _, fields) = &bind.value {
+            for (_, bind) in fields {
+                self.bind(bind);
+            }
+        }
+    }
+
+    fn type_(&mut self, ty: &Type) {
+        if !self.push(ty.loc) {
+            return;
+        }
+        match &ty.value {
+            Type_::Apply(_, tys) | Type_::Multiple(tys) => {
+                tys.iter().for_each(|ty| self.type_(ty));
+            }
+            Type_::Ref(_, ty) => self.type_(ty),
+            Type_::Fun(tys, ty) => {
+                tys.iter().for_each(|ty| self.type_(ty));
+                self.type_(ty);
+            }
+            Type_::Unit => {}
+        }
+    }
+
+    fn exp(&mut self, exp: &Exp) {
+        if !self.push(exp.loc) {
+            return;
+        }
+        match &exp.value {
+            Exp_::Value(_)
+            | Exp_::Move(_)
+            | Exp_::Copy(_)
+            | Exp_::Name(_, _)
+            | Exp_::Unit
+            | Exp_::Break
+            | Exp_::Continue
+            | Exp_::Spec(_)
+            | Exp_::InferredNum(_)
+            | Exp_::UnresolvedError => {}
+            Exp_::Call(_, _, args) => {
+                if self.push(args.loc) {
+                    args.value.iter().for_each(|arg| self.exp(arg));
+                }
+            }
+            Exp_::Pack(_, _, fields) => fields.iter().for_each(|(_, exp)| self.exp(exp)),
+            Exp_::IfElse(cond, if_true, if_false) => {
+                self.exp(cond);
+                self.exp(if_true);
+                if let Some(if_false) = if_false {
+                    self.exp(if_false);
+                }
+            }
+            Exp_::While(cond, body) => {
+                self.exp(cond);
+                self.exp(body);
+            }
+            Exp_::Block(seq) => self.sequence(seq),
+            Exp_::Lambda(binds, body) => {
+                binds.value.iter().for_each(|bind| self.bind(bind));
+                self.exp(body);
+            }
+            Exp_::ExpList(exps) => exps.iter().for_each(|exp| self.exp(exp)),
+            Exp_::Assign(lhs, rhs) | Exp_::BinopExp(lhs, _, rhs) | Exp_::Index(lhs, rhs) => {
+                self.exp(lhs);
+                self.exp(rhs);
+            }
+            Exp_::Return(exp) => {
+                if let Some(exp) = exp {
+                    self.exp(exp);
+                }
+            }
+            Exp_::Abort(exp)
+            | Exp_::Dereference(exp)
+            | Exp_::Loop(exp)
+            | Exp_::UnaryExp(_, exp)
+            | Exp_::Borrow(_, exp)
+            | Exp_::Dot(exp, _) => self.exp(exp),
+            Exp_::Cast(exp, ty) | Exp_::Annotate(exp, ty) => {
+                self.exp(exp);
+                self.type_(ty);
+            }
+        }
+    }
+}
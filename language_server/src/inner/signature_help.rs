@@ -0,0 +1,258 @@
+use std::sync::Arc;
+
+use crate::inner::analysis::Analysis;
+use crate::inner::completion::parse_address;
+use crate::inner::symbols::{identifier_start, FileSymbols, FunctionSymbol, ItemPath, Span};
+
+/// Inserted at the cursor to close the call and make the incomplete code parseable.
+const CALL_CLOSINGS: &[&str] = &[")", ");", ">()", ">();"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureHelpResult {
+    /// Signature of the called function.
+    pub label: String,
+    /// Spans of the parameters, or of the type parameters, within the `label`.
+    pub parameters: Vec<Span>,
+    pub active_parameter: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArgumentList {
+    /// `f(|)`
+    Values,
+    /// `f<|>`
+    Types,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct CallContext {
+    /// `0x1::Module::f` split by `::`.
+    callee: Vec<String>,
+    /// Offset of the callee, used to find the enclosing module or script.
+    callee_start: usize,
+    list: ArgumentList,
+    /// Index of the argument the cursor is at.
+    argument: usize,
+}
+
+pub fn signature_help(
+    analysis: &Analysis,
+    fpath: &str,
+    offset: usize,
+) -> Option<SignatureHelpResult> {
+    let text = analysis.db().available_files.get(fpath)?;
+    if offset > text.len() || !text.is_char_boundary(offset) {
+        return None;
+    }
+    let context = call_context(text, offset)?;
+    let current_file = current_file_symbols(analysis, fpath, text, offset)?;
+    let function = resolve_function(analysis, &current_file, &context)?;
+
+    let (label, parameters) = signature_with_parameters(&function, context.list);
+    let active_parameter = Some(context.argument).filter(|idx| *idx < parameters.len());
+    Some(SignatureHelpResult {
+        label,
+        parameters,
+        active_parameter,
+    })
+}
+
+/// Finds the innermost unclosed argument list before the `offset`, within the current statement.
+/// `<` and `>` are taken as brackets only without the whitespace before them, like in `f<u8>`,
+/// otherwise they are comparison operators.
+fn call_context(text: &str, offset: usize) -> Option<CallContext> {
+    let bytes = text.as_bytes();
+    let mut depth = 0;
+    let mut angle_depth = 0;
+    let mut argument = 0;
+    let mut idx = offset;
+    while idx > 0 {
+        idx -= 1;
+        let is_glued = idx > 0 && !bytes[idx - 1].is_ascii_whitespace();
+        match bytes[idx] {
+            b'"' => idx = text[..idx].rfind('"')?,
+            b')' | b']' | b'}' => depth += 1,
+            b'(' | b'[' | b'{' if depth > 0 => depth -= 1,
+            b'(' if angle_depth == 0 => {
+                return callee(text, idx).map(|(callee, callee_start)| CallContext {
+                    callee,
+                    callee_start,
+                    list: ArgumentList::Values,
+                    argument,
+                });
+            }
+            b'[' | b'{' | b';' if depth == 0 => return None,
+            b'>' if is_glued && bytes[idx - 1] != b'=' && bytes.get(idx + 1) != Some(&b'=') => {
+                angle_depth += 1
+            }
+            b'<' if angle_depth > 0 => angle_depth -= 1,
+            b'<' if depth == 0 && is_glued && bytes.get(idx + 1) != Some(&b'=') => {
+                return callee(text, idx).map(|(callee, callee_start)| CallContext {
+                    callee,
+                    callee_start,
+                    list: ArgumentList::Types,
+                    argument,
+                });
+            }
+            b',' if depth == 0 && angle_depth == 0 => argument += 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Path of the function, which is called with the argument list opened at `list_start`.
+fn callee(text: &str, list_start: usize) -> Option<(Vec<String>, usize)> {
+    let mut end = list_start;
+    if text[..end].ends_with('>') {
+        // skip the type arguments of `f<u8>(`
+        let mut angle_depth = 0;
+        for (idx, ch) in text[..end].char_indices().rev() {
+            match ch {
+                '>' => angle_depth += 1,
+                '<' => angle_depth -= 1,
+                _ => {}
+            }
+            if angle_depth == 0 {
+                end = idx;
+                break;
+            }
+        }
+    }
+
+    let mut segments = vec![];
+    loop {
+        let start = identifier_start(text, end);
+        if start == end {
+            break;
+        }
+        segments.insert(0, text[start..end].to_owned());
+        end = start;
+        if !text[..end].ends_with("::") {
+            break;
+        }
+        end -= 2;
+    }
+    if segments.is_empty() {
+        None
+    } else {
+        Some((segments, end))
+    }
+}
+
+/// Symbols of the file, the unclosed call at the cursor is closed if it's needed for parsing.
+fn current_file_symbols(
+    analysis: &Analysis,
+    fpath: &str,
+    text: &str,
+    offset: usize,
+) -> Option<Arc<FileSymbols>> {
+    if let Some(symbols) = analysis.file_symbols(fpath) {
+        return Some(symbols);
+    }
+    let config = &analysis.db().config;
+    CALL_CLOSINGS
+        .iter()
+        .find_map(|closing| {
+            let patched = format!("{}{}{}", &text[..offset], closing, &text[offset..]);
+            FileSymbols::parse(config, fpath, &patched)
+        })
+        .map(Arc::new)
+        .or_else(|| analysis.db().last_valid_symbols(fpath))
+}
+
+fn resolve_function(
+    analysis: &Analysis,
+    current_file: &FileSymbols,
+    context: &CallContext,
+) -> Option<FunctionSymbol> {
+    let offset = context.callee_start;
+    let uses = current_file.uses_at(offset);
+    let current_module = current_file.module_at(offset);
+    let path = match context.callee.as_slice() {
+        [name] => current_module
+            .filter(|module| module.functions.iter().any(|f| &f.name == name))
+            .map(|module| ItemPath::member(module.address, module.name.clone(), name.clone()))
+            .or_else(|| {
+                uses.iter().find_map(|use_| {
+                    use_.member_by_alias(name).map(|member| {
+                        ItemPath::member(use_.address, use_.module.clone(), member.to_owned())
+                    })
+                })
+            })?,
+        [module_name, name] => uses
+            .iter()
+            .find(|use_| use_.module_alias() == Some(module_name.as_str()))
+            .map(|use_| (use_.address, use_.module.clone()))
+            .or_else(|| {
+                current_module
+                    .filter(|module| &module.name == module_name)
+                    .map(|module| (module.address, module.name.clone()))
+            })
+            .map(|(address, module)| ItemPath::member(address, module, name.clone()))?,
+        [address, module_name, name] => ItemPath::member(
+            parse_address(analysis, address)?,
+            module_name.clone(),
+            name.clone(),
+        ),
+        _ => return None,
+    };
+
+    let name = path.member.as_ref()?;
+    let find_function = |symbols: &FileSymbols| {
+        symbols
+            .module(&path)?
+            .functions
+            .iter()
+            .find(|function| &function.name == name)
+            .cloned()
+    };
+    if let Some(function) = find_function(current_file) {
+        return Some(function);
+    }
+    // modules are taken from the dependencies the file is compiled with
+    let db = analysis.db();
+    db.available_files
+        .keys()
+        .filter(|fpath| fpath.as_str() != current_file.fpath && db.is_dependency_file(fpath))
+        .filter_map(|fpath| db.last_valid_symbols(fpath))
+        .find_map(|symbols| find_function(&symbols))
+}
+
+/// Signature of the function and the spans of the `list` parameters in it.
+fn signature_with_parameters(
+    function: &FunctionSymbol,
+    list: ArgumentList,
+) -> (String, Vec<Span>) {
+    let label = function.signature();
+    let parameters: Vec<String> = match list {
+        ArgumentList::Values => function
+            .parameters
+            .iter()
+            .map(|(name, ty)| format!("{}: {}", name, ty))
+            .collect(),
+        ArgumentList::Types => function
+            .type_parameters
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+    };
+    // parameters go in the order of declaration after the name
+    let name = format!("fun {}", function.name);
+    let mut pos = label.find(&name).map_or(0, |idx| idx + name.len());
+    if list == ArgumentList::Values {
+        pos = label[pos..].find('(').map_or(pos, |idx| pos + idx);
+    }
+    let mut spans = vec![];
+    for parameter in parameters {
+        match label[pos..].find(&parameter) {
+            Some(idx) => {
+                let start = pos + idx;
+                pos = start + parameter.len();
+                spans.push((start, pos));
+            }
+            None => break,
+        }
+    }
+    (label, spans)
+}
@@ -0,0 +1,187 @@
+//! Workspace-wide index of module items, used for `workspace/symbol` requests.
+//! The index is updated on every file change, so that queries do not reparse the workspace.
+//! Files with syntax errors keep the symbols of their last parsed version.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::inner::config::Config;
+use crate::inner::symbols::{FileSymbols, ItemKind, ItemPath, ModuleSymbol, Span};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedSymbol {
+    pub name: String,
+    pub kind: ItemKind,
+    /// Path of the module, which defines the item (of the module itself for modules).
+    pub path: ItemPath,
+    /// Address for modules, `Address::Module` for module members,
+    /// with the address written in the format of the dialect.
+    pub container: String,
+    pub fpath: String,
+    pub name_span: Span,
+}
+
+impl IndexedSymbol {
+    /// Fully qualified name, like `0x1::Signer::address_of`.
+    pub fn qualified_name(&self) -> String {
+        format!("{}::{}", self.container, self.name)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct SymbolIndex {
+    files: HashMap<String, Arc<[IndexedSymbol]>>,
+}
+
+impl SymbolIndex {
+    /// Reindexes the parsed file.
+    pub fn update_file(&mut self, config: &Config, symbols: &FileSymbols) {
+        let dialect = config.dialect();
+        let mut indexed = vec![];
+        for module in &symbols.modules {
+            let address = dialect.format_address(&module.address);
+            index_module(&mut indexed, &symbols.fpath, &address, module);
+        }
+        self.files.insert(symbols.fpath.clone(), indexed.into());
+    }
+
+    pub fn remove_file(&mut self, fpath: &str) {
+        self.files.remove(fpath);
+    }
+
+    pub fn clear(&mut self) {
+        self.files.clear();
+    }
+
+    /// Kind of the indexed module member, `None` if its module is not indexed.
+    pub fn member_kind(&self, path: &ItemPath) -> Option<ItemKind> {
+        let member = path.member.as_ref()?;
+        self.files
+            .values()
+            .flat_map(|symbols| symbols.iter())
+            .find(|symbol| {
+                symbol.kind != ItemKind::Module
+                    && &symbol.name == member
+                    && symbol.path.address == path.address
+                    && symbol.path.module == path.module
+            })
+            .map(|symbol| symbol.kind)
+    }
+
+    /// Symbols named exactly `name`, one per module.
+    pub fn find(&self, name: &str) -> Vec<IndexedSymbol> {
+        let mut found: Vec<_> = self
+            .files
+            .values()
+            .flat_map(|symbols| symbols.iter())
+            .filter(|symbol| symbol.name == name)
+            .cloned()
+            .collect();
+        found.sort_by(|l, r| l.container.cmp(&r.container));
+        found.dedup_by(|l, r| l.container == r.container && l.kind == r.kind);
+        found
+    }
+
+    /// Symbols fuzzy matching `query`, best matches first.
+    /// If the query contains `::`, the part before the last `::` is matched against the container.
+    pub fn query(&self, query: &str, limit: usize) -> Vec<IndexedSymbol> {
+        query_indexes(&[self], query, limit)
+    }
+}
+
+/// Symbols of all the indexes fuzzy matching `query`, best matches first.
+/// Files indexed by several of them, like the shared dependencies, are searched once.
+pub fn query_indexes(indexes: &[&SymbolIndex], query: &str, limit: usize) -> Vec<IndexedSymbol> {
+    let query = query.trim().to_lowercase();
+    let (container_query, name_query) = match query.rfind("::") {
+        Some(idx) => (Some(&query[..idx]), &query[idx + 2..]),
+        None => (None, query.as_str()),
+    };
+
+    let mut searched_files = HashSet::new();
+    let mut matches: Vec<(u8, &IndexedSymbol)> = indexes
+        .iter()
+        .flat_map(|index| index.files.iter())
+        .filter(|(fpath, _)| searched_files.insert(fpath.as_str()))
+        .flat_map(|(_, symbols)| symbols.iter())
+        .filter(|symbol| match container_query {
+            Some(container_query) => {
+                let container = symbol.container.to_lowercase();
+                let hex_container =
+                    format!("{}::{}", symbol.path.address, symbol.path.module).to_lowercase();
+                fuzzy_score(container_query, &container).is_some()
+                    || fuzzy_score(container_query, &hex_container).is_some()
+            }
+            None => true,
+        })
+        .filter_map(|symbol| {
+            fuzzy_score(name_query, &symbol.name.to_lowercase()).map(|score| (score, symbol))
+        })
+        .collect();
+    matches.sort_by(|(l_score, l), (r_score, r)| {
+        l_score
+            .cmp(r_score)
+            .then_with(|| l.name.cmp(&r.name))
+            .then_with(|| l.container.cmp(&r.container))
+    });
+    matches
+        .into_iter()
+        .take(limit)
+        .map(|(_, symbol)| symbol.clone())
+        .collect()
+}
+
+fn index_module(
+    indexed: &mut Vec<IndexedSymbol>,
+    fpath: &str,
+    address: &str,
+    module: &ModuleSymbol,
+) {
+    let container = format!("{}::{}", address, module.name);
+    let mut push = |name: &str, kind: ItemKind, name_span: Span, container: &str| {
+        indexed.push(IndexedSymbol {
+            name: name.to_owned(),
+            kind,
+            path: module.path(),
+            container: container.to_owned(),
+            fpath: fpath.to_owned(),
+            name_span,
+        })
+    };
+
+    push(&module.name, ItemKind::Module, module.name_span, address);
+    for s in &module.structs {
+        push(&s.name, ItemKind::Struct, s.name_span, &container);
+    }
+    for f in &module.functions {
+        push(&f.name, ItemKind::Function, f.name_span, &container);
+    }
+    for c in &module.constants {
+        push(&c.name, ItemKind::Constant, c.name_span, &container);
+    }
+}
+
+/// Lower is better: exact match, prefix, substring, then subsequence.
+/// Both strings are expected to be lowercased.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<u8> {
+    if query.is_empty() {
+        return Some(3);
+    }
+    if candidate == query {
+        Some(0)
+    } else if candidate.starts_with(query) {
+        Some(1)
+    } else if candidate.contains(query) {
+        Some(2)
+    } else {
+        let mut candidate_chars = candidate.chars();
+        if query
+            .chars()
+            .all(|ch| candidate_chars.any(|candidate_ch| candidate_ch == ch))
+        {
+            Some(3)
+        } else {
+            None
+        }
+    }
+}
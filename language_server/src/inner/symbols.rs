@@ -0,0 +1,1426 @@
+//! Owned view of the definitions of a single Move file and of the module items it refers to.
+//! All spans are byte offsets into the original text, before any address or `{{sender}}`
+//! rewriting has been applied.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use codespan::Span as CodespanSpan;
+use libra::move_ir_types::location::Loc;
+use libra::move_lang::parser::ast::{
+    Bind, BindList, Bind_, Constant, Definition, Exp, Exp_, Function, FunctionBody_,
+    FunctionVisibility, Kind, Kind_, ModuleAccess, ModuleAccess_, ModuleDefinition, ModuleIdent,
+    ModuleMember, Script, Sequence, SequenceItem_, SpecBlock, SpecBlockTarget_, StructDefinition,
+    StructFields, Type, Type_, Use,
+};
+use libra::move_lang::name_pool::ConstPool;
+use libra::move_lang::parser::lexer::{Lexer, Tok};
+use libra::move_lang::shared::{Address, Name};
+use libra::move_lang::strip_comments_and_verify;
+use lang::compiler::parser::parse_file;
+use lang::compiler::source_map::FileOffsetMap;
+use lang::meta_extractor::extract_type_name;
+
+use crate::inner::config::Config;
+
+pub type Span = (usize, usize);
+
+pub fn span_contains(span: Span, offset: usize) -> bool {
+    span.0 <= offset && offset <= span.1
+}
+
+pub fn is_identifier_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_'
+}
+
+/// Start of the identifier which ends at `end`, `end` itself if there's none.
+pub fn identifier_start(text: &str, end: usize) -> usize {
+    text[..end]
+        .char_indices()
+        .rev()
+        .take_while(|(_, ch)| is_identifier_char(*ch))
+        .last()
+        .map(|(idx, _)| idx)
+        .unwrap_or(end)
+}
+
+/// Span of the identifier which contains `offset` (or ends right at it).
+pub fn identifier_at(text: &str, offset: usize) -> Option<Span> {
+    if offset > text.len() || !text.is_char_boundary(offset) {
+        return None;
+    }
+    let start = identifier_start(text, offset);
+    let end = text[offset..]
+        .char_indices()
+        .find(|(_, ch)| !is_identifier_char(*ch))
+        .map(|(idx, _)| offset + idx)
+        .unwrap_or_else(|| text.len());
+    if start == end {
+        None
+    } else {
+        Some((start, end))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    Module,
+    Struct,
+    Function,
+    Constant,
+}
+
+/// Fully qualified path to a module or to one of its members.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ItemPath {
+    pub address: Address,
+    pub module: String,
+    pub member: Option<String>,
+}
+
+impl ItemPath {
+    pub fn module(address: Address, module: String) -> ItemPath {
+        ItemPath {
+            address,
+            module,
+            member: None,
+        }
+    }
+
+    pub fn member(address: Address, module: String, member: String) -> ItemPath {
+        ItemPath {
+            address,
+            module,
+            member: Some(member),
+        }
+    }
+}
+
+/// Occurrence of a module item name inside the code.
+#[derive(Debug, Clone)]
+pub struct Reference {
+    pub kind: ItemKind,
+    pub path: ItemPath,
+    pub span: Span,
+    /// Member name in the `use` declaration, its kind is looked up in the imported module.
+    pub imported: bool,
+}
+
+/// Occurrence of a struct field name: in field access, pack or unpack.
+#[derive(Debug, Clone)]
+pub struct FieldReference {
+    pub struct_path: ItemPath,
+    pub field: String,
+    pub span: Span,
+    /// Field is set by a pack or an assignment.
+    pub is_write: bool,
+}
+
+/// Usage of a function local, `definition` is the name span of its declaration.
+#[derive(Debug, Clone)]
+pub struct LocalReference {
+    pub definition: Span,
+    pub span: Span,
+    /// Local is assigned to.
+    pub is_write: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct UseMember {
+    pub name: String,
+    pub alias: Option<String>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum UseKind {
+    Module { alias: Option<String> },
+    Members(Vec<UseMember>),
+}
+
+#[derive(Debug, Clone)]
+pub struct UseSymbol {
+    pub address: Address,
+    pub module: String,
+    pub module_span: Span,
+    pub kind: UseKind,
+    /// Declaration from the `use` keyword to the `;`.
+    pub span: Span,
+}
+
+impl UseSymbol {
+    fn new(use_: &Use, builder: &SymbolsBuilder) -> UseSymbol {
+        let (ident, kind) = match use_ {
+            Use::Module(ident, alias) => (
+                ident,
+                UseKind::Module {
+                    alias: alias.as_ref().map(|alias| alias.0.value.clone()),
+                },
+            ),
+            Use::Members(ident, members) => (
+                ident,
+                UseKind::Members(
+                    members
+                        .iter()
+                        .map(|(name, alias)| UseMember {
+                            name: name.value.clone(),
+                            alias: alias.as_ref().map(|alias| alias.value.clone()),
+                            span: builder.span(name.loc),
+                        })
+                        .collect(),
+                ),
+            ),
+        };
+        let ident = &ident.0.value;
+        UseSymbol {
+            address: ident.address,
+            module: ident.name.0.value.clone(),
+            module_span: builder.span(ident.name.0.loc),
+            kind,
+            span: builder.use_span(ident.name.0.loc),
+        }
+    }
+
+    /// Name under which the imported module is visible in the code.
+    pub fn module_alias(&self) -> Option<&str> {
+        match &self.kind {
+            UseKind::Module { alias } => Some(alias.as_deref().unwrap_or(&self.module)),
+            UseKind::Members(_) => None,
+        }
+    }
+
+    /// Original name of the member imported as `alias`.
+    pub fn member_by_alias(&self, alias: &str) -> Option<&str> {
+        match &self.kind {
+            UseKind::Module { .. } => None,
+            UseKind::Members(members) => members
+                .iter()
+                .find(|member| member.alias.as_deref().unwrap_or(&member.name) == alias)
+                .map(|member| member.name.as_str()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeParameter {
+    pub name: String,
+    pub constraint: Option<String>,
+}
+
+impl fmt::Display for TypeParameter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.constraint {
+            Some(constraint) => write!(f, "{}: {}", self.name, constraint),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+fn type_parameters_list(type_parameters: &[TypeParameter]) -> String {
+    if type_parameters.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "<{}>",
+            type_parameters
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl TypeParameter {
+    fn new((name, kind): &(Name, Kind)) -> TypeParameter {
+        let constraint = match kind.value {
+            Kind_::Resource => Some("resource".to_owned()),
+            Kind_::Copyable => Some("copyable".to_owned()),
+            _ => None,
+        };
+        TypeParameter {
+            name: name.value.clone(),
+            constraint,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldSymbol {
+    pub name: String,
+    pub name_span: Span,
+    pub span: Span,
+    pub ty: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct StructSymbol {
+    pub name: String,
+    pub name_span: Span,
+    pub span: Span,
+    pub is_resource: bool,
+    pub is_native: bool,
+    pub type_parameters: Vec<TypeParameter>,
+    pub fields: Vec<FieldSymbol>,
+}
+
+impl StructSymbol {
+    pub fn signature(&self) -> String {
+        let mut signature = String::new();
+        if self.is_native {
+            signature.push_str("native ");
+        }
+        if self.is_resource {
+            signature.push_str("resource ");
+        }
+        signature.push_str("struct ");
+        signature.push_str(&self.name);
+        signature.push_str(&type_parameters_list(&self.type_parameters));
+        if !self.is_native {
+            signature.push_str(" {");
+            for (i, field) in self.fields.iter().enumerate() {
+                let separator = if i + 1 == self.fields.len() { "" } else { "," };
+                signature.push_str(&format!("\n    {}: {}{}", field.name, field.ty, separator));
+            }
+            if !self.fields.is_empty() {
+                signature.push('\n');
+            }
+            signature.push('}');
+        }
+        signature
+    }
+}
+
+/// Function parameter or local variable declaration.
+#[derive(Debug, Clone)]
+pub struct LocalSymbol {
+    pub name: String,
+    pub name_span: Span,
+    /// Declared type, `None` if it has to be inferred.
+    pub ty: Option<String>,
+    /// Bound by `let` itself, not a parameter or a field of the unpacked struct.
+    pub is_let: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionSymbol {
+    pub name: String,
+    pub name_span: Span,
+    pub span: Span,
+    pub is_public: bool,
+    pub is_native: bool,
+    pub type_parameters: Vec<TypeParameter>,
+    pub parameters: Vec<(String, String)>,
+    pub return_type: String,
+    pub acquires: Vec<String>,
+    /// Parameters first, then locals in the order of declaration.
+    pub locals: Vec<LocalSymbol>,
+}
+
+impl FunctionSymbol {
+    pub fn signature(&self) -> String {
+        let mut signature = String::new();
+        if self.is_public {
+            signature.push_str("public ");
+        }
+        if self.is_native {
+            signature.push_str("native ");
+        }
+        signature.push_str("fun ");
+        signature.push_str(&self.name);
+        signature.push_str(&type_parameters_list(&self.type_parameters));
+        signature.push('(');
+        signature.push_str(
+            &self
+                .parameters
+                .iter()
+                .map(|(name, ty)| format!("{}: {}", name, ty))
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+        signature.push(')');
+        if !self.return_type.is_empty() {
+            signature.push_str(": ");
+            signature.push_str(&self.return_type);
+        }
+        if !self.acquires.is_empty() {
+            signature.push_str(" acquires ");
+            signature.push_str(&self.acquires.join(", "));
+        }
+        signature
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConstantSymbol {
+    pub name: String,
+    pub name_span: Span,
+    pub span: Span,
+    pub ty: String,
+}
+
+impl ConstantSymbol {
+    pub fn signature(&self) -> String {
+        format!("const {}: {}", self.name, self.ty)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SpecSymbol {
+    /// Spec block header, like `spec fun transfer` or `spec module`.
+    pub name: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleSymbol {
+    pub address: Address,
+    pub name: String,
+    pub name_span: Span,
+    pub span: Span,
+    pub uses: Vec<UseSymbol>,
+    pub structs: Vec<StructSymbol>,
+    pub functions: Vec<FunctionSymbol>,
+    pub constants: Vec<ConstantSymbol>,
+    pub specs: Vec<SpecSymbol>,
+}
+
+impl ModuleSymbol {
+    pub fn path(&self) -> ItemPath {
+        ItemPath::module(self.address, self.name.clone())
+    }
+
+    /// Returns `(name_span, span)` of the member definition.
+    pub fn member_spans(&self, kind: ItemKind, name: &str) -> Option<(Span, Span)> {
+        match kind {
+            ItemKind::Module => None,
+            ItemKind::Struct => self
+                .structs
+                .iter()
+                .find(|s| s.name == name)
+                .map(|s| (s.name_span, s.span)),
+            ItemKind::Function => self
+                .functions
+                .iter()
+                .find(|f| f.name == name)
+                .map(|f| (f.name_span, f.span)),
+            ItemKind::Constant => self
+                .constants
+                .iter()
+                .find(|c| c.name == name)
+                .map(|c| (c.name_span, c.span)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptSymbol {
+    pub span: Span,
+    pub uses: Vec<UseSymbol>,
+    pub constants: Vec<ConstantSymbol>,
+    pub function: FunctionSymbol,
+    pub specs: Vec<SpecSymbol>,
+}
+
+/// `address` block, `name` is the address literal as written in the source.
+#[derive(Debug, Clone)]
+pub struct AddressSymbol {
+    pub name: String,
+    pub name_span: Span,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileSymbols {
+    pub fpath: String,
+    pub addresses: Vec<AddressSymbol>,
+    pub modules: Vec<ModuleSymbol>,
+    pub scripts: Vec<ScriptSymbol>,
+    pub references: Vec<Reference>,
+    pub field_references: Vec<FieldReference>,
+    pub local_references: Vec<LocalReference>,
+    /// Spans of the type parameter names, both in the declarations and in the types.
+    pub type_parameter_references: Vec<Span>,
+}
+
+impl FileSymbols {
+    /// Parses the file with the dialect and sender from `config`.
+    /// Returns `None` if the file contains syntax errors.
+    pub fn parse(config: &Config, fpath: &str, text: &str) -> Option<FileSymbols> {
+        let sender = config.sender();
+        let fname = ConstPool::push(fpath);
+        let mut files = HashMap::new();
+        let (defs, _, errors, offsets) = parse_file(
+            config.dialect().as_ref(),
+            &mut files,
+            fname,
+            text,
+            Some(sender),
+        );
+        if !errors.is_empty() {
+            return None;
+        }
+
+        let mut builder = SymbolsBuilder::new(&offsets, use_locs(fname, &files[fname]));
+        let mut symbols = FileSymbols {
+            fpath: fpath.to_owned(),
+            addresses: vec![],
+            modules: vec![],
+            scripts: vec![],
+            references: vec![],
+            field_references: vec![],
+            local_references: vec![],
+            type_parameter_references: vec![],
+        };
+        for def in &defs {
+            match def {
+                Definition::Address(loc, address, modules) => {
+                    symbols
+                        .addresses
+                        .push(address_symbol(text, builder.span(*loc), address));
+                    for module in modules {
+                        symbols.modules.push(builder.module(*address, module));
+                    }
+                }
+                Definition::Module(module) => {
+                    symbols
+                        .modules
+                        .push(builder.module(sender.as_address(), module));
+                }
+                Definition::Script(script) => symbols.scripts.push(builder.script(script)),
+            }
+        }
+        symbols.references = builder.references;
+        symbols.field_references = builder.field_references;
+        symbols.local_references = builder.local_references;
+        symbols.type_parameter_references = builder.type_parameter_references;
+        if let Some(resolved) = symbols.resolve_imported_kinds(|_| None) {
+            symbols = resolved;
+        }
+        Some(symbols)
+    }
+
+    /// Sets the kinds of the imported members to the ones returned by `member_kind`.
+    /// Members of the unknown modules get the kind of their usages within the file.
+    /// Returns `None` if every kind is already correct.
+    pub fn resolve_imported_kinds(
+        &self,
+        member_kind: impl Fn(&ItemPath) -> Option<ItemKind>,
+    ) -> Option<FileSymbols> {
+        let kinds: Vec<(usize, ItemKind)> = self
+            .references
+            .iter()
+            .enumerate()
+            .filter(|(_, reference)| reference.imported)
+            .filter_map(|(idx, reference)| {
+                let kind = member_kind(&reference.path).or_else(|| {
+                    self.references
+                        .iter()
+                        .find(|usage| !usage.imported && usage.path == reference.path)
+                        .map(|usage| usage.kind)
+                })?;
+                if kind == reference.kind {
+                    None
+                } else {
+                    Some((idx, kind))
+                }
+            })
+            .collect();
+        if kinds.is_empty() {
+            return None;
+        }
+        let mut symbols = self.clone();
+        for (idx, kind) in kinds {
+            symbols.references[idx].kind = kind;
+        }
+        Some(symbols)
+    }
+
+    pub fn reference_at(&self, offset: usize) -> Option<&Reference> {
+        self.references
+            .iter()
+            .find(|reference| span_contains(reference.span, offset))
+    }
+
+    pub fn defined_modules(&self) -> impl Iterator<Item = ItemPath> + '_ {
+        self.modules.iter().map(|module| module.path())
+    }
+
+    /// Modules of other files, which are imported or accessed by the file.
+    pub fn used_modules(&self) -> HashSet<ItemPath> {
+        self.references
+            .iter()
+            .map(|reference| {
+                ItemPath::module(reference.path.address, reference.path.module.clone())
+            })
+            .filter(|path| self.module(path).is_none())
+            .collect()
+    }
+
+    pub fn module(&self, path: &ItemPath) -> Option<&ModuleSymbol> {
+        self.modules
+            .iter()
+            .find(|module| module.address == path.address && module.name == path.module)
+    }
+
+    pub fn functions(&self) -> impl Iterator<Item = &FunctionSymbol> {
+        self.modules
+            .iter()
+            .flat_map(|module| module.functions.iter())
+            .chain(self.scripts.iter().map(|script| &script.function))
+    }
+
+    pub fn module_at(&self, offset: usize) -> Option<&ModuleSymbol> {
+        self.modules
+            .iter()
+            .find(|module| span_contains(module.span, offset))
+    }
+
+    /// `use` declarations of the module or script which contains the `offset`.
+    pub fn uses_at(&self, offset: usize) -> &[UseSymbol] {
+        if let Some(module) = self.module_at(offset) {
+            return &module.uses;
+        }
+        self.scripts
+            .iter()
+            .find(|script| span_contains(script.span, offset))
+            .map(|script| script.uses.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Function whose body contains the `offset`.
+    pub fn function_at(&self, offset: usize) -> Option<&FunctionSymbol> {
+        self.functions()
+            .find(|function| span_contains(function.span, offset))
+    }
+
+    /// Struct path and name of the field, which is defined or used at `offset`.
+    pub fn field_at(&self, offset: usize) -> Option<(ItemPath, String)> {
+        if let Some(reference) = self
+            .field_references
+            .iter()
+            .find(|reference| span_contains(reference.span, offset))
+        {
+            return Some((reference.struct_path.clone(), reference.field.clone()));
+        }
+        self.modules.iter().find_map(|module| {
+            module.structs.iter().find_map(|s| {
+                s.fields
+                    .iter()
+                    .find(|field| span_contains(field.name_span, offset))
+                    .map(|field| {
+                        let path =
+                            ItemPath::member(module.address, module.name.clone(), s.name.clone());
+                        (path, field.name.clone())
+                    })
+            })
+        })
+    }
+
+    /// Name span of the declaration of the local, which is declared or used at `offset`.
+    pub fn local_at(&self, offset: usize) -> Option<Span> {
+        if let Some(reference) = self
+            .local_references
+            .iter()
+            .find(|reference| span_contains(reference.span, offset))
+        {
+            return Some(reference.definition);
+        }
+        self.function_at(offset)?
+            .locals
+            .iter()
+            .find(|local| span_contains(local.name_span, offset))
+            .map(|local| local.name_span)
+    }
+}
+
+fn address_symbol(text: &str, span: Span, address: &Address) -> AddressSymbol {
+    let literal = text
+        .get(span.0..span.1)
+        .and_then(|block| block.strip_prefix("address"))
+        .map(|rest| {
+            let start = span.1 - rest.trim_start().len();
+            let end = text[start..span.1]
+                .find(|ch: char| !is_identifier_char(ch))
+                .map_or(span.1, |len| start + len);
+            (start, end)
+        })
+        .filter(|(start, end)| start < end);
+    match literal {
+        Some((start, end)) => AddressSymbol {
+            name: text[start..end].to_owned(),
+            name_span: (start, end),
+            span,
+        },
+        None => AddressSymbol {
+            name: address.to_string(),
+            name_span: span,
+            span,
+        },
+    }
+}
+
+/// Locations of the `use` declarations in the normalized `text`, from the keyword to the `;`.
+fn use_locs(fname: &'static str, text: &str) -> Vec<Loc> {
+    let text = match strip_comments_and_verify(fname, text) {
+        Ok((text, _)) => text,
+        Err(_) => return vec![],
+    };
+    let mut lexer = Lexer::new(&text, fname, Default::default());
+    let mut locs = vec![];
+    let mut start = None;
+    while lexer.advance().is_ok() && lexer.peek() != Tok::EOF {
+        match lexer.peek() {
+            Tok::Use => start = Some(lexer.start_loc()),
+            Tok::Semicolon => {
+                if let Some(start) = start.take() {
+                    let end = lexer.start_loc() + 1;
+                    let span = CodespanSpan::new(start as u32, end as u32);
+                    locs.push(Loc::new(fname, span));
+                }
+            }
+            _ => {}
+        }
+    }
+    locs
+}
+
+/// Local visible at the current point of the function body.
+struct ScopeLocal {
+    name: String,
+    name_span: Span,
+    /// Struct of the declared type, used to resolve field accesses.
+    struct_path: Option<ItemPath>,
+}
+
+struct SymbolsBuilder<'a> {
+    offsets: &'a FileOffsetMap,
+    /// `use` declarations of the parsed text, in the order of appearance.
+    use_locs: Vec<Loc>,
+    references: Vec<Reference>,
+    field_references: Vec<FieldReference>,
+    local_references: Vec<LocalReference>,
+    type_parameter_references: Vec<Span>,
+    uses: Vec<UseSymbol>,
+    current_module: Option<ItemPath>,
+    module_members: Vec<(ItemKind, String)>,
+    /// `(struct, field)` pairs of the current module.
+    module_fields: Vec<(String, String)>,
+    type_parameters: Vec<String>,
+    locals: Vec<LocalSymbol>,
+    scope: Vec<ScopeLocal>,
+}
+
+impl<'a> SymbolsBuilder<'a> {
+    fn new(offsets: &'a FileOffsetMap, use_locs: Vec<Loc>) -> SymbolsBuilder<'a> {
+        SymbolsBuilder {
+            offsets,
+            use_locs,
+            references: vec![],
+            field_references: vec![],
+            local_references: vec![],
+            type_parameter_references: vec![],
+            uses: vec![],
+            current_module: None,
+            module_members: vec![],
+            module_fields: vec![],
+            type_parameters: vec![],
+            locals: vec![],
+            scope: vec![],
+        }
+    }
+
+    fn span(&self, loc: Loc) -> Span {
+        let span = self.offsets.translate_span(loc.span());
+        (span.start().to_usize(), span.end().to_usize())
+    }
+
+    /// Span of the `use` declaration of the module name at `module_loc`.
+    fn use_span(&self, module_loc: Loc) -> Span {
+        let declaration = self
+            .use_locs
+            .iter()
+            .rev()
+            .find(|loc| loc.span().start() < module_loc.span().start());
+        self.span(*declaration.unwrap_or(&module_loc))
+    }
+
+    fn module(&mut self, address: Address, module: &ModuleDefinition) -> ModuleSymbol {
+        let name = module.name.0.value.clone();
+        self.current_module = Some(ItemPath::module(address, name.clone()));
+        self.module_members = module
+            .members
+            .iter()
+            .filter_map(|member| match member {
+                ModuleMember::Struct(s) => Some((ItemKind::Struct, s.name.0.value.clone())),
+                ModuleMember::Function(f) => Some((ItemKind::Function, f.name.0.value.clone())),
+                ModuleMember::Constant(c) => Some((ItemKind::Constant, c.name.0.value.clone())),
+                ModuleMember::Use(_) | ModuleMember::Spec(_) => None,
+            })
+            .collect();
+        self.module_fields = module
+            .members
+            .iter()
+            .flat_map(|member| match member {
+                ModuleMember::Struct(s) => match &s.fields {
+                    StructFields::Defined(fields) => fields
+                        .iter()
+                        .map(|(field, _)| (s.name.0.value.clone(), field.0.value.clone()))
+                        .collect(),
+                    StructFields::Native(_) => vec![],
+                },
+                _ => vec![],
+            })
+            .collect();
+        self.uses = module
+            .members
+            .iter()
+            .filter_map(|member| match member {
+                ModuleMember::Use(use_) => Some(UseSymbol::new(use_, self)),
+                _ => None,
+            })
+            .collect();
+
+        let mut symbol = ModuleSymbol {
+            address,
+            name,
+            name_span: self.span(module.name.0.loc),
+            span: self.span(module.loc),
+            uses: self.uses.clone(),
+            structs: vec![],
+            functions: vec![],
+            constants: vec![],
+            specs: vec![],
+        };
+        for use_ in &symbol.uses {
+            self.use_references(use_);
+        }
+        for member in &module.members {
+            match member {
+                ModuleMember::Struct(s) => symbol.structs.push(self.struct_def(s)),
+                ModuleMember::Function(f) => symbol.functions.push(self.function(f)),
+                ModuleMember::Constant(c) => symbol.constants.push(self.constant(c)),
+                ModuleMember::Spec(spec) => symbol.specs.push(self.spec(spec)),
+                ModuleMember::Use(_) => {}
+            }
+        }
+        self.current_module = None;
+        self.module_members.clear();
+        self.module_fields.clear();
+        symbol
+    }
+
+    fn script(&mut self, script: &Script) -> ScriptSymbol {
+        self.uses = script
+            .uses
+            .iter()
+            .map(|use_| UseSymbol::new(use_, self))
+            .collect();
+        let uses = self.uses.clone();
+        for use_ in &uses {
+            self.use_references(use_);
+        }
+        ScriptSymbol {
+            span: self.span(script.loc),
+            uses,
+            constants: script.constants.iter().map(|c| self.constant(c)).collect(),
+            function: self.function(&script.function),
+            specs: script.specs.iter().map(|spec| self.spec(spec)).collect(),
+        }
+    }
+
+    fn spec(&mut self, spec: &SpecBlock) -> SpecSymbol {
+        match &spec.value.target.value {
+            SpecBlockTarget_::Function(name) => {
+                if let Some(path) = self.resolve_name(&name.0.value, ItemKind::Function) {
+                    self.push(ItemKind::Function, path, self.span(name.0.loc));
+                }
+            }
+            SpecBlockTarget_::Structure(name) => {
+                if let Some(path) = self.resolve_name(&name.0.value, ItemKind::Struct) {
+                    self.push(ItemKind::Struct, path, self.span(name.0.loc));
+                }
+            }
+            _ => {}
+        }
+        let name = match &spec.value.target.value {
+            SpecBlockTarget_::Code => "spec".to_owned(),
+            SpecBlockTarget_::Module => "spec module".to_owned(),
+            SpecBlockTarget_::Function(name) => format!("spec fun {}", name.0.value),
+            SpecBlockTarget_::Structure(name) => format!("spec struct {}", name.0.value),
+            SpecBlockTarget_::Schema(name, _) => format!("spec schema {}", name.value),
+        };
+        SpecSymbol {
+            name,
+            span: self.span(spec.loc),
+        }
+    }
+
+    fn use_references(&mut self, use_: &UseSymbol) {
+        let module_path = ItemPath::module(use_.address, use_.module.clone());
+        self.push(ItemKind::Module, module_path.clone(), use_.module_span);
+        if let UseKind::Members(members) = &use_.kind {
+            for member in members {
+                let path =
+                    ItemPath::member(use_.address, use_.module.clone(), member.name.clone());
+                // the kind is set by `resolve_imported_kinds`, once the file is built
+                self.references.push(Reference {
+                    kind: ItemKind::Function,
+                    path,
+                    span: member.span,
+                    imported: true,
+                });
+            }
+        }
+    }
+
+    fn struct_def(&mut self, s: &StructDefinition) -> StructSymbol {
+        self.declare_type_parameters(&s.type_parameters);
+        let (is_native, fields) = match &s.fields {
+            StructFields::Defined(fields) => (
+                false,
+                fields
+                    .iter()
+                    .map(|(field, ty)| {
+                        self.type_(ty);
+                        let name_span = self.span(field.0.loc);
+                        FieldSymbol {
+                            name: field.0.value.clone(),
+                            name_span,
+                            span: (name_span.0, self.span(ty.loc).1),
+                            ty: extract_type_name(ty.clone()),
+                        }
+                    })
+                    .collect(),
+            ),
+            StructFields::Native(_) => (true, vec![]),
+        };
+        self.type_parameters.clear();
+        StructSymbol {
+            name: s.name.0.value.clone(),
+            name_span: self.span(s.name.0.loc),
+            span: self.span(s.loc),
+            is_resource: s.resource_opt.is_some(),
+            is_native,
+            type_parameters: s.type_parameters.iter().map(TypeParameter::new).collect(),
+            fields,
+        }
+    }
+
+    fn function(&mut self, f: &Function) -> FunctionSymbol {
+        self.declare_type_parameters(&f.signature.type_parameters);
+        self.scope.clear();
+        for (var, ty) in &f.signature.parameters {
+            self.type_(ty);
+            let local = LocalSymbol {
+                name: var.0.value.clone(),
+                name_span: self.span(var.0.loc),
+                ty: Some(extract_type_name(ty.clone())),
+                is_let: false,
+            };
+            let struct_path = self.struct_path(ty);
+            self.scope.push(ScopeLocal {
+                name: local.name.clone(),
+                name_span: local.name_span,
+                struct_path,
+            });
+            self.locals.push(local);
+        }
+        self.type_(&f.signature.return_type);
+        for access in &f.acquires {
+            self.access(access, ItemKind::Struct);
+        }
+        let is_native = match &f.body.value {
+            FunctionBody_::Defined(seq) => {
+                self.sequence(seq);
+                false
+            }
+            FunctionBody_::Native => true,
+        };
+        self.type_parameters.clear();
+        self.scope.clear();
+
+        let return_type = match &f.signature.return_type.value {
+            Type_::Unit => String::new(),
+            _ => extract_type_name(f.signature.return_type.clone()),
+        };
+        FunctionSymbol {
+            name: f.name.0.value.clone(),
+            name_span: self.span(f.name.0.loc),
+            span: self.span(f.loc),
+            is_public: matches!(f.visibility, FunctionVisibility::Public(_)),
+            is_native,
+            type_parameters: f
+                .signature
+                .type_parameters
+                .iter()
+                .map(TypeParameter::new)
+                .collect(),
+            parameters: f
+                .signature
+                .parameters
+                .iter()
+                .map(|(var, ty)| (var.0.value.clone(), extract_type_name(ty.clone())))
+                .collect(),
+            return_type,
+            acquires: f.acquires.iter().map(access_name).collect(),
+            locals: std::mem::take(&mut self.locals),
+        }
+    }
+
+    fn declare_type_parameters(&mut self, type_parameters: &[(Name, Kind)]) {
+        self.type_parameters = type_parameters
+            .iter()
+            .map(|(name, _)| name.value.clone())
+            .collect();
+        for (name, _) in type_parameters {
+            let span = self.span(name.loc);
+            self.type_parameter_references.push(span);
+        }
+    }
+
+    fn constant(&mut self, c: &Constant) -> ConstantSymbol {
+        self.type_(&c.signature);
+        self.exp(&c.value);
+        ConstantSymbol {
+            name: c.name.0.value.clone(),
+            name_span: self.span(c.name.0.loc),
+            span: self.span(c.loc),
+            ty: extract_type_name(c.signature.clone()),
+        }
+    }
+
+    fn sequence(&mut self, (uses, items, _, exp): &Sequence) {
+        let uses_len = self.uses.len();
+        let scope_len = self.scope.len();
+        for use_ in uses {
+            let use_ = UseSymbol::new(use_, self);
+            self.use_references(&use_);
+            self.uses.push(use_);
+        }
+        for item in items {
+            match &item.value {
+                SequenceItem_::Seq(exp) => self.exp(exp),
+                SequenceItem_::Declare(binds, ty) => {
+                    let declared = self.declaration(binds, ty, true);
+                    self.scope.extend(declared);
+                }
+                SequenceItem_::Bind(binds, ty, exp) => {
+                    let declared = self.declaration(binds, ty, true);
+                    // declared locals are visible only after the initializer
+                    self.exp(exp);
+                    self.scope.extend(declared);
+                }
+            }
+        }
+        if let Some(exp) = exp.as_ref() {
+            self.exp(exp);
+        }
+        self.uses.truncate(uses_len);
+        self.scope.truncate(scope_len);
+    }
+
+    /// Records the declared locals, which have to be brought into the scope by the caller.
+    fn declaration(
+        &mut self,
+        binds: &BindList,
+        ty: &Option<Type>,
+        is_let: bool,
+    ) -> Vec<ScopeLocal> {
+        let locals_len = self.locals.len();
+        for bind in &binds.value {
+            self.bind(bind);
+            if let (Bind_::Var(_), Some(local)) = (&bind.value, self.locals.last_mut()) {
+                local.is_let = is_let;
+            }
+        }
+        let mut struct_path = None;
+        if let Some(ty) = ty {
+            self.type_(ty);
+            if let [bind] = binds.value.as_slice() {
+                if let (Bind_::Var(_), Some(local)) = (&bind.value, self.locals.last_mut()) {
+                    local.ty = Some(extract_type_name(ty.clone()));
+                    struct_path = self.struct_path(ty);
+                }
+            }
+        }
+        self.locals[locals_len..]
+            .iter()
+            .map(|local| ScopeLocal {
+                name: local.name.clone(),
+                name_span: local.name_span,
+                struct_path: struct_path.clone(),
+            })
+            .collect()
+    }
+
+    fn bind(&mut self, bind: &Bind) {
+        match &bind.value {
+            Bind_::Var(var) => {
+                let local = LocalSymbol {
+                    name: var.0.value.clone(),
+                    name_span: self.span(var.0.loc),
+                    ty: None,
+                    is_let: false,
+                };
+                self.locals.push(local);
+            }
+            Bind_::Unpack(access, tys, fields) => {
+                self.access(access, ItemKind::Struct);
+                self.types(tys);
+                let struct_path = self.access_path(access, ItemKind::Struct);
+                for (field, bind) in fields {
+                    self.field_reference(&struct_path, &field.0, false);
+                    self.bind(bind);
+                }
+            }
+        }
+    }
+
+    fn types(&mut self, tys: &Option<Vec<Type>>) {
+        if let Some(tys) = tys {
+            for ty in tys {
+                self.type_(ty);
+            }
+        }
+    }
+
+    fn type_(&mut self, ty: &Type) {
+        match &ty.value {
+            Type_::Apply(access, tys) => {
+                self.access(access, ItemKind::Struct);
+                for ty in tys {
+                    self.type_(ty);
+                }
+            }
+            Type_::Ref(_, ty) => self.type_(ty),
+            Type_::Fun(tys, ty) => {
+                for ty in tys {
+                    self.type_(ty);
+                }
+                self.type_(ty);
+            }
+            Type_::Multiple(tys) => {
+                for ty in tys {
+                    self.type_(ty);
+                }
+            }
+            Type_::Unit => {}
+        }
+    }
+
+    fn exp(&mut self, exp: &Exp) {
+        match &exp.value {
+            Exp_::Move(var) | Exp_::Copy(var) => {
+                self.local_reference(&var.0, false);
+            }
+            Exp_::Value(_)
+            | Exp_::Unit
+            | Exp_::Break
+            | Exp_::Continue
+            | Exp_::Spec(_)
+            | Exp_::InferredNum(_)
+            | Exp_::UnresolvedError => {}
+            Exp_::Name(access, tys) => {
+                let is_local = match &access.value {
+                    ModuleAccess_::Name(name) if tys.is_none() => {
+                        self.local_reference(name, false)
+                    }
+                    _ => false,
+                };
+                if !is_local {
+                    self.access(access, ItemKind::Constant);
+                    self.types(tys);
+                }
+            }
+            Exp_::Call(access, tys, args) => {
+                self.access(access, ItemKind::Function);
+                self.types(tys);
+                for arg in &args.value {
+                    self.exp(arg);
+                }
+            }
+            Exp_::Pack(access, tys, fields) => {
+                self.access(access, ItemKind::Struct);
+                self.types(tys);
+                let struct_path = self.access_path(access, ItemKind::Struct);
+                for (field, exp) in fields {
+                    self.field_reference(&struct_path, &field.0, true);
+                    self.exp(exp);
+                }
+            }
+            Exp_::IfElse(cond, if_true, if_false) => {
+                self.exp(cond);
+                self.exp(if_true);
+                if let Some(if_false) = if_false {
+                    self.exp(if_false);
+                }
+            }
+            Exp_::While(cond, body) => {
+                self.exp(cond);
+                self.exp(body);
+            }
+            Exp_::Block(seq) => self.sequence(seq),
+            Exp_::Lambda(binds, body) => {
+                let scope_len = self.scope.len();
+                let declared = self.declaration(binds, &None, false);
+                self.scope.extend(declared);
+                self.exp(body);
+                self.scope.truncate(scope_len);
+            }
+            Exp_::ExpList(exps) => {
+                for exp in exps {
+                    self.exp(exp);
+                }
+            }
+            Exp_::Assign(lhs, rhs) => {
+                self.assigned(lhs);
+                self.exp(rhs);
+            }
+            Exp_::BinopExp(lhs, _, rhs) | Exp_::Index(lhs, rhs) => {
+                self.exp(lhs);
+                self.exp(rhs);
+            }
+            Exp_::Return(exp) => {
+                if let Some(exp) = exp {
+                    self.exp(exp);
+                }
+            }
+            Exp_::Abort(exp)
+            | Exp_::Dereference(exp)
+            | Exp_::Loop(exp)
+            | Exp_::UnaryExp(_, exp)
+            | Exp_::Borrow(_, exp) => self.exp(exp),
+            Exp_::Dot(exp, field) => {
+                self.exp(exp);
+                let struct_path = self.dot_struct(exp, &field.value);
+                self.field_reference(&struct_path, field, false);
+            }
+            Exp_::Cast(exp, ty) | Exp_::Annotate(exp, ty) => {
+                self.exp(exp);
+                self.type_(ty);
+            }
+        }
+    }
+
+    /// Left side of the assignment, `x = ..`, `s.f = ..` or `(x, s.f) = ..`.
+    fn assigned(&mut self, lhs: &Exp) {
+        match &lhs.value {
+            Exp_::Name(access, None) => match &access.value {
+                ModuleAccess_::Name(name) if self.local_reference(name, true) => {}
+                _ => self.exp(lhs),
+            },
+            Exp_::Dot(base, field) => {
+                self.exp(base);
+                let struct_path = self.dot_struct(base, &field.value);
+                self.field_reference(&struct_path, field, true);
+            }
+            Exp_::ExpList(exps) => {
+                for exp in exps {
+                    self.assigned(exp);
+                }
+            }
+            _ => self.exp(lhs),
+        }
+    }
+
+    fn access(&mut self, access: &ModuleAccess, kind: ItemKind) {
+        match &access.value {
+            ModuleAccess_::QualifiedModuleAccess(ident, name) => {
+                let module_path = self.module_reference(ident);
+                self.push(
+                    kind,
+                    ItemPath::member(module_path.address, module_path.module, name.value.clone()),
+                    self.span(name.loc),
+                );
+            }
+            ModuleAccess_::ModuleAccess(module, name) => {
+                let module_path = self
+                    .uses
+                    .iter()
+                    .rev()
+                    .find(|use_| use_.module_alias() == Some(module.0.value.as_str()))
+                    .map(|use_| ItemPath::module(use_.address, use_.module.clone()));
+                if let Some(module_path) = module_path {
+                    self.push(
+                        ItemKind::Module,
+                        module_path.clone(),
+                        self.span(module.0.loc),
+                    );
+                    self.push(
+                        kind,
+                        ItemPath::member(
+                            module_path.address,
+                            module_path.module,
+                            name.value.clone(),
+                        ),
+                        self.span(name.loc),
+                    );
+                }
+            }
+            ModuleAccess_::Name(name) => {
+                if kind == ItemKind::Struct && self.type_parameters.contains(&name.value) {
+                    let span = self.span(name.loc);
+                    self.type_parameter_references.push(span);
+                } else if let Some(path) = self.resolve_name(&name.value, kind) {
+                    self.push(kind, path, self.span(name.loc));
+                }
+            }
+        }
+    }
+
+    /// Path of the item, which is accessed, without recording any references.
+    fn access_path(&self, access: &ModuleAccess, kind: ItemKind) -> Option<ItemPath> {
+        match &access.value {
+            ModuleAccess_::QualifiedModuleAccess(ident, name) => {
+                let ident = &ident.0.value;
+                Some(ItemPath::member(
+                    ident.address,
+                    ident.name.0.value.clone(),
+                    name.value.clone(),
+                ))
+            }
+            ModuleAccess_::ModuleAccess(module, name) => self
+                .uses
+                .iter()
+                .rev()
+                .find(|use_| use_.module_alias() == Some(module.0.value.as_str()))
+                .map(|use_| {
+                    ItemPath::member(use_.address, use_.module.clone(), name.value.clone())
+                }),
+            ModuleAccess_::Name(name) => self.resolve_name(&name.value, kind),
+        }
+    }
+
+    /// Struct of the type, references are skipped.
+    fn struct_path(&self, ty: &Type) -> Option<ItemPath> {
+        match &ty.value {
+            Type_::Apply(access, _) => self.access_path(access, ItemKind::Struct),
+            Type_::Ref(_, ty) => self.struct_path(ty),
+            _ => None,
+        }
+    }
+
+    /// Struct of the `base` expression in `base.field`: from the declared type of the local,
+    /// or the only struct of the current module with such a field.
+    fn dot_struct(&self, base: &Exp, field: &str) -> Option<ItemPath> {
+        let declared = match &base.value {
+            Exp_::Name(access, None) => match &access.value {
+                ModuleAccess_::Name(name) => self.scope_local(&name.value),
+                _ => None,
+            },
+            Exp_::Move(var) | Exp_::Copy(var) => self.scope_local(&var.0.value),
+            Exp_::Borrow(_, exp) | Exp_::Dereference(exp) => {
+                return self.dot_struct(exp, field);
+            }
+            _ => None,
+        }
+        .and_then(|local| local.struct_path.clone());
+        if declared.is_some() {
+            return declared;
+        }
+
+        let module = self.current_module.as_ref()?;
+        let mut structs = self
+            .module_fields
+            .iter()
+            .filter(|(_, name)| name == field)
+            .map(|(s, _)| s);
+        match (structs.next(), structs.next()) {
+            (Some(s), None) => Some(ItemPath::member(
+                module.address,
+                module.module.clone(),
+                s.clone(),
+            )),
+            _ => None,
+        }
+    }
+
+    fn scope_local(&self, name: &str) -> Option<&ScopeLocal> {
+        self.scope.iter().rev().find(|local| local.name == name)
+    }
+
+    /// Records the usage of the local, returns `false` if there's no such local in scope.
+    fn local_reference(&mut self, name: &Name, is_write: bool) -> bool {
+        match self.scope_local(&name.value).map(|local| local.name_span) {
+            Some(definition) => {
+                let span = self.span(name.loc);
+                self.local_references.push(LocalReference {
+                    definition,
+                    span,
+                    is_write,
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn field_reference(&mut self, struct_path: &Option<ItemPath>, field: &Name, is_write: bool) {
+        if let Some(struct_path) = struct_path {
+            self.field_references.push(FieldReference {
+                struct_path: struct_path.clone(),
+                field: field.value.clone(),
+                span: self.span(field.loc),
+                is_write,
+            });
+        }
+    }
+
+    fn module_reference(&mut self, ident: &ModuleIdent) -> ItemPath {
+        let ident = &ident.0.value;
+        let path = ItemPath::module(ident.address, ident.name.0.value.clone());
+        self.push(ItemKind::Module, path.clone(), self.span(ident.name.0.loc));
+        path
+    }
+
+    fn resolve_name(&self, name: &str, kind: ItemKind) -> Option<ItemPath> {
+        if kind == ItemKind::Struct && self.type_parameters.iter().any(|tp| tp == name) {
+            return None;
+        }
+        let imported = self.uses.iter().rev().find_map(|use_| {
+            use_.member_by_alias(name).map(|member| {
+                ItemPath::member(use_.address, use_.module.clone(), member.to_owned())
+            })
+        });
+        if imported.is_some() {
+            return imported;
+        }
+        let module = self.current_module.as_ref()?;
+        if self
+            .module_members
+            .iter()
+            .any(|(member_kind, member)| *member_kind == kind && member == name)
+        {
+            Some(ItemPath::member(
+                module.address,
+                module.module.clone(),
+                name.to_owned(),
+            ))
+        } else {
+            None
+        }
+    }
+
+    fn push(&mut self, kind: ItemKind, path: ItemPath, span: Span) {
+        self.references.push(Reference {
+            kind,
+            path,
+            span,
+            imported: false,
+        });
+    }
+}
+
+fn access_name(access: &ModuleAccess) -> String {
+    match &access.value {
+        ModuleAccess_::Name(name) => name.value.clone(),
+        ModuleAccess_::ModuleAccess(module, name) => {
+            format!("{}::{}", module.0.value, name.value)
+        }
+        ModuleAccess_::QualifiedModuleAccess(ident, name) => {
+            let ident = &ident.0.value;
+            format!("{}::{}::{}", ident.address, ident.name.0.value, name.value)
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! Types of the function locals, extracted from the typed program the checker produces.
+
+use libra::move_lang::cfgir::ast::{Function, FunctionBody_, Program};
+use libra::move_lang::compiled_unit::CompiledUnit;
+use libra::move_lang::errors::Errors;
+use libra::move_lang::hlir::ast::{BaseType, BaseType_, SingleType, SingleType_, TypeName_};
+use lang::compiler::parser::{ParserArtifact, ParsingMeta};
+use lang::compiler::source_map::FileOffsetMap;
+use lang::compiler::{CheckerResult, CompileFlow, Step};
+
+use crate::inner::db::Revision;
+use crate::inner::symbols::Span;
+
+#[derive(Debug, Clone)]
+pub struct LocalType {
+    pub name: String,
+    /// Span of the local declaration (`let` binding or function parameter).
+    pub span: Span,
+    pub ty: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FileTypes {
+    pub locals: Vec<LocalType>,
+    /// Revision of the database the file was checked with.
+    pub revision: Revision,
+}
+
+impl FileTypes {
+    pub fn new(program: &Program, fname: &str, offsets: &FileOffsetMap) -> FileTypes {
+        let mut file_types = FileTypes::default();
+        for (_, module) in program.modules.iter() {
+            for (_, function) in module.functions.iter() {
+                file_types.add_function_locals(function, fname, offsets);
+            }
+        }
+        for script in program.scripts.values() {
+            file_types.add_function_locals(&script.function, fname, offsets);
+        }
+        file_types
+    }
+
+    fn add_function_locals(&mut self, function: &Function, fname: &str, offsets: &FileOffsetMap) {
+        let locals = match &function.body.value {
+            FunctionBody_::Defined { locals, .. } => locals,
+            FunctionBody_::Native => return,
+        };
+        for (var, ty) in locals.iter() {
+            let loc = var.0.loc;
+            // compiler temporaries are named `%#N`, shadowed locals get `#N` suffix
+            let name = var.0.value.split('#').next().unwrap_or_default();
+            if loc.file() != fname || name.is_empty() || name.starts_with('%') {
+                continue;
+            }
+            let span = offsets.translate_span(loc.span());
+            self.locals.push(LocalType {
+                name: name.to_owned(),
+                span: (span.start().to_usize(), span.end().to_usize()),
+                ty: single_type_name(ty),
+            });
+        }
+    }
+
+    /// Finds the closest declaration of the local `name` before the `offset`, inside `scope`.
+    pub fn local_at(&self, name: &str, offset: usize, scope: Span) -> Option<&LocalType> {
+        self.locals
+            .iter()
+            .filter(|local| local.name == name)
+            .filter(|local| scope.0 <= local.span.0 && local.span.0 <= offset)
+            .max_by_key(|local| local.span.0)
+    }
+}
+
+/// Compile flow which stops after the type checking and returns local types for the file.
+pub struct TypesExtractor<'a> {
+    fname: &'a str,
+}
+
+impl<'a> TypesExtractor<'a> {
+    pub fn new(fname: &'a str) -> TypesExtractor<'a> {
+        TypesExtractor { fname }
+    }
+}
+
+impl<'a> CompileFlow<Result<FileTypes, Errors>> for TypesExtractor<'a> {
+    fn after_parsing(
+        &mut self,
+        parser_artifact: ParserArtifact,
+    ) -> Step<Result<FileTypes, Errors>, ParserArtifact> {
+        if parser_artifact.result.is_err() {
+            let ParserArtifact { meta, result } = parser_artifact;
+            Step::Stop(
+                result
+                    .map(|_| FileTypes::default())
+                    .map_err(|errors| meta.offsets_map.transform(errors)),
+            )
+        } else {
+            Step::Next(parser_artifact)
+        }
+    }
+
+    fn after_check(
+        &mut self,
+        meta: ParsingMeta,
+        check_result: CheckerResult,
+    ) -> Step<Result<FileTypes, Errors>, (ParsingMeta, CheckerResult)> {
+        let offsets_map = meta.offsets_map;
+        let fname = self.fname;
+        Step::Stop(match check_result {
+            Ok(program) => Ok(offsets_map
+                .0
+                .get(fname)
+                .map(|offsets| FileTypes::new(&program, fname, offsets))
+                .unwrap_or_default()),
+            Err(errors) => Err(offsets_map.transform(errors)),
+        })
+    }
+
+    fn after_translate(
+        &mut self,
+        _: ParsingMeta,
+        _: Result<Vec<CompiledUnit>, Errors>,
+    ) -> Result<FileTypes, Errors> {
+        Ok(FileTypes::default())
+    }
+}
+
+pub fn single_type_name(ty: &SingleType) -> String {
+    match &ty.value {
+        SingleType_::Base(base) => base_type_name(base),
+        SingleType_::Ref(true, base) => format!("&mut {}", base_type_name(base)),
+        SingleType_::Ref(false, base) => format!("&{}", base_type_name(base)),
+    }
+}
+
+fn base_type_name(ty: &BaseType) -> String {
+    match &ty.value {
+        BaseType_::Param(param) => param.user_specified_name.value.clone(),
+        BaseType_::Apply(_, name, args) => {
+            let mut name = match &name.value {
+                TypeName_::Builtin(builtin) => builtin.value.to_string(),
+                TypeName_::ModuleType(module, struct_name) => {
+                    format!("{}::{}", module.0.value.name.0.value, struct_name.0.value)
+                }
+            };
+            if !args.is_empty() {
+                name.push('<');
+                name.push_str(
+                    &args
+                        .iter()
+                        .map(base_type_name)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                );
+                name.push('>');
+            }
+            name
+        }
+        BaseType_::Unreachable | BaseType_::UnresolvedError => "_".to_owned(),
+    }
+}
@@ -1,7 +1,10 @@
 pub mod dispatcher;
 pub mod global_state;
+pub mod handlers;
 pub mod inner;
 pub mod main_loop;
+pub mod progress;
 pub mod req;
+pub mod semantic_tokens;
 pub mod server;
 pub mod subscriptions;
@@ -1,34 +1,44 @@
 use std::error::Error;
 use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use anyhow::bail;
 use anyhow::Result;
-use crossbeam_channel::{unbounded, Sender};
-use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
 use lsp_types::notification::{
-    DidChangeConfiguration, DidChangeTextDocument, DidChangeWatchedFiles, DidCloseTextDocument,
-    DidOpenTextDocument, PublishDiagnostics, ShowMessage,
+    Cancel, DidChangeConfiguration, DidChangeTextDocument, DidChangeWatchedFiles,
+    DidChangeWorkspaceFolders, DidCloseTextDocument, DidOpenTextDocument, PublishDiagnostics,
+    ShowMessage,
 };
 use lsp_types::request::WorkspaceConfiguration;
 use lsp_types::{
-    ConfigurationItem, ConfigurationParams, Diagnostic, MessageType, PublishDiagnosticsParams,
-    ShowMessageParams, Url,
+    ConfigurationItem, ConfigurationParams, Diagnostic, FileChangeType, MessageType,
+    NumberOrString, PublishDiagnosticsParams, ShowMessageParams, TextDocumentContentChangeEvent,
+    Url,
 };
 
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use threadpool::ThreadPool;
 
-use crate::global_state::{initialize_new_global_state, GlobalState};
+use crate::dispatcher::{PendingRequests, PoolDispatcher};
+use crate::global_state::{load_global_state, GlobalState};
+use crate::handlers;
+use crate::progress::{Progress, ProgressCreation, CHECKING_PROGRESS_TOKEN, INDEXING_PROGRESS_TOKEN};
+use crate::req;
 
 use crate::subscriptions::OpenedFiles;
-use std::collections::HashSet;
-use crate::inner::db::FileDiagnostic;
+use std::mem;
+use crate::inner::db::{self, FileDiagnostic, Revision};
+use crate::inner::typing::FileTypes;
 use crate::inner::config::Config;
 use crate::inner::analysis::Analysis;
 use lang::compiler::file::MoveFile;
 use std::fmt::Debug;
 use libra::move_lang::name_pool::ConstPool;
+use dove::manifest::MANIFEST;
 
 #[derive(Debug)]
 pub struct LspError {
@@ -56,10 +66,16 @@ impl fmt::Display for LspError {
 
 impl Error for LspError {}
 
+/// Files are checked only after there are no changes for this long.
+const CHECK_FILES_DELAY: Duration = Duration::from_millis(200);
+
 #[derive(Debug)]
 pub enum ResponseEvent {
     Respond(Response),
-    Diagnostic(Vec<FileDiagnostic>),
+    /// Diagnostics computed with the database of the revision.
+    Diagnostic(Revision, Vec<FileDiagnostic>),
+    FileTypes(Revision, Vec<(String, FileTypes)>),
+    ShowMessage(ShowMessageParams),
 }
 
 #[derive(Debug)]
@@ -67,12 +83,17 @@ pub enum FileSystemEvent {
     AddFile(MoveFile<'static, 'static>),
     RemoveFile(String),
     ChangeFile(MoveFile<'static, 'static>),
+    EditFile(String, Vec<TextDocumentContentChangeEvent>),
 }
 
 pub enum Event {
     Response(ResponseEvent),
     FileSystem(FileSystemEvent),
     Lsp(Message),
+    /// Files affected by the changes are to be checked.
+    CheckFiles,
+    /// Workspace folders are indexed with the new settings.
+    Reindexed(GlobalState),
 }
 
 impl fmt::Debug for Event {
@@ -93,21 +114,60 @@ impl fmt::Debug for Event {
             Event::Lsp(it) => fmt::Debug::fmt(it, f),
             Event::FileSystem(it) => fmt::Debug::fmt(it, f),
             Event::Response(it) => fmt::Debug::fmt(it, f),
+            Event::CheckFiles => f.write_str("CheckFiles"),
+            Event::Reindexed(_) => f.write_str("Reindexed"),
         }
     }
 }
 
 pub fn main_loop(global_state: &mut GlobalState, connection: &Connection) -> Result<()> {
+    run_loop(global_state, connection, None)
+}
+
+/// Requests are served with the empty state, while the workspace folders are indexed.
+pub fn main_loop_with_indexing(configs: Vec<Config>, connection: &Connection) -> Result<()> {
+    let mut global_state = GlobalState::with_workspaces(configs.clone());
+    run_loop(&mut global_state, connection, Some(configs))
+}
+
+fn run_loop(
+    global_state: &mut GlobalState,
+    connection: &Connection,
+    indexed_configs: Option<Vec<Config>>,
+) -> Result<()> {
     log::info!("starting example main loop");
 
-    let pool = ThreadPool::new(1);
+    let pool = ThreadPool::new(global_state.config().worker_threads);
     let (resp_events_sender, resp_events_receiver) = unbounded::<ResponseEvent>();
     let (fs_events_sender, fs_events_receiver) = unbounded::<FileSystemEvent>();
 
     let mut loop_state = LoopState::default();
 
+    match indexed_configs {
+        Some(configs) => start_indexing(&pool, connection, &mut loop_state, configs),
+        None => {
+            // files loaded on the start are checked right away
+            let progress = checking_progress(global_state, connection, &loop_state);
+            check_affected_files(
+                &pool,
+                &resp_events_sender,
+                global_state,
+                &loop_state,
+                progress,
+            );
+        }
+    }
+
     log::info!("server initialized, serving requests");
     loop {
+        let check_files_timer = match loop_state.check_files_deadline {
+            Some(deadline) => crossbeam_channel::at(deadline),
+            None => crossbeam_channel::never(),
+        };
+        let reindexed = match &loop_state.reindexed {
+            Some(reindexed) => reindexed.clone(),
+            None => crossbeam_channel::never(),
+        };
         let event = crossbeam_channel::select! {
             recv(&connection.receiver) -> message => match message {
                 Ok(message) => Event::Lsp(message),
@@ -115,9 +175,26 @@ pub fn main_loop(global_state: &mut GlobalState, connection: &Connection) -> Res
             },
             recv(&resp_events_receiver) -> event => Event::Response(event.unwrap()),
             recv(fs_events_receiver) -> fs_event => Event::FileSystem(fs_event.unwrap()),
+            recv(check_files_timer) -> _ => Event::CheckFiles,
+            recv(reindexed) -> new_state => match new_state {
+                Ok(new_state) => Event::Reindexed(new_state),
+                Err(_) => {
+                    log::error!("indexing of the workspace folders failed");
+                    loop_state.reindexed = None;
+                    continue;
+                }
+            },
         };
         if let Event::Lsp(Message::Request(req)) = &event {
             if connection.handle_shutdown(&req)? {
+                // responses are not received anymore
+                loop_state.progress_creation.cancel_all();
+                // the state is left with the last requested settings
+                if let Some(reindexed) = loop_state.reindexed.take() {
+                    if let Ok(new_state) = reindexed.recv() {
+                        replace_global_state(global_state, &loop_state, new_state);
+                    }
+                }
                 break;
             }
         }
@@ -139,6 +216,16 @@ pub struct LoopState {
     next_request_id: i32,
     opened_files: OpenedFiles,
     configuration_request_id: Option<RequestId>,
+    /// Workspace folders the settings are requested for, in the order of the items.
+    configuration_roots: Vec<Option<PathBuf>>,
+    pending_requests: PendingRequests,
+    /// Postponed by every change, so that the files are not rechecked on each keystroke.
+    check_files_deadline: Option<Instant>,
+    /// State being indexed with the new settings, the current one serves the requests meanwhile.
+    reindexed: Option<Receiver<GlobalState>>,
+    /// Files of the reindexed state are checked with the progress.
+    check_reindexed: bool,
+    progress_creation: ProgressCreation,
 }
 
 impl LoopState {
@@ -168,21 +255,72 @@ pub fn loop_turn(
         log::info!("loop turn = {:#?}", &event);
     }
     let fs_changed = match event {
+        Event::Response(ResponseEvent::Respond(response)) => {
+            if loop_state.pending_requests.complete(&response.id) {
+                on_task(ResponseEvent::Respond(response), &connection.sender);
+            }
+            false
+        }
+        Event::Response(ResponseEvent::Diagnostic(revision, file_diags)) => {
+            // newer diagnostics of the changed files are on their way
+            let file_diags = file_diags
+                .into_iter()
+                .filter(|file_diag| {
+                    let analysis = global_state.analysis_for(&file_diag.fpath);
+                    !analysis.db().is_outdated(&file_diag.fpath, revision)
+                })
+                .collect();
+            on_task(
+                ResponseEvent::Diagnostic(revision, file_diags),
+                &connection.sender,
+            );
+            false
+        }
+        Event::Response(ResponseEvent::FileTypes(revision, mut file_types)) => {
+            file_types.retain(|(fpath, _)| {
+                let analysis = global_state.analysis_for(fpath);
+                !analysis.db().is_outdated(fpath, revision)
+            });
+            global_state.update_file_types(revision, file_types);
+            false
+        }
         Event::Response(task) => {
             on_task(task, &connection.sender);
             false
         }
         Event::FileSystem(fs_event) => {
             global_state.update_from_events(vec![fs_event]);
+            // the text is kept, so the server is out of sync with the client
+            for error in global_state.take_edit_errors() {
+                show_message(MessageType::Error, error, &connection.sender);
+            }
             true
         }
         Event::Lsp(message) => {
             match message {
-                Message::Request(_) => {}
+                Message::Request(req) => {
+                    on_request(
+                        global_state,
+                        &mut loop_state.pending_requests,
+                        pool,
+                        &connection.sender,
+                        resp_events_sender,
+                        req,
+                    )?;
+                }
                 Message::Notification(not) => {
-                    on_notification(&connection.sender, fs_events_sender, loop_state, not)?;
+                    on_notification(
+                        &connection.sender,
+                        fs_events_sender,
+                        global_state,
+                        loop_state,
+                        not,
+                    )?;
                 }
                 Message::Response(resp) => {
+                    if loop_state.progress_creation.complete(&resp) {
+                        return Ok(());
+                    }
                     if Some(&resp.id) == loop_state.configuration_request_id.as_ref() {
                         loop_state.configuration_request_id = None;
                         log::info!("config update response: '{:?}", resp);
@@ -192,13 +330,36 @@ pub fn loop_turn(
                             (Some(err), _) => {
                                 log::error!("failed to fetch the server settings: {:?}", err)
                             }
-                            (None, Some(configs)) => {
-                                if let Some(new_config) = configs.get(0) {
-                                    let mut config = Config::default();
-                                    config.update(new_config);
-                                    *global_state = initialize_new_global_state(config);
+                            (None, Some(serde_json::Value::Array(settings))) => {
+                                if !settings.is_empty() {
+                                    let mut roots =
+                                        mem::take(&mut loop_state.configuration_roots);
+                                    if roots.is_empty() {
+                                        roots = global_state.workspace_roots();
+                                    }
+                                    let work_done_progress =
+                                        global_state.config().work_done_progress;
+                                    // folders without the settings of their own share the first ones
+                                    let configs: Vec<_> = roots
+                                        .into_iter()
+                                        .enumerate()
+                                        .map(|(idx, root_path)| {
+                                            let mut config = Config::default();
+                                            config.root_path = root_path;
+                                            config.work_done_progress = work_done_progress;
+                                            config.update(
+                                                settings.get(idx).unwrap_or(&settings[0]),
+                                            );
+                                            config
+                                        })
+                                        .collect();
+                                    pool.set_num_threads(configs[0].worker_threads);
+                                    start_indexing(pool, connection, loop_state, configs);
                                 }
                             }
+                            (None, Some(settings)) => {
+                                log::error!("invalid server settings response: {:?}", settings)
+                            }
                             (None, None) => log::error!(
                                 "received empty server settings response from the client"
                             ),
@@ -208,25 +369,140 @@ pub fn loop_turn(
             };
             false
         }
+        Event::CheckFiles => {
+            loop_state.check_files_deadline = None;
+            let progress = if mem::take(&mut loop_state.check_reindexed) {
+                checking_progress(global_state, connection, loop_state)
+            } else {
+                None
+            };
+            check_affected_files(pool, resp_events_sender, global_state, loop_state, progress);
+            false
+        }
+        Event::Reindexed(new_state) => {
+            loop_state.reindexed = None;
+            loop_state.check_reindexed = true;
+            replace_global_state(global_state, loop_state, new_state);
+            // files are rechecked with the new settings
+            true
+        }
     };
     if fs_changed {
-        log::info!("fs_state_changed = true, recompute diagnostics");
-        let analysis = global_state.analysis();
-
-        let files = loop_state
-            .opened_files
-            .files()
-            .iter()
-            .chain(analysis.db().module_files().keys())
-            .map(|f| f.to_string())
-            .collect::<HashSet<_>>();
-
-        let cloned_task_sender = resp_events_sender.clone();
-        pool.execute(move || compute_file_diagnostics(analysis, cloned_task_sender, files));
+        loop_state.check_files_deadline = Some(Instant::now() + CHECK_FILES_DELAY);
     }
     Ok(())
 }
 
+fn checking_progress(
+    global_state: &GlobalState,
+    connection: &Connection,
+    loop_state: &LoopState,
+) -> Option<Progress> {
+    if global_state.config().work_done_progress {
+        Some(Progress::begin(
+            &connection.sender,
+            &loop_state.progress_creation,
+            CHECKING_PROGRESS_TOKEN,
+            "Checking",
+        ))
+    } else {
+        None
+    }
+}
+
+/// Indexes the workspace folders in the pool, indexing with the previous settings is abandoned.
+fn start_indexing(
+    pool: &ThreadPool,
+    connection: &Connection,
+    loop_state: &mut LoopState,
+    configs: Vec<Config>,
+) {
+    let (sender, receiver) = crossbeam_channel::bounded(1);
+    loop_state.reindexed = Some(receiver);
+    let msg_sender = connection.sender.clone();
+    let progress_creation = loop_state.progress_creation.clone();
+    pool.execute(move || {
+        let new_state = index_workspaces(configs, &msg_sender, &progress_creation);
+        sender.send(new_state).ok();
+    });
+}
+
+fn check_affected_files(
+    pool: &ThreadPool,
+    resp_events_sender: &Sender<ResponseEvent>,
+    global_state: &mut GlobalState,
+    loop_state: &LoopState,
+    progress: Option<Progress>,
+) {
+    log::info!("recompute diagnostics of the affected files");
+    let affected_files = global_state.take_affected_files();
+
+    // every file is checked with the dialect and the dependencies of its workspace folder
+    let workspace_files = global_state
+        .group_by_workspace(affected_files)
+        .into_iter()
+        .map(|(analysis, files)| {
+            let files = files
+                .into_iter()
+                .filter(|f| {
+                    loop_state.opened_files.files().contains(f) || analysis.db().is_module_file(f)
+                })
+                .collect::<Vec<_>>();
+            (analysis, files)
+        })
+        .collect();
+
+    let cloned_task_sender = resp_events_sender.clone();
+    pool.execute(move || {
+        compute_file_diagnostics_with_progress(workspace_files, cloned_task_sender, progress)
+    });
+}
+
+/// Opened files out of the module folders are not indexed, their texts are moved to the new state.
+fn replace_global_state(
+    global_state: &mut GlobalState,
+    loop_state: &LoopState,
+    mut new_state: GlobalState,
+) {
+    let opened_files = loop_state
+        .opened_files
+        .files()
+        .iter()
+        .filter_map(|fpath| {
+            let analysis = global_state.analysis_for(fpath);
+            let text = analysis.db().available_files.get(fpath)?.to_string();
+            Some(FileSystemEvent::AddFile(MoveFile::with_content(
+                fpath.clone(),
+                text,
+            )))
+        })
+        .collect();
+    new_state.update_from_events(opened_files);
+    *global_state = new_state;
+}
+
+/// Loads the stdlib and the module files of every workspace folder,
+/// showing the progress if the client supports it.
+pub fn index_workspaces(
+    configs: Vec<Config>,
+    msg_sender: &Sender<Message>,
+    progress_creation: &ProgressCreation,
+) -> GlobalState {
+    let work_done_progress = configs.iter().any(|config| config.work_done_progress);
+    if !work_done_progress {
+        return load_global_state(configs, |_, _| {});
+    }
+    let mut progress = Progress::begin(
+        msg_sender,
+        progress_creation,
+        INDEXING_PROGRESS_TOKEN,
+        "Indexing",
+    );
+    let global_state = load_global_state(configs, |parsed, total| progress.report(parsed, total));
+    progress.end(global_state.indexed_files_message());
+    global_state
+}
+
 fn diagnostic_as_string(d: &Diagnostic) -> String {
     format!(
         "({}, {}), ({}, {}): {}",
@@ -243,14 +519,11 @@ pub fn on_task(task: ResponseEvent, msg_sender: &Sender<Message>) {
         ResponseEvent::Respond(response) => {
             msg_sender.send(response.into()).unwrap();
         }
-        ResponseEvent::Diagnostic(file_diags) => {
+        ResponseEvent::Diagnostic(_, file_diags) => {
             for file_diag in file_diags {
                 let uri = Url::from_file_path(&file_diag.fpath).unwrap();
 
-                let mut diagnostics = vec![];
-                if file_diag.diagnostic.is_some() {
-                    diagnostics.push(file_diag.diagnostic.unwrap());
-                }
+                let diagnostics = file_diag.diagnostics;
                 log::info!(
                     "Send diagnostic for file {:?}: {:#?}",
                     file_diag.fpath,
@@ -265,12 +538,64 @@ pub fn on_task(task: ResponseEvent, msg_sender: &Sender<Message>) {
                 msg_sender.send(notif.into()).unwrap();
             }
         }
+        ResponseEvent::ShowMessage(params) => {
+            let not = notification_new::<ShowMessage>(params);
+            msg_sender.send(not.into()).unwrap();
+        }
+        ResponseEvent::FileTypes(..) => {}
     }
 }
 
+fn on_request(
+    global_state: &mut GlobalState,
+    pending_requests: &mut PendingRequests,
+    pool: &ThreadPool,
+    msg_sender: &Sender<Message>,
+    resp_events_sender: &Sender<ResponseEvent>,
+    req: Request,
+) -> Result<()> {
+    let mut pool_dispatcher = PoolDispatcher::new(
+        req,
+        pool,
+        global_state,
+        pending_requests,
+        msg_sender,
+        resp_events_sender,
+    );
+    pool_dispatcher
+        .on::<req::GotoDefinition>(handlers::handle_goto_definition)?
+        .on::<req::HoverRequest>(handlers::handle_hover)?
+        .on::<req::Completion>(handlers::handle_completion)?
+        .on::<req::SignatureHelpRequest>(handlers::handle_signature_help)?
+        .on::<req::DocumentSymbolRequest>(handlers::handle_document_symbol)?
+        .on::<req::WorkspaceSymbol>(handlers::handle_workspace_symbol)?
+        .on::<req::References>(handlers::handle_references)?
+        .on::<req::PrepareRenameRequest>(handlers::handle_prepare_rename)?
+        .on::<req::Rename>(handlers::handle_rename)?
+        .on::<req::Formatting>(handlers::handle_formatting)?
+        .on::<req::RangeFormatting>(handlers::handle_range_formatting)?
+        .on::<req::SemanticTokensFullRequest>(handlers::handle_semantic_tokens)?
+        .on::<req::SemanticTokensRangeRequest>(handlers::handle_semantic_tokens_range)?
+        .on::<req::CodeLensRequest>(handlers::handle_code_lens)?
+        .on::<req::CodeActionRequest>(handlers::handle_code_action)?
+        .on::<req::InlayHintRequest>(handlers::handle_inlay_hints)?
+        .on::<req::FoldingRangeRequest>(handlers::handle_folding_range)?
+        .on::<req::SelectionRangeRequest>(handlers::handle_selection_range)?
+        .on::<req::DocumentHighlightRequest>(handlers::handle_document_highlight)?
+        .on::<req::CallHierarchyPrepare>(handlers::handle_call_hierarchy_prepare)?
+        .on::<req::CallHierarchyIncomingCalls>(handlers::handle_call_hierarchy_incoming)?
+        .on::<req::CallHierarchyOutgoingCalls>(handlers::handle_call_hierarchy_outgoing)?
+        .on_with_events::<req::ExecuteCommand>(handlers::handle_execute_command)?
+        .on::<req::ExecuteScript>(handlers::handle_execute_script)?
+        .on::<req::DecompiledSource>(handlers::handle_decompiled_source)?
+        .finish();
+    Ok(())
+}
+
 fn on_notification(
     msg_sender: &Sender<Message>,
     fs_events_sender: &Sender<FileSystemEvent>,
+    global_state: &GlobalState,
     loop_state: &mut LoopState,
     not: Notification,
 ) -> Result<()> {
@@ -289,18 +614,13 @@ fn on_notification(
         Err(not) => not,
     };
     let not = match notification_cast::<DidChangeTextDocument>(not) {
-        Ok(mut params) => {
+        Ok(params) => {
             let fpath = uri_to_str(params.text_document.uri)?;
-
-            let new_text = params
-                .content_changes
-                .pop()
-                .ok_or_else(|| anyhow::anyhow!("empty changes".to_string()))?
-                .text;
-
-            let changed_file = MoveFile::with_content(fpath.clone(), new_text);
             fs_events_sender
-                .send(FileSystemEvent::ChangeFile(changed_file))
+                .send(FileSystemEvent::EditFile(
+                    fpath.clone(),
+                    params.content_changes,
+                ))
                 .unwrap();
             loop_state.opened_files.add(fpath);
             return Ok(());
@@ -320,21 +640,38 @@ fn on_notification(
         Ok(_) => {
             // As stated in https://github.com/microsoft/language-server-protocol/issues/676,
             // this notification's parameters should be ignored and the actual config queried separately.
-            let request_id = loop_state.next_request_id();
-            let config_item = ConfigurationItem {
-                section: Some("move".to_string()),
-                scope_uri: None,
-            };
-            let request = request_new::<WorkspaceConfiguration>(
-                request_id.clone(),
-                ConfigurationParams {
-                    items: vec![config_item],
-                },
-            );
-            log::info!("Sending config request: {:?}", &request);
-            msg_sender.send(request.into())?;
-            loop_state.configuration_request_id = Some(request_id);
-
+            request_configuration(msg_sender, loop_state, global_state.workspace_roots())?;
+            return Ok(());
+        }
+        Err(not) => not,
+    };
+    let not = match notification_cast::<DidChangeWorkspaceFolders>(not) {
+        Ok(params) => {
+            let removed: Vec<_> = params
+                .event
+                .removed
+                .into_iter()
+                .filter_map(|folder| folder.uri.to_file_path().ok())
+                .collect();
+            let mut roots: Vec<_> = global_state
+                .workspace_roots()
+                .into_iter()
+                .flatten()
+                .filter(|root| !removed.contains(root))
+                .collect();
+            for folder in params.event.added {
+                if let Ok(root) = folder.uri.to_file_path() {
+                    if !roots.contains(&root) {
+                        roots.push(root);
+                    }
+                }
+            }
+            let mut roots: Vec<_> = roots.into_iter().map(Some).collect();
+            if roots.is_empty() {
+                roots.push(None);
+            }
+            // folders are reindexed with their own settings
+            request_configuration(msg_sender, loop_state, roots)?;
             return Ok(());
         }
         Err(not) => not,
@@ -343,10 +680,46 @@ fn on_notification(
         Ok(params) => {
             for file_event in params.changes {
                 let fpath = uri_to_str(file_event.uri)?;
-                loop_state.opened_files.remove(fpath.clone());
-                fs_events_sender
-                    .send(FileSystemEvent::RemoveFile(fpath))
-                    .unwrap();
+                if Path::new(&fpath).ends_with(MANIFEST) {
+                    // settings are reloaded together with the manifest
+                    request_configuration(
+                        msg_sender,
+                        loop_state,
+                        global_state.workspace_roots(),
+                    )?;
+                    continue;
+                }
+                match file_event.typ {
+                    FileChangeType::Deleted => {
+                        loop_state.opened_files.remove(fpath.clone());
+                        fs_events_sender
+                            .send(FileSystemEvent::RemoveFile(fpath))
+                            .unwrap();
+                    }
+                    _ => {
+                        if let Some(fs_event) = reload_file(global_state, loop_state, fpath) {
+                            fs_events_sender.send(fs_event).unwrap();
+                        }
+                    }
+                }
+            }
+            return Ok(());
+        }
+        Err(not) => not,
+    };
+    let not = match notification_cast::<Cancel>(not) {
+        Ok(params) => {
+            let id: RequestId = match params.id {
+                NumberOrString::Number(id) => id.into(),
+                NumberOrString::String(id) => id.into(),
+            };
+            if loop_state.pending_requests.cancel(&id) {
+                let response = Response::new_err(
+                    id,
+                    ErrorCode::RequestCanceled as i32,
+                    "canceled by client".to_string(),
+                );
+                msg_sender.send(response.into()).unwrap();
             }
             return Ok(());
         }
@@ -358,6 +731,57 @@ fn on_notification(
     Ok(())
 }
 
+/// Content of the opened files comes from the editor, files unknown to the server
+/// are loaded only if they could be used as dependencies.
+fn reload_file(
+    global_state: &GlobalState,
+    loop_state: &LoopState,
+    fpath: String,
+) -> Option<FileSystemEvent> {
+    if loop_state.opened_files.files().contains(&fpath) {
+        return None;
+    }
+    let analysis = global_state.analysis_for(&fpath);
+    let db = analysis.db();
+    let is_tracked = db.available_files.contains_key(&fpath);
+    if !is_tracked && !db.is_dependency_file(&fpath) {
+        return None;
+    }
+    match MoveFile::load(&fpath) {
+        Ok(file) if is_tracked => Some(FileSystemEvent::ChangeFile(file)),
+        Ok(file) => Some(FileSystemEvent::AddFile(file)),
+        Err(err) => {
+            log::error!("failed to reload {:?}: {:?}", fpath, err);
+            None
+        }
+    }
+}
+
+/// Settings are requested for every workspace folder separately.
+fn request_configuration(
+    msg_sender: &Sender<Message>,
+    loop_state: &mut LoopState,
+    roots: Vec<Option<PathBuf>>,
+) -> Result<()> {
+    let request_id = loop_state.next_request_id();
+    let items = roots
+        .iter()
+        .map(|root| ConfigurationItem {
+            section: Some("move".to_string()),
+            scope_uri: root
+                .as_ref()
+                .and_then(|root| Url::from_directory_path(root).ok()),
+        })
+        .collect();
+    let request =
+        request_new::<WorkspaceConfiguration>(request_id.clone(), ConfigurationParams { items });
+    log::info!("Sending config request: {:?}", &request);
+    msg_sender.send(request.into())?;
+    loop_state.configuration_request_id = Some(request_id);
+    loop_state.configuration_roots = roots;
+    Ok(())
+}
+
 pub fn compute_file_diagnostics<I>(
     analysis: Analysis,
     task_sender: Sender<ResponseEvent>,
@@ -365,28 +789,60 @@ pub fn compute_file_diagnostics<I>(
 ) where
     I: IntoIterator<Item = String> + Debug,
 {
-    log::info!("Computing diagnostics for files: {:#?}", files);
-    let _pool = ConstPool::new();
-
-    let mut diagnostics = vec![];
-    for fpath in files {
-        // clear previous diagnostics for file
-        diagnostics.push(FileDiagnostic::new_empty(&fpath));
+    let files = files.into_iter().collect();
+    compute_file_diagnostics_with_progress(vec![(analysis, files)], task_sender, None)
+}
 
-        let text = match analysis.db().available_files.get(&fpath) {
-            Some(text) => text,
-            None => {
-                log::warn!("Trying to check untracked file: {:?}", fpath);
-                continue;
+fn compute_file_diagnostics_with_progress(
+    workspace_files: Vec<(Analysis, Vec<String>)>,
+    task_sender: Sender<ResponseEvent>,
+    mut progress: Option<Progress>,
+) {
+    let _pool = ConstPool::new();
+    let total: usize = workspace_files.iter().map(|(_, files)| files.len()).sum();
+
+    let mut checked = 0;
+    for (analysis, files) in workspace_files {
+        log::info!("Computing diagnostics for files: {:#?}", files);
+        let revision = analysis.db().revision;
+
+        let mut diagnostics = vec![];
+        let mut file_types = vec![];
+        for fpath in files {
+            if let Some(progress) = &mut progress {
+                progress.report(checked, total);
+            }
+            checked += 1;
+            // clear previous diagnostics for file
+            diagnostics.push(FileDiagnostic::new_empty(&fpath));
+
+            let text = match analysis.db().available_files.get(&fpath) {
+                Some(text) => text,
+                None => {
+                    log::warn!("Trying to check untracked file: {:?}", fpath);
+                    continue;
+                }
+            };
+            match analysis.check_file_with_types(MoveFile::with_content(fpath.clone(), &**text)) {
+                Ok(types) => file_types.push((fpath, types)),
+                Err(ds) => diagnostics.extend(ds),
             }
-        };
-        if let Some(d) = analysis.check_file(MoveFile::with_content(fpath, text)) {
-            diagnostics.push(d);
         }
+        task_sender
+            .send(ResponseEvent::Diagnostic(
+                revision,
+                db::group_by_file(diagnostics),
+            ))
+            .unwrap();
+        if !file_types.is_empty() {
+            task_sender
+                .send(ResponseEvent::FileTypes(revision, file_types))
+                .unwrap();
+        }
+    }
+    if let Some(progress) = progress {
+        progress.end(format!("{} files checked", total));
     }
-    task_sender
-        .send(ResponseEvent::Diagnostic(diagnostics))
-        .unwrap();
 }
 
 pub fn notification_cast<N>(notification: Notification) -> Result<N::Params, Notification>
@@ -426,7 +882,7 @@ pub fn show_message(typ: MessageType, message: impl Into<String>, sender: &Sende
     sender.send(not.into()).unwrap();
 }
 
-fn uri_to_str(url: Url) -> Result<String> {
+pub fn uri_to_str(url: Url) -> Result<String> {
     url.to_file_path()
         .map_err(|_| anyhow::anyhow!("invalid uri: {}", url))
         .and_then(|path| {
@@ -0,0 +1,171 @@
+//! Reporting of the long running tasks with the `window/workDoneProgress`.
+//! Every run has a token of its own, the progress begins once the client has created it.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use lsp_server::{Message, RequestId, Response};
+use lsp_types::notification::Progress as ProgressNotification;
+use lsp_types::request::WorkDoneProgressCreate;
+use lsp_types::{
+    NumberOrString, ProgressParams, ProgressParamsValue, WorkDoneProgress, WorkDoneProgressBegin,
+    WorkDoneProgressCreateParams, WorkDoneProgressEnd, WorkDoneProgressReport,
+};
+
+use crate::main_loop::{notification_new, request_new};
+
+/// Prefixes of the progress tokens, the number of the run is appended to them.
+pub const INDEXING_PROGRESS_TOKEN: &str = "move/indexing";
+pub const CHECKING_PROGRESS_TOKEN: &str = "move/checking";
+
+static NEXT_PROGRESS_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// `window/workDoneProgress/create` requests waiting for the client's response,
+/// shared by the main loop, which receives the responses, and the workers.
+#[derive(Debug, Default, Clone)]
+pub struct ProgressCreation {
+    requests: Arc<Mutex<HashMap<RequestId, Sender<bool>>>>,
+}
+
+impl ProgressCreation {
+    /// Passes the response to the progress, returns `false` if it's not a creation response.
+    pub fn complete(&self, response: &Response) -> bool {
+        match self.requests.lock().unwrap().remove(&response.id) {
+            Some(created) => {
+                if let Some(error) = &response.error {
+                    log::error!("progress is not created: {:?}", error);
+                }
+                created.send(response.error.is_none()).ok();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Progresses still waiting for the client are not shown.
+    pub fn cancel_all(&self) {
+        self.requests.lock().unwrap().clear();
+    }
+
+    fn add(&self, id: RequestId) -> Receiver<bool> {
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        self.requests.lock().unwrap().insert(id, sender);
+        receiver
+    }
+}
+
+#[derive(Debug)]
+enum State {
+    /// Waiting for the client to create the token.
+    Creating(Receiver<bool>),
+    Begun,
+    /// The client has failed to create the token.
+    Skipped,
+}
+
+pub struct Progress {
+    sender: Sender<Message>,
+    token: NumberOrString,
+    title: String,
+    percentage: u32,
+    state: State,
+}
+
+impl Progress {
+    /// Asks the client to create the progress with the new token, starting with `token_prefix`.
+    /// It begins after the successful response, reports sent before it are dropped.
+    pub fn begin(
+        sender: &Sender<Message>,
+        creation: &ProgressCreation,
+        token_prefix: &str,
+        title: &str,
+    ) -> Progress {
+        let token = format!(
+            "{}/{}",
+            token_prefix,
+            NEXT_PROGRESS_ID.fetch_add(1, Ordering::SeqCst)
+        );
+        let request_id = RequestId::from(format!("{}/create", token));
+        let created = creation.add(request_id.clone());
+        let create_req = request_new::<WorkDoneProgressCreate>(
+            request_id,
+            WorkDoneProgressCreateParams {
+                token: NumberOrString::String(token.clone()),
+            },
+        );
+        sender.send(create_req.into()).unwrap();
+
+        Progress {
+            sender: sender.clone(),
+            token: NumberOrString::String(token),
+            title: title.to_string(),
+            percentage: 0,
+            state: State::Creating(created),
+        }
+    }
+
+    /// Only the changes of the percentage are sent, not to flood the client.
+    pub fn report(&mut self, done: usize, total: usize) {
+        let percentage = if total == 0 {
+            100
+        } else {
+            (done * 100 / total) as u32
+        };
+        if percentage == self.percentage || !self.is_begun(false) {
+            return;
+        }
+        self.percentage = percentage;
+        self.send(WorkDoneProgress::Report(WorkDoneProgressReport {
+            cancellable: None,
+            message: Some(format!("{}/{}", done, total)),
+            percentage: Some(percentage),
+        }));
+    }
+
+    /// Waits for the client to create the progress, if it hasn't yet.
+    pub fn end(mut self, message: String) {
+        if !self.is_begun(true) {
+            return;
+        }
+        self.send(WorkDoneProgress::End(WorkDoneProgressEnd {
+            message: Some(message),
+        }));
+    }
+
+    /// Sends `Begin` once the progress is created.
+    fn is_begun(&mut self, wait: bool) -> bool {
+        let created = match &self.state {
+            State::Creating(created) if wait => created.recv().unwrap_or(false),
+            State::Creating(created) => match created.try_recv() {
+                Ok(created) => created,
+                Err(TryRecvError::Empty) => return false,
+                Err(TryRecvError::Disconnected) => false,
+            },
+            State::Begun => return true,
+            State::Skipped => return false,
+        };
+        if !created {
+            self.state = State::Skipped;
+            return false;
+        }
+        self.state = State::Begun;
+        self.send(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+            title: self.title.clone(),
+            cancellable: Some(false),
+            message: None,
+            percentage: Some(self.percentage),
+        }));
+        true
+    }
+
+    fn send(&self, value: WorkDoneProgress) {
+        let params = ProgressParams {
+            token: self.token.clone(),
+            value: ProgressParamsValue::WorkDone(value),
+        };
+        let not = notification_new::<ProgressNotification>(params);
+        self.sender.send(not.into()).unwrap();
+    }
+}
@@ -6,9 +6,111 @@ pub use lsp_types::{
     DiagnosticTag, DidChangeConfigurationParams, DidChangeWatchedFilesParams,
     DidChangeWatchedFilesRegistrationOptions, DocumentOnTypeFormattingParams,
     DocumentSymbolParams, DocumentSymbolResponse, FileSystemWatcher, Hover, InitializeResult,
-    MessageType, PartialResultParams, ProgressParams, ProgressParamsValue, ProgressToken,
-    PublishDiagnosticsParams, ReferenceParams, Registration, RegistrationParams, SelectionRange,
-    SelectionRangeParams, ServerCapabilities, ShowMessageParams, SignatureHelp, SymbolKind,
-    TextDocumentEdit, TextDocumentPositionParams, TextEdit, WorkDoneProgressParams,
-    WorkspaceEdit, WorkspaceSymbolParams,
+    MessageType, PartialResultParams, Position, ProgressParams, ProgressParamsValue,
+    ProgressToken, PublishDiagnosticsParams, Range, ReferenceParams, Registration,
+    RegistrationParams, SelectionRange, SelectionRangeParams, ServerCapabilities,
+    ShowMessageParams, SignatureHelp, SymbolKind, TextDocumentEdit, TextDocumentIdentifier,
+    TextDocumentPositionParams, TextEdit, Url, WorkDoneProgressParams, WorkspaceEdit,
+    WorkspaceSymbolParams,
 };
+
+use move_executor::explain::StepExecutionResult;
+use serde::{Deserialize, Serialize};
+
+pub const RUN_SCRIPT_COMMAND: &str = "move.runScript";
+pub const DRY_RUN_SCRIPT_COMMAND: &str = "move.dryRunScript";
+
+/// Argument of the script running commands.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunScriptArgs {
+    pub uri: Url,
+    /// Index of the script in the file, all of them are run if it's not set.
+    pub script: Option<usize>,
+}
+
+pub enum ExecuteScript {}
+
+impl Request for ExecuteScript {
+    type Params = ExecuteScriptParams;
+    type Result = ExecuteScriptResult;
+    const METHOD: &'static str = "move/executeScript";
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteScriptParams {
+    pub uri: Url,
+    /// Index of the script in the file, all of them are run if it's not set.
+    #[serde(default)]
+    pub script: Option<usize>,
+    /// Arguments of the script function, like `10` or `0x1`.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Meta comments applied to every script after its own ones, like `signers: 0x1`.
+    #[serde(default)]
+    pub meta: Vec<String>,
+}
+
+pub enum DecompiledSource {}
+
+impl Request for DecompiledSource {
+    type Params = DecompiledSourceParams;
+    type Result = String;
+    const METHOD: &'static str = "move/decompiledSource";
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecompiledSourceParams {
+    /// `.mv` file, its `move-decompiled` view or an on-chain module
+    /// like `move-decompiled://chain/0x1/Account`.
+    pub uri: Url,
+}
+
+/// `textDocument/inlayHint` of LSP 3.17, which is not supported by `lsp_types` yet.
+pub enum InlayHintRequest {}
+
+impl Request for InlayHintRequest {
+    type Params = InlayHintParams;
+    type Result = Option<Vec<InlayHint>>;
+    const METHOD: &'static str = "textDocument/inlayHint";
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHintParams {
+    pub text_document: TextDocumentIdentifier,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHint {
+    pub position: Position,
+    pub label: String,
+    /// `1` for types, `2` for parameters.
+    pub kind: u32,
+    pub padding_left: bool,
+    pub padding_right: bool,
+}
+
+pub const INLAY_HINT_KIND_TYPE: u32 = 1;
+pub const INLAY_HINT_KIND_PARAMETER: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteScriptResult {
+    pub steps: Vec<ExecutedStep>,
+    /// Compiler errors, nothing is executed if they're present.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutedStep {
+    pub name: String,
+    pub gas: u64,
+    pub write_set_size: usize,
+    #[serde(flatten)]
+    pub result: StepExecutionResult,
+}
@@ -0,0 +1,107 @@
+//! Conversion of the highlighted ranges to the LSP semantic tokens.
+
+use codespan::ByteIndex;
+use lang::compiler::location::File;
+use lsp_types::{SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensLegend};
+
+use crate::inner::highlighting::{HighlightKind, HighlightedRange};
+
+pub const RESOURCE: SemanticTokenModifier = SemanticTokenModifier::new("resource");
+
+const SUPPORTED_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::STRUCT,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::TYPE_PARAMETER,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::NUMBER,
+];
+
+const SUPPORTED_MODIFIERS: &[SemanticTokenModifier] = &[
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::READONLY,
+    RESOURCE,
+];
+
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: SUPPORTED_TYPES.to_vec(),
+        token_modifiers: SUPPORTED_MODIFIERS.to_vec(),
+    }
+}
+
+fn token_type(kind: HighlightKind) -> SemanticTokenType {
+    match kind {
+        HighlightKind::Module => SemanticTokenType::NAMESPACE,
+        HighlightKind::Struct | HighlightKind::Resource => SemanticTokenType::STRUCT,
+        HighlightKind::Function => SemanticTokenType::FUNCTION,
+        HighlightKind::TypeParameter => SemanticTokenType::TYPE_PARAMETER,
+        HighlightKind::Local | HighlightKind::Constant => SemanticTokenType::VARIABLE,
+        HighlightKind::Address => SemanticTokenType::NUMBER,
+    }
+}
+
+fn token_modifiers(range: &HighlightedRange) -> Vec<SemanticTokenModifier> {
+    let mut modifiers = vec![];
+    if range.is_declaration {
+        modifiers.push(SemanticTokenModifier::DECLARATION);
+    }
+    match range.kind {
+        HighlightKind::Constant => modifiers.push(SemanticTokenModifier::READONLY),
+        HighlightKind::Resource => modifiers.push(RESOURCE),
+        _ => {}
+    }
+    modifiers
+}
+
+fn type_index(ty: SemanticTokenType) -> u32 {
+    SUPPORTED_TYPES.iter().position(|it| *it == ty).unwrap() as u32
+}
+
+fn modifiers_bitset(modifiers: &[SemanticTokenModifier]) -> u32 {
+    modifiers
+        .iter()
+        .map(|modifier| {
+            SUPPORTED_MODIFIERS
+                .iter()
+                .position(|it| it == modifier)
+                .unwrap()
+        })
+        .fold(0, |bitset, idx| bitset | (1 << idx))
+}
+
+/// Encodes the ranges relative to each other, as the protocol requires.
+/// Ranges must be ordered and not overlapping, multiline ones are skipped.
+pub fn to_semantic_tokens(text: &str, ranges: &[HighlightedRange]) -> Vec<SemanticToken> {
+    let file = File::new(text);
+    let mut tokens = vec![];
+    let (mut prev_line, mut prev_start) = (0, 0);
+    for range in ranges {
+        let (start, end) = range.span;
+        let token_text = match text.get(start..end) {
+            Some(token_text) if !token_text.contains('\n') => token_text,
+            _ => continue,
+        };
+        let position = match file.position(ByteIndex(start as u32)) {
+            Ok(position) => position,
+            Err(_) => continue,
+        };
+
+        let delta_line = position.line - prev_line;
+        let delta_start = if delta_line == 0 {
+            position.character - prev_start
+        } else {
+            position.character
+        };
+        tokens.push(SemanticToken {
+            delta_line,
+            delta_start,
+            length: token_text.chars().count() as u32,
+            token_type: type_index(token_type(range.kind)),
+            token_modifiers_bitset: modifiers_bitset(&token_modifiers(range)),
+        });
+        prev_line = position.line;
+        prev_start = position.character;
+    }
+    tokens
+}
@@ -3,65 +3,174 @@ use std::path::PathBuf;
 use anyhow::Result;
 use lsp_server::{Connection, ProtocolError, RequestId};
 use lsp_types::{
-    DidChangeWatchedFilesRegistrationOptions, FileSystemWatcher, RegistrationParams,
-    ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind,
-    TextDocumentSyncOptions, WatchKind,
+    CallHierarchyServerCapability, CodeActionKind, CodeActionOptions,
+    CodeActionProviderCapability, CodeLensOptions, CompletionOptions,
+    DidChangeWatchedFilesRegistrationOptions, ExecuteCommandOptions, FileSystemWatcher,
+    FoldingRangeProviderCapability, HoverProviderCapability, OneOf, RegistrationParams,
+    RenameOptions, SelectionRangeProviderCapability, SemanticTokensFullOptions,
+    SemanticTokensOptions, SemanticTokensServerCapabilities, ServerCapabilities,
+    SignatureHelpOptions, TextDocumentSyncCapability, TextDocumentSyncKind,
+    TextDocumentSyncOptions, WatchKind, WorkDoneProgressOptions,
+    WorkspaceFoldersServerCapabilities, WorkspaceServerCapabilities,
 };
 use serde::de::DeserializeOwned;
+use dove::manifest::MANIFEST;
 
-use crate::global_state::initialize_new_global_state;
 use crate::main_loop;
 use crate::main_loop::request_new;
 use crate::inner::config::Config;
+use crate::req::{DRY_RUN_SCRIPT_COMMAND, RUN_SCRIPT_COMMAND};
+use crate::semantic_tokens::semantic_tokens_legend;
 
 fn move_language_server_capabilities() -> ServerCapabilities {
     ServerCapabilities {
         text_document_sync: Some(TextDocumentSyncCapability::Options(
             TextDocumentSyncOptions {
                 open_close: Some(true),
-                change: Some(TextDocumentSyncKind::Full),
+                change: Some(TextDocumentSyncKind::Incremental),
                 ..TextDocumentSyncOptions::default()
             },
         )),
+        definition_provider: Some(OneOf::Left(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        workspace_symbol_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        document_range_formatting_provider: Some(OneOf::Left(true)),
+        rename_provider: Some(OneOf::Right(RenameOptions {
+            prepare_provider: Some(true),
+            work_done_progress_options: WorkDoneProgressOptions::default(),
+        })),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+        selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+        document_highlight_provider: Some(OneOf::Left(true)),
+        call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![".".to_string(), ":".to_string()]),
+            ..CompletionOptions::default()
+        }),
+        signature_help_provider: Some(SignatureHelpOptions {
+            trigger_characters: Some(vec!["(".to_string(), "<".to_string()]),
+            retrigger_characters: Some(vec![",".to_string()]),
+            ..SignatureHelpOptions::default()
+        }),
+        semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
+            SemanticTokensOptions {
+                legend: semantic_tokens_legend(),
+                range: Some(true),
+                full: Some(SemanticTokensFullOptions::Bool(true)),
+                work_done_progress_options: WorkDoneProgressOptions::default(),
+            },
+        )),
+        code_lens_provider: Some(CodeLensOptions {
+            resolve_provider: Some(false),
+        }),
+        code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+            code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+            work_done_progress_options: WorkDoneProgressOptions::default(),
+            resolve_provider: None,
+        })),
+        execute_command_provider: Some(ExecuteCommandOptions {
+            commands: vec![
+                RUN_SCRIPT_COMMAND.to_string(),
+                DRY_RUN_SCRIPT_COMMAND.to_string(),
+            ],
+            work_done_progress_options: WorkDoneProgressOptions::default(),
+        }),
+        workspace: Some(WorkspaceServerCapabilities {
+            workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                supported: Some(true),
+                change_notifications: Some(OneOf::Left(true)),
+            }),
+            ..WorkspaceServerCapabilities::default()
+        }),
         ..ServerCapabilities::default()
     }
 }
 
 pub fn initialize_server(connection: &Connection) -> Result<serde_json::Value, ProtocolError> {
-    connection.initialize(serde_json::to_value(move_language_server_capabilities()).unwrap())
+    let mut capabilities = serde_json::to_value(move_language_server_capabilities()).unwrap();
+    // not a part of `ServerCapabilities` of `lsp_types` yet
+    capabilities["inlayHintProvider"] = serde_json::Value::Bool(true);
+    connection.initialize(capabilities)
 }
 
 pub fn parse_initialize_params(init_params: serde_json::Value) -> Result<(PathBuf, Config)> {
     let initialize_params =
         from_json::<lsp_types::InitializeParams>("InitializeParams", init_params)?;
-    if let Some(client_info) = initialize_params.client_info {
-        log::info!(
-            "Client '{}' {}",
-            client_info.name,
-            client_info.version.unwrap_or_default()
-        );
-    }
+    log_client_info(&initialize_params);
 
     let cwd = std::env::current_dir()?;
     let root = initialize_params
         .root_uri
+        .as_ref()
         .and_then(|it| it.to_file_path().ok())
         .unwrap_or(cwd);
+    let config = workspace_config(root.clone(), &initialize_params);
+    Ok((root, config))
+}
+
+/// Config of every workspace folder, or of the root if the client doesn't support folders.
+pub fn parse_workspace_configs(init_params: serde_json::Value) -> Result<Vec<Config>> {
+    let initialize_params =
+        from_json::<lsp_types::InitializeParams>("InitializeParams", init_params.clone())?;
+    let folders: Vec<PathBuf> = initialize_params
+        .workspace_folders
+        .iter()
+        .flatten()
+        .filter_map(|folder| folder.uri.to_file_path().ok())
+        .collect();
+    if folders.is_empty() {
+        let (_, config) = parse_initialize_params(init_params)?;
+        return Ok(vec![config]);
+    }
+    log_client_info(&initialize_params);
+    Ok(folders
+        .into_iter()
+        .map(|root| workspace_config(root, &initialize_params))
+        .collect())
+}
 
+fn log_client_info(initialize_params: &lsp_types::InitializeParams) {
+    if let Some(client_info) = &initialize_params.client_info {
+        log::info!(
+            "Client '{}' {}",
+            client_info.name,
+            client_info.version.clone().unwrap_or_default()
+        );
+    }
+}
+
+fn workspace_config(root: PathBuf, initialize_params: &lsp_types::InitializeParams) -> Config {
     let mut config = Config::default();
-    if let Some(value) = &initialize_params.initialization_options {
-        config.update(value);
+    config.root_path = Some(root);
+    config.work_done_progress = initialize_params
+        .capabilities
+        .window
+        .as_ref()
+        .and_then(|window| window.work_done_progress)
+        .unwrap_or(false);
+    match &initialize_params.initialization_options {
+        Some(value) => config.update(value),
+        None => {
+            config.load_manifest();
+        }
     }
-    Ok((root, config))
+    config
 }
 
 fn register_for_file_changes(connection: &Connection) {
     let move_files_watcher = FileSystemWatcher {
         glob_pattern: "**/*.move".to_string(),
-        kind: Some(WatchKind::Delete),
+        kind: Some(WatchKind::Create | WatchKind::Change | WatchKind::Delete),
+    };
+    let manifest_watcher = FileSystemWatcher {
+        glob_pattern: format!("**/{}", MANIFEST),
+        kind: Some(WatchKind::Create | WatchKind::Change | WatchKind::Delete),
     };
     let registration_options = DidChangeWatchedFilesRegistrationOptions {
-        watchers: vec![move_files_watcher],
+        watchers: vec![move_files_watcher, manifest_watcher],
     };
     let registration = lsp_types::Registration {
         id: "workspace/didChangeWatchedFiles".to_string(),
@@ -79,13 +188,12 @@ fn register_for_file_changes(connection: &Connection) {
 
 pub fn run_server(connection: &Connection) -> Result<()> {
     let init_params = initialize_server(connection)?;
-    let (_, config) = parse_initialize_params(init_params)?;
+    let configs = parse_workspace_configs(init_params)?;
     log::info!("Initialization is finished");
 
     register_for_file_changes(connection);
 
-    let mut global_state = initialize_new_global_state(config);
-    main_loop::main_loop(&mut global_state, connection)
+    main_loop::main_loop_with_indexing(configs, connection)
 }
 
 pub fn from_json<T: DeserializeOwned>(what: &'static str, json: serde_json::Value) -> Result<T> {
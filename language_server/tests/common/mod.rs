@@ -0,0 +1,37 @@
+//! Fixtures shared by the integration tests.
+#![allow(dead_code)]
+
+use lang::compiler::file::MoveFile;
+use move_language_server::global_state::{initialize_new_global_state, GlobalState};
+use move_language_server::inner::config::Config;
+use move_language_server::main_loop::FileSystemEvent;
+use resources::{modules_path, stdlib_path};
+
+/// Config of the `dialect` with the stdlib and modules folders of the resources.
+pub fn config(dialect: &str) -> Config {
+    let mut config = Config::default();
+    config.update(&serde_json::json!({
+        "dialect": dialect,
+        "stdlib_folder": stdlib_path(),
+        "modules_folders": [modules_path()],
+    }));
+    config
+}
+
+/// Global state of the `dialect` with the `(path, text)` files added.
+pub fn global_state(dialect: &str, files: &[(String, &str)]) -> GlobalState {
+    global_state_with_config(config(dialect), files)
+}
+
+pub fn global_state_with_config(config: Config, files: &[(String, &str)]) -> GlobalState {
+    let mut global_state = initialize_new_global_state(config);
+    global_state.update_from_events(
+        files
+            .iter()
+            .map(|(fpath, text)| {
+                FileSystemEvent::AddFile(MoveFile::with_content(fpath.clone(), text.to_string()))
+            })
+            .collect(),
+    );
+    global_state
+}
@@ -0,0 +1,153 @@
+use lang::compiler::ConstPool;
+use move_language_server::global_state::GlobalState;
+use move_language_server::inner::call_hierarchy::Call;
+use resources::resources_dir;
+
+mod common;
+
+const BANK: &str = r"
+address 0x2 {
+module Bank {
+    public fun withdraw(amount: u64): u64 {
+        amount
+    }
+
+    public fun withdraw_all(): u64 {
+        withdraw(10) + withdraw(20)
+    }
+}
+}
+";
+
+const SHOP: &str = r"
+address 0x2 {
+module Shop {
+    use 0x2::Bank as Vault;
+
+    public fun buy(): u64 {
+        Vault::withdraw(5)
+    }
+}
+}
+";
+
+const SCRIPT: &str = r"
+script {
+    fun main() {
+        let _ = 0x2::Bank::withdraw(1);
+        0x2::Shop::buy();
+    }
+}
+";
+
+fn file_path(name: &str) -> String {
+    resources_dir().join(name).to_string_lossy().to_string()
+}
+
+fn global_state() -> GlobalState {
+    common::global_state(
+        "libra",
+        &[
+            (file_path("bank.move"), BANK),
+            (file_path("shop.move"), SHOP),
+            (file_path("main.move"), SCRIPT),
+        ],
+    )
+}
+
+/// Names of the called or calling functions with the text of the calls.
+fn calls(
+    calls: Vec<Call>,
+    caller_source: impl Fn(&Call) -> &'static str,
+) -> Vec<(String, Vec<String>)> {
+    let mut calls: Vec<_> = calls
+        .iter()
+        .map(|call| {
+            let source = caller_source(call);
+            let texts = call
+                .call_spans
+                .iter()
+                .map(|(start, end)| source[*start..*end].to_owned())
+                .collect();
+            (call.item.name.clone(), texts)
+        })
+        .collect();
+    calls.sort();
+    calls
+}
+
+fn source_of(fpath: &str) -> &'static str {
+    if fpath == file_path("bank.move") {
+        BANK
+    } else if fpath == file_path("shop.move") {
+        SHOP
+    } else {
+        SCRIPT
+    }
+}
+
+#[test]
+fn test_prepare_call_hierarchy_at_call() {
+    let _pool = ConstPool::new();
+
+    let global_state = global_state();
+    let offset = SHOP.find("withdraw(5)").unwrap();
+    let item = global_state
+        .analysis()
+        .prepare_call_hierarchy(&file_path("shop.move"), offset)
+        .unwrap();
+    assert_eq!(item.name, "withdraw");
+    assert_eq!(item.fpath, file_path("bank.move"));
+    assert_eq!(&BANK[item.name_span.0..item.name_span.1], "withdraw");
+    assert_eq!(item.signature, "public fun withdraw(amount: u64): u64");
+}
+
+#[test]
+fn test_incoming_calls_through_aliases_and_qualified_paths() {
+    let _pool = ConstPool::new();
+
+    let global_state = global_state();
+    let offset = BANK.find("withdraw(amount").unwrap();
+    let incoming = global_state
+        .analysis()
+        .incoming_calls(&file_path("bank.move"), offset);
+    assert_eq!(
+        calls(incoming, |call| source_of(&call.item.fpath)),
+        vec![
+            ("buy".to_owned(), vec!["withdraw".to_owned()]),
+            ("main".to_owned(), vec!["withdraw".to_owned()]),
+            (
+                "withdraw_all".to_owned(),
+                vec!["withdraw".to_owned(), "withdraw".to_owned()]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_outgoing_calls() {
+    let _pool = ConstPool::new();
+
+    let global_state = global_state();
+    let offset = SCRIPT.find("main").unwrap();
+    let outgoing = global_state
+        .analysis()
+        .outgoing_calls(&file_path("main.move"), offset);
+    assert_eq!(
+        calls(outgoing, |_| SCRIPT),
+        vec![
+            ("buy".to_owned(), vec!["buy".to_owned()]),
+            ("withdraw".to_owned(), vec!["withdraw".to_owned()]),
+        ]
+    );
+
+    // the call through the module alias
+    let offset = SHOP.find("buy").unwrap();
+    let outgoing = global_state
+        .analysis()
+        .outgoing_calls(&file_path("shop.move"), offset);
+    assert_eq!(
+        calls(outgoing, |_| SHOP),
+        vec![("withdraw".to_owned(), vec!["withdraw".to_owned()])]
+    );
+}
@@ -0,0 +1,263 @@
+use lang::compiler::file::MoveFile;
+use lang::compiler::ConstPool;
+use move_language_server::global_state::GlobalState;
+use move_language_server::inner::code_actions::{ActionDiagnostic, CodeAction};
+use resources::resources_dir;
+
+mod common;
+
+fn script_path() -> String {
+    resources_dir()
+        .join("script.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn global_state(text: &str) -> GlobalState {
+    global_state_of_dialect("libra", text)
+}
+
+fn global_state_of_dialect(dialect: &str, text: &str) -> GlobalState {
+    common::global_state(dialect, &[(script_path(), text)])
+}
+
+fn span_of(text: &str, pattern: &str) -> (usize, usize) {
+    let start = text.find(pattern).unwrap();
+    (start, start + pattern.len())
+}
+
+fn code_actions(
+    text: &str,
+    range: (usize, usize),
+    diagnostics: &[ActionDiagnostic],
+) -> Vec<CodeAction> {
+    global_state(text)
+        .analysis()
+        .code_actions(&script_path(), range, diagnostics)
+}
+
+fn code_actions_in_dialect(dialect: &str, text: &str, range: (usize, usize)) -> Vec<CodeAction> {
+    global_state_of_dialect(dialect, text)
+        .analysis()
+        .code_actions(&script_path(), range, &[])
+}
+
+fn titles(actions: &[CodeAction]) -> Vec<&str> {
+    actions.iter().map(|action| action.title.as_str()).collect()
+}
+
+fn action<'a>(actions: &'a [CodeAction], title: &str) -> &'a CodeAction {
+    actions
+        .iter()
+        .find(|action| action.title == title)
+        .unwrap_or_else(|| panic!("no {:?} in {:#?}", title, actions))
+}
+
+fn apply(text: &str, action: &CodeAction) -> String {
+    let mut edits = action.edits.clone();
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.span.0));
+    let mut text = text.to_owned();
+    for edit in edits {
+        text.replace_range(edit.span.0..edit.span.1, &edit.new_text);
+    }
+    text
+}
+
+#[test]
+fn test_import_module_of_unbound_alias() {
+    let _pool = ConstPool::new();
+
+    let text = r"script {
+    fun main(s: &signer) {
+        let _ = Signer::address_of(s);
+    }
+}";
+    let global_state = global_state(text);
+    let analysis = global_state.analysis();
+    let file_diagnostics = analysis.check_file(MoveFile::with_content(script_path(), text));
+    let diagnostics: Vec<_> = file_diagnostics
+        .iter()
+        .flat_map(|file_diagnostic| file_diagnostic.diagnostics.iter())
+        .map(|diagnostic| {
+            let db = analysis.db();
+            ActionDiagnostic {
+                span: (
+                    db.offset(&script_path(), diagnostic.range.start).unwrap(),
+                    db.offset(&script_path(), diagnostic.range.end).unwrap(),
+                ),
+                message: diagnostic.message.clone(),
+            }
+        })
+        .collect();
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+
+    let actions = analysis.code_actions(&script_path(), diagnostics[0].span, &diagnostics);
+    let import = action(&actions, "Import `0x1::Signer`");
+    assert_eq!(import.diagnostic, Some(0));
+    assert_eq!(
+        apply(text, import),
+        r"script {
+    use 0x1::Signer;
+
+    fun main(s: &signer) {
+        let _ = Signer::address_of(s);
+    }
+}"
+    );
+}
+
+#[test]
+fn test_import_unbound_function_or_its_module() {
+    let _pool = ConstPool::new();
+
+    let text = r"script {
+    use 0x1::Vector;
+
+    fun main(s: &signer) {
+        let v = Vector::empty<u8>();
+        Vector::push_back(&mut v, 1);
+        let _ = address_of(s);
+    }
+}";
+    let diagnostics = vec![ActionDiagnostic {
+        span: span_of(text, "address_of"),
+        message: "Unbound function 'address_of' in current scope".to_string(),
+    }];
+    let actions = code_actions(text, diagnostics[0].span, &diagnostics);
+
+    let import_member = action(&actions, "Import `0x1::Signer::address_of`");
+    assert!(apply(text, import_member)
+        .contains("    use 0x1::Vector;\n    use 0x1::Signer::address_of;\n\n    fun main"));
+
+    let import_module = action(
+        &actions,
+        "Import `0x1::Signer` and qualify as `Signer::address_of`",
+    );
+    let fixed = apply(text, import_module);
+    assert!(fixed.contains("    use 0x1::Vector;\n    use 0x1::Signer;\n"));
+    assert!(fixed.contains("let _ = Signer::address_of(s);"));
+}
+
+#[test]
+fn test_remove_unused_uses() {
+    let _pool = ConstPool::new();
+
+    let text = r"script {
+    use 0x1::Signer::{address_of, borrow_address};
+    use 0x1::Vector;
+
+    fun main(s: &signer) {
+        let _ = address_of(s);
+    }
+}";
+    let actions = code_actions(text, span_of(text, "Vector"), &[]);
+    assert_eq!(actions.len(), 1, "{:#?}", actions);
+    assert_eq!(
+        apply(text, action(&actions, "Remove unused `use 0x1::Vector`")),
+        text.replace("    use 0x1::Vector;\n", "")
+    );
+
+    let actions = code_actions(text, span_of(text, "borrow_address"), &[]);
+    assert_eq!(
+        apply(text, action(&actions, "Remove unused `borrow_address`")),
+        text.replace(
+            "use 0x1::Signer::{address_of, borrow_address};",
+            "use 0x1::Signer::address_of;"
+        )
+    );
+
+    // used declarations are kept
+    let actions = code_actions(text, span_of(text, "main"), &[]);
+    assert!(actions.is_empty(), "{:#?}", actions);
+}
+
+#[test]
+fn test_remove_unused_member_imported_from_bech32_address() {
+    let _pool = ConstPool::new();
+
+    let text = r"script {
+    use wallet1useqqqqqqqqqqqqqqqqqqqqqqqqqqqqqgneyz4::Coins::{deposit, withdraw};
+
+    fun main(s: &signer) {
+        deposit(s);
+    }
+}";
+    let actions = code_actions_in_dialect("dfinance", text, span_of(text, "withdraw"));
+    assert_eq!(
+        apply(text, action(&actions, "Remove unused `withdraw`")),
+        text.replace("::{deposit, withdraw};", "::deposit;")
+    );
+}
+
+#[test]
+fn test_add_missing_acquires() {
+    let _pool = ConstPool::new();
+
+    let text = r"address 0x2 {
+module Storage {
+    resource struct T { value: u8 }
+
+    public fun take(addr: address): u8 {
+        let T { value } = move_from<T>(addr);
+        value
+    }
+}
+}";
+    let diagnostics = vec![ActionDiagnostic {
+        span: span_of(text, "move_from<T>(addr)"),
+        message: "Invalid call to move_from.\nThe call acquires '0x2::Storage::T', but the \
+                  'acquires' list for the current function does not contain this type. \
+                  It must be present in the calling context's acquires list"
+            .to_string(),
+    }];
+    let actions = code_actions(text, diagnostics[0].span, &diagnostics);
+    assert_eq!(
+        apply(text, action(&actions, "Add `acquires T` to `take`")),
+        text.replace(
+            "take(addr: address): u8 {",
+            "take(addr: address): u8 acquires T {"
+        )
+    );
+}
+
+#[test]
+fn test_convert_address_literal() {
+    let _pool = ConstPool::new();
+
+    let text = r"script {
+    fun main() {
+        let _ = wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh;
+    }
+}";
+    let actions = code_actions_in_dialect("dfinance", text, span_of(text, "wallet1"));
+    assert_eq!(actions.len(), 1, "{:#?}", actions);
+    assert!(actions[0].title.starts_with("Convert to hex `0x"));
+    let converted = apply(text, &actions[0]);
+    assert!(!converted.contains("wallet1"));
+
+    // and back
+    let offset = converted.find("0x").unwrap();
+    let actions = code_actions_in_dialect("dfinance", &converted, (offset, offset));
+    assert_eq!(
+        titles(&actions),
+        vec!["Convert to bech32 `wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh`"]
+    );
+    assert_eq!(apply(&converted, &actions[0]), text);
+}
+
+#[test]
+fn test_convert_address_only_into_format_of_dialect() {
+    let _pool = ConstPool::new();
+
+    let text = "script { fun main() { let _ = 0x0001; } }";
+    let offset = text.find("0x").unwrap();
+    assert_eq!(
+        titles(&code_actions(text, (offset, offset), &[])),
+        vec!["Convert to hex `0x1`"]
+    );
+
+    let actions = code_actions_in_dialect("polkadot", text, (offset, offset));
+    assert_eq!(actions.len(), 2, "{:#?}", actions);
+    assert_eq!(actions[0].title, "Convert to hex `0x1`");
+    assert!(actions[1].title.starts_with("Convert to ss58 `"));
+}
@@ -45,7 +45,7 @@ fn diagnostics(file: MoveFile) -> Vec<Diagnostic> {
 
 fn diagnostics_with_config(file: MoveFile, config: Config) -> Vec<Diagnostic> {
     let loc_ds = diagnostics_with_config_and_filename(file, config);
-    loc_ds.into_iter().filter_map(|d| d.diagnostic).collect()
+    loc_ds.into_iter().flat_map(|d| d.diagnostics).collect()
 }
 
 fn diagnostics_with_config_and_filename(file: MoveFile, config: Config) -> Vec<FileDiagnostic> {
@@ -56,20 +56,20 @@ fn diagnostics_with_config_and_filename(file: MoveFile, config: Config) -> Vec<F
     compute_file_diagnostics(state_snapshot.analysis, task_sender, vec![fpath]);
 
     let task = task_receiver.try_recv().unwrap();
-    let mut ds = match task {
-        ResponseEvent::Diagnostic(ds) => ds,
+    let ds = match task {
+        ResponseEvent::Diagnostic(_, ds) => ds,
         _ => panic!(),
     };
-    let empty = ds.remove(0);
-    assert!(empty.diagnostic.is_none());
-    ds
+    ds.into_iter()
+        .filter(|d| !d.diagnostics.is_empty())
+        .collect()
 }
 
 fn diagnostics_with_deps(
     script_file: MoveFile<'static, 'static>,
     deps: Vec<MoveFile<'static, 'static>>,
     config: Config,
-) -> Option<FileDiagnostic> {
+) -> Vec<FileDiagnostic> {
     let mut config = config;
     config.update(&serde_json::json!({ "modules_folders": [modules_path()] }));
 
@@ -114,6 +114,7 @@ mod tests {
     use move_language_server::inner::db::RootDatabase;
     use move_language_server::inner::analysis::Analysis;
     use std::collections::HashMap;
+    use std::sync::Arc;
     use lang::compiler::ConstPool;
 
     #[test]
@@ -365,11 +366,11 @@ script {
 
         let config = config!({ "modules_folders": [modules_path()] });
 
-        let mut files = HashMap::new();
+        let mut files: HashMap<String, Arc<str>> = HashMap::new();
 
         files.insert(
             path("dep_module.move"),
-            "address 0x0 { modules T { public fun how_many() {} } }".to_owned(),
+            "address 0x0 { modules T { public fun how_many() {} } }".into(),
         );
 
         let source = r"
@@ -380,21 +381,19 @@ script {
         }
     }
 ";
-        files.insert(path("module.move"), source.to_owned());
+        files.insert(path("module.move"), source.into());
 
         let db = RootDatabase {
             config,
             available_files: files,
+            ..RootDatabase::default()
         };
         let analysis = Analysis::new(db);
-        let error = analysis
-            .check_file(MoveFile::with_content(path("module.move"), source))
-            .unwrap();
-        assert_eq!(error.fpath, path("dep_module.move"));
-        assert_eq!(
-            error.diagnostic.as_ref().unwrap().message,
-            "Unexpected 'modules'"
-        );
+        let errors = analysis.check_file(MoveFile::with_content(path("module.move"), source));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].fpath, path("dep_module.move"));
+        assert_eq!(errors[0].diagnostics.len(), 1);
+        assert_eq!(errors[0].diagnostics[0].message, "Unexpected 'modules'");
     }
 
     #[test]
@@ -666,7 +665,7 @@ script {
             "dialect": "libra",
             "sender_address": "0x1",
         });
-        let error = diagnostics_with_deps(
+        let errors = diagnostics_with_deps(
             MoveFile::with_content(script_path(), source),
             vec![MoveFile::with_content(
                 ConstPool::push(&modules_path().join("debug.move").to_str().unwrap()),
@@ -674,7 +673,7 @@ script {
             )],
             config,
         );
-        assert!(error.is_none(), "{:#?}", error);
+        assert!(errors.is_empty(), "{:#?}", errors);
     }
 
     #[test]
@@ -791,4 +790,63 @@ script {
         let errors = diagnostics(MoveFile::with_content(script_path(), source));
         assert!(errors.is_empty(), "{:#?}", errors);
     }
+
+    #[test]
+    fn test_all_errors_of_the_file_are_reported() {
+        let _pool = ConstPool::new();
+
+        let source = r"
+script {
+    fun main() {
+        unknown_first();
+        unknown_second();
+    }
+}
+";
+        let errors = diagnostics(MoveFile::with_content(script_path(), source));
+        assert_eq!(errors.len(), 2, "{:#?}", errors);
+        assert_eq!(errors[0].range.start.line, 3);
+        assert!(errors[0].message.contains("unknown_first"));
+        assert_eq!(errors[1].range.start.line, 4);
+        assert!(errors[1].message.contains("unknown_second"));
+    }
+
+    #[test]
+    fn test_errors_in_dependency_are_published_for_the_dependency_file() {
+        let _pool = ConstPool::new();
+
+        let dep = MoveFile::with_content(
+            path("dep_module.move"),
+            "address 0x0 { modules T { public fun how_many() {} } }",
+        );
+        let source = r"
+script {
+    use 0x0::T;
+    fun main() {
+        T::how_many();
+    }
+}
+";
+        let config = config!({ "modules_folders": [modules_path()] });
+        let snapshot = global_state_snapshot(
+            MoveFile::with_content(script_path(), source),
+            config,
+            vec![dep],
+        );
+        let (task_sender, task_receiver) = unbounded::<ResponseEvent>();
+        let fpath: Cow<str> = script_path().into();
+        let fpath = fpath.to_string();
+        compute_file_diagnostics(snapshot.analysis, task_sender, vec![fpath.clone()]);
+
+        let ds = match task_receiver.try_recv().unwrap() {
+            ResponseEvent::Diagnostic(_, ds) => ds,
+            _ => panic!(),
+        };
+        assert_eq!(ds.len(), 2, "{:#?}", ds);
+        assert_eq!(ds[0].fpath, fpath);
+        assert!(ds[0].diagnostics.is_empty());
+        assert_eq!(ds[1].fpath, path("dep_module.move"));
+        assert_eq!(ds[1].diagnostics.len(), 1);
+        assert_eq!(ds[1].diagnostics[0].message, "Unexpected 'modules'");
+    }
 }
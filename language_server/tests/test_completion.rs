@@ -0,0 +1,156 @@
+use lang::compiler::file::MoveFile;
+use lang::compiler::ConstPool;
+use move_language_server::global_state::initialize_new_global_state;
+use move_language_server::inner::completion::{CompletionItem, CompletionKind};
+use move_language_server::inner::config::Config;
+use move_language_server::main_loop::FileSystemEvent;
+use resources::{modules_path, resources_dir, stdlib_path};
+
+fn script_path() -> String {
+    resources_dir()
+        .join("script.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn config() -> Config {
+    let mut config = Config::default();
+    config.update(&serde_json::json!({
+        "stdlib_folder": stdlib_path(),
+        "modules_folders": [modules_path()],
+    }));
+    config
+}
+
+/// Completions at the position of the `|` marker in `source`.
+fn completions(source: &str) -> Vec<CompletionItem> {
+    let offset = source.find('|').unwrap();
+    let source = source.replacen('|', "", 1);
+
+    let mut global_state = initialize_new_global_state(config());
+    global_state.update_from_events(vec![FileSystemEvent::AddFile(MoveFile::with_content(
+        script_path(),
+        source,
+    ))]);
+    global_state.analysis().completions(&script_path(), offset)
+}
+
+fn labels(items: &[CompletionItem], kind: CompletionKind) -> Vec<&str> {
+    items
+        .iter()
+        .filter(|item| item.kind == kind)
+        .map(|item| item.label.as_str())
+        .collect()
+}
+
+#[test]
+fn test_complete_modules_after_address() {
+    let _pool = ConstPool::new();
+
+    let items = completions(
+        r"
+script {
+    use 0x1::Si|
+    fun main() {}
+}
+",
+    );
+    assert_eq!(labels(&items, CompletionKind::Module), vec!["Signer"]);
+}
+
+#[test]
+fn test_complete_public_functions_after_module() {
+    let _pool = ConstPool::new();
+
+    let items = completions(
+        r"
+address 0x2 {
+    module Wallet {
+        struct Coin { value: u64 }
+        fun private_fun() {}
+        public fun public_fun() {}
+    }
+}
+script {
+    use 0x2::Wallet;
+
+    fun main() {
+        Wallet::|
+    }
+}
+",
+    );
+    assert_eq!(labels(&items, CompletionKind::Function), vec!["public_fun"]);
+    assert_eq!(labels(&items, CompletionKind::Struct), vec!["Coin"]);
+}
+
+#[test]
+fn test_complete_stdlib_function_with_signature() {
+    let _pool = ConstPool::new();
+
+    let items = completions(
+        r"
+script {
+    use 0x1::Signer;
+
+    fun main(s: &signer) {
+        let addr = Signer::add|
+    }
+}
+",
+    );
+    let item = items
+        .iter()
+        .find(|item| item.label == "address_of")
+        .unwrap();
+    assert_eq!(item.kind, CompletionKind::Function);
+    assert_eq!(
+        item.detail.as_deref(),
+        Some("public fun address_of(s: &signer): address")
+    );
+}
+
+#[test]
+fn test_complete_fields_of_parameter() {
+    let _pool = ConstPool::new();
+
+    let items = completions(
+        r"
+address 0x2 {
+    module Storage {
+        struct Item { value: u64, owner: address }
+
+        public fun value(item: &Item): u64 {
+            item.|
+        }
+    }
+}
+",
+    );
+    assert_eq!(
+        labels(&items, CompletionKind::Field),
+        vec!["owner", "value"]
+    );
+}
+
+#[test]
+fn test_complete_locals_and_builtins_in_scope() {
+    let _pool = ConstPool::new();
+
+    let items = completions(
+        r"
+script {
+    fun main(account: &signer) {
+        let amount = 10;
+        a|
+    }
+}
+",
+    );
+    assert_eq!(
+        labels(&items, CompletionKind::Local),
+        vec!["account", "amount"]
+    );
+    assert!(labels(&items, CompletionKind::BuiltinFunction).contains(&"assert"));
+    assert!(labels(&items, CompletionKind::Keyword).contains(&"abort"));
+}
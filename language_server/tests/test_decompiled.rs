@@ -0,0 +1,122 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use lang::compiler::file::MoveFile;
+use lang::compiler::ConstPool;
+use lsp_types::Url;
+use move_language_server::global_state::initialize_new_global_state;
+use move_language_server::inner::config::Config;
+use move_language_server::inner::decompiled::{
+    decompile_file, decompiled_source, decompiled_uri, span_to_location, DecompiledCache,
+    DECOMPILED_SCHEME,
+};
+use move_language_server::main_loop::FileSystemEvent;
+use resources::resources_dir;
+
+/// Modules compiled to the Libra bytecode format.
+fn libra_modules_path() -> PathBuf {
+    resources_dir()
+        .parent()
+        .unwrap()
+        .join("lang")
+        .join("tests")
+        .join("libra_std")
+        .join("modules")
+}
+
+fn debug_module_path() -> String {
+    libra_modules_path()
+        .join("0_Debug.mv")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn script_path() -> String {
+    resources_dir()
+        .join("script.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+#[test]
+fn test_decompile_libra_bytecode() {
+    let source = decompile_file(&debug_module_path()).unwrap();
+    assert!(source.contains("module Debug {"), "{}", source);
+    assert!(source.contains("fun print<"), "{}", source);
+}
+
+#[test]
+fn test_decompiled_source_of_file_and_virtual_uris() {
+    let config = Config::default();
+    let file_uri = Url::from_file_path(debug_module_path()).unwrap();
+    let virtual_uri = decompiled_uri(&debug_module_path()).unwrap();
+    assert_eq!(virtual_uri.scheme(), DECOMPILED_SCHEME);
+    assert_eq!(
+        decompiled_source(&config, &file_uri).unwrap(),
+        decompiled_source(&config, &virtual_uri).unwrap()
+    );
+}
+
+#[test]
+fn test_on_chain_module_requires_blockchain_api() {
+    let uri = Url::parse(&format!("{}://chain/0x1/Account", DECOMPILED_SCHEME)).unwrap();
+    let error = decompiled_source(&Config::default(), &uri).unwrap_err();
+    assert!(error.to_string().contains("blockchain_api"), "{}", error);
+}
+
+#[test]
+fn test_goto_definition_into_bytecode_module() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+script {
+    use 0x1::Debug;
+
+    fun main() {
+        Debug::print<u8>(&1);
+    }
+}
+";
+    let mut config = Config::default();
+    config.update(&serde_json::json!({
+        "modules_folders": [libra_modules_path()],
+    }));
+    let mut global_state = initialize_new_global_state(config);
+    global_state.update_from_events(vec![FileSystemEvent::AddFile(MoveFile::with_content(
+        script_path(),
+        source.to_owned(),
+    ))]);
+
+    let offset = source.find("print").unwrap();
+    let target = global_state
+        .analysis()
+        .goto_definition(&script_path(), offset)
+        .unwrap();
+    assert_eq!(target.fpath, debug_module_path());
+    let decompiled = decompile_file(&target.fpath).unwrap();
+    assert_eq!(
+        &decompiled[target.focus_span.0..target.focus_span.1],
+        "print"
+    );
+
+    let location = span_to_location(&target.fpath, target.focus_span).unwrap();
+    assert_eq!(location.uri, decompiled_uri(&debug_module_path()).unwrap());
+}
+
+#[test]
+fn test_decompiled_symbols_are_cached() {
+    let _pool = ConstPool::new();
+
+    let config = Config::default();
+    let cache = DecompiledCache::default();
+    let symbols = cache.symbols(&config, &debug_module_path()).unwrap();
+    let cached = cache.symbols(&config, &debug_module_path()).unwrap();
+    assert!(Arc::ptr_eq(&symbols, &cached));
+
+    // clones of the database share the cache
+    let shared = cache
+        .clone()
+        .symbols(&config, &debug_module_path())
+        .unwrap();
+    assert!(Arc::ptr_eq(&symbols, &shared));
+}
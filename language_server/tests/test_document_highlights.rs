@@ -0,0 +1,104 @@
+use lang::compiler::file::MoveFile;
+use lang::compiler::ConstPool;
+use move_language_server::global_state::initialize_new_global_state;
+use move_language_server::inner::config::Config;
+use move_language_server::inner::document_highlights::HighlightAccess;
+use move_language_server::main_loop::FileSystemEvent;
+use resources::resources_dir;
+
+fn module_path() -> String {
+    resources_dir()
+        .join("highlights.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Highlighted occurrences with the text of their lines.
+fn highlights(source: &str, offset: usize) -> Vec<(String, HighlightAccess)> {
+    let mut global_state = initialize_new_global_state(Config::default());
+    global_state.update_from_events(vec![FileSystemEvent::AddFile(MoveFile::with_content(
+        module_path(),
+        source.to_owned(),
+    ))]);
+    global_state
+        .analysis()
+        .document_highlights(&module_path(), offset)
+        .into_iter()
+        .map(|highlight| {
+            let line_start = source[..highlight.span.0]
+                .rfind('\n')
+                .map_or(0, |idx| idx + 1);
+            let line_end = source[highlight.span.0..]
+                .find('\n')
+                .map_or(source.len(), |idx| highlight.span.0 + idx);
+            (
+                source[line_start..line_end].trim().to_owned(),
+                highlight.access,
+            )
+        })
+        .collect()
+}
+
+const SOURCE: &str = r"
+address 0x1 {
+module Counter {
+    struct Counter { count: u64 }
+
+    fun increment(counter: &mut Counter): u64 {
+        let current = counter.count;
+        current = current + 1;
+        counter.count = current;
+        current
+    }
+
+    fun zero(): Counter {
+        Counter { count: 0 }
+    }
+}
+}
+";
+
+#[test]
+fn test_highlight_reads_and_writes_of_local() {
+    let _pool = ConstPool::new();
+
+    let offset = SOURCE.find("current + 1").unwrap();
+    assert_eq!(
+        highlights(SOURCE, offset),
+        vec![
+            (
+                "let current = counter.count;".to_owned(),
+                HighlightAccess::Write
+            ),
+            ("current = current + 1;".to_owned(), HighlightAccess::Write),
+            ("current = current + 1;".to_owned(), HighlightAccess::Read),
+            ("counter.count = current;".to_owned(), HighlightAccess::Read),
+            ("current".to_owned(), HighlightAccess::Read),
+        ]
+    );
+}
+
+#[test]
+fn test_highlight_reads_and_writes_of_field() {
+    let _pool = ConstPool::new();
+
+    let offset = SOURCE.find("count: u64").unwrap();
+    assert_eq!(
+        highlights(SOURCE, offset),
+        vec![
+            (
+                "struct Counter { count: u64 }".to_owned(),
+                HighlightAccess::Text
+            ),
+            (
+                "let current = counter.count;".to_owned(),
+                HighlightAccess::Read
+            ),
+            (
+                "counter.count = current;".to_owned(),
+                HighlightAccess::Write
+            ),
+            ("Counter { count: 0 }".to_owned(), HighlightAccess::Write),
+        ]
+    );
+}
@@ -0,0 +1,153 @@
+use lang::compiler::file::MoveFile;
+use lang::compiler::ConstPool;
+use move_language_server::global_state::GlobalState;
+use move_language_server::inner::config::Config;
+use move_language_server::inner::file_structure::{StructureNode, StructureNodeKind};
+use move_language_server::main_loop::FileSystemEvent;
+use resources::resources_dir;
+
+fn script_path() -> String {
+    resources_dir()
+        .join("script.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn file_structure(source: &str, config: Config) -> Vec<StructureNode> {
+    let global_state = GlobalState::new(
+        config,
+        vec![FileSystemEvent::AddFile(MoveFile::with_content(
+            script_path(),
+            source.to_owned(),
+        ))],
+    );
+    global_state
+        .analysis()
+        .file_structure(&script_path())
+        .unwrap()
+}
+
+fn outline(nodes: &[StructureNode]) -> Vec<(StructureNodeKind, &str)> {
+    nodes
+        .iter()
+        .map(|node| (node.kind, node.label.as_str()))
+        .collect()
+}
+
+fn navigation_text<'a>(source: &'a str, node: &StructureNode) -> &'a str {
+    &source[node.navigation_span.0..node.navigation_span.1]
+}
+
+#[test]
+fn test_module_outline() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+address 0x2 {
+    module Coins {
+        const ERR_ZERO: u64 = 1;
+
+        resource struct Coin {
+            value: u64,
+            owner: address,
+        }
+
+        public fun value(coin: &Coin): u64 {
+            coin.value
+        }
+
+        spec fun value {
+            aborts_if false;
+        }
+    }
+}
+";
+    let nodes = file_structure(source, Config::default());
+    assert_eq!(outline(&nodes), vec![(StructureNodeKind::Address, "0x2")]);
+
+    let modules = &nodes[0].children;
+    assert_eq!(outline(modules), vec![(StructureNodeKind::Module, "Coins")]);
+    assert_eq!(navigation_text(source, &modules[0]), "Coins");
+
+    let members = &modules[0].children;
+    assert_eq!(
+        outline(members),
+        vec![
+            (StructureNodeKind::Constant, "ERR_ZERO"),
+            (StructureNodeKind::Struct, "Coin"),
+            (StructureNodeKind::Function, "value"),
+            (StructureNodeKind::Spec, "spec fun value"),
+        ]
+    );
+    assert_eq!(
+        outline(&members[1].children),
+        vec![
+            (StructureNodeKind::Field, "value"),
+            (StructureNodeKind::Field, "owner"),
+        ]
+    );
+    assert_eq!(members[1].children[1].detail.as_deref(), Some("address"));
+    assert_eq!(
+        members[2].detail.as_deref(),
+        Some("public fun value(coin: &Coin): u64")
+    );
+    assert_eq!(navigation_text(source, &members[2]), "value");
+}
+
+#[test]
+fn test_script_outline() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+script {
+    const AMOUNT: u128 = 10;
+
+    fun main(_: &signer) {}
+}
+";
+    let nodes = file_structure(source, Config::default());
+    assert_eq!(outline(&nodes), vec![(StructureNodeKind::Script, "script")]);
+    assert_eq!(
+        outline(&nodes[0].children),
+        vec![
+            (StructureNodeKind::Constant, "AMOUNT"),
+            (StructureNodeKind::Function, "main"),
+        ]
+    );
+}
+
+#[test]
+fn test_positions_are_not_shifted_by_bech32_addresses() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+address wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh {
+    module Bank {
+        struct Account { owner: address }
+
+        public fun owner(account: &Account): address {
+            account.owner
+        }
+    }
+}
+";
+    let mut config = Config::default();
+    config.update(&serde_json::json!({"dialect": "dfinance"}));
+    let nodes = file_structure(source, config);
+
+    let address = &nodes[0];
+    assert_eq!(
+        address.label,
+        "wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh"
+    );
+    assert_eq!(
+        navigation_text(source, address),
+        "wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh"
+    );
+
+    let module = &address.children[0];
+    assert_eq!(navigation_text(source, module), "Bank");
+    let function = &module.children[1];
+    assert_eq!(navigation_text(source, function), "owner");
+    assert!(source[function.span.0..function.span.1].ends_with('}'));
+}
@@ -0,0 +1,134 @@
+use std::fs;
+use std::path::PathBuf;
+
+use lang::compiler::dialects::DialectName;
+use lsp_types::{ClientCapabilities, InitializeParams, Url};
+use move_language_server::inner::config::Config;
+use move_language_server::server::parse_initialize_params;
+use resources::stdlib_path;
+
+const MANIFEST: &str = r#"
+[package]
+name = "Project"
+account_address = "0x2"
+dialect = "libra"
+"#;
+
+/// Dove project in the temporary directory, with the fetched dependencies.
+fn dove_project(name: &str, manifest: Option<&str>) -> PathBuf {
+    let project_dir = std::env::temp_dir().join(format!("move-language-server-{}", name));
+    let _ = fs::remove_dir_all(&project_dir);
+    fs::create_dir_all(project_dir.join("modules")).unwrap();
+    fs::create_dir_all(project_dir.join("target").join(".external")).unwrap();
+    if let Some(manifest) = manifest {
+        fs::write(project_dir.join("Dove.toml"), manifest).unwrap();
+    }
+    project_dir
+}
+
+fn config(project_dir: PathBuf, settings: serde_json::Value) -> Config {
+    let mut config = Config::default();
+    config.root_path = Some(project_dir);
+    config.update(&settings);
+    config
+}
+
+#[test]
+fn test_settings_are_taken_from_manifest() {
+    let project_dir = dove_project("settings", Some(MANIFEST));
+    let config = config(
+        project_dir.clone(),
+        serde_json::json!({"dialect": "dfinance", "stdlib_folder": stdlib_path()}),
+    );
+
+    assert_eq!(config.dialect_name, DialectName::Libra);
+    assert_eq!(config.sender_address.original, "0x2");
+    assert_eq!(
+        config.modules_folders,
+        vec![
+            project_dir.join("modules"),
+            project_dir.join("target").join(".external")
+        ]
+    );
+    assert_eq!(config.stdlib_folder, Some(stdlib_path()));
+}
+
+#[test]
+fn test_editor_sender_is_normalized_with_dialect_of_manifest() {
+    let manifest = "[package]\nname = \"Project\"\ndialect = \"dfinance\"\n";
+    let project_dir = dove_project("sender", Some(manifest));
+    let config = config(
+        project_dir,
+        serde_json::json!({"sender_address": "wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh"}),
+    );
+
+    assert_eq!(config.dialect_name, DialectName::DFinance);
+    assert_eq!(
+        config.sender_address.original,
+        "wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh"
+    );
+}
+
+#[test]
+fn test_fetched_dependencies_are_read_only() {
+    let project_dir = dove_project("read_only", Some(MANIFEST));
+    let config = config(project_dir.clone(), serde_json::json!({}));
+
+    let dependency = project_dir
+        .join("target")
+        .join(".external")
+        .join("coins.move");
+    assert!(config.is_read_only(dependency.to_str().unwrap()));
+    let module = project_dir.join("modules").join("record.move");
+    assert!(!config.is_read_only(module.to_str().unwrap()));
+
+    // folders are compared by the path components
+    let neighbour = project_dir
+        .join("target")
+        .join(".external_modules")
+        .join("coins.move");
+    assert!(!config.is_read_only(neighbour.to_str().unwrap()));
+}
+
+#[test]
+fn test_test_files_are_in_tests_folder() {
+    let project_dir = dove_project("tests_folder", Some(MANIFEST));
+    let config = config(project_dir.clone(), serde_json::json!({}));
+
+    let test = project_dir.join("tests").join("record_test.move");
+    assert!(config.is_test_file(test.to_str().unwrap()));
+    let helper = project_dir.join("tests_helpers").join("record.move");
+    assert!(!config.is_test_file(helper.to_str().unwrap()));
+}
+
+#[test]
+fn test_editor_settings_are_used_without_manifest() {
+    let project_dir = dove_project("no_manifest", None);
+    let config = config(project_dir, serde_json::json!({"dialect": "dfinance"}));
+
+    assert_eq!(config.dialect_name, DialectName::DFinance);
+    assert!(config.modules_folders.is_empty());
+    assert!(config.external_deps_folder.is_none());
+}
+
+#[test]
+fn test_manifest_of_workspace_root_is_loaded_on_initialization() {
+    let project_dir = dove_project("initialization", Some(MANIFEST));
+    #[allow(deprecated)]
+    let params = InitializeParams {
+        process_id: None,
+        root_path: None,
+        root_uri: Some(Url::from_file_path(&project_dir).unwrap()),
+        initialization_options: None,
+        capabilities: ClientCapabilities::default(),
+        trace: None,
+        workspace_folders: None,
+        client_info: None,
+        locale: None,
+    };
+    let (root, config) = parse_initialize_params(serde_json::to_value(params).unwrap()).unwrap();
+
+    assert_eq!(root, project_dir);
+    assert_eq!(config.dialect_name, DialectName::Libra);
+    assert_eq!(config.modules_folders.len(), 2);
+}
@@ -0,0 +1,78 @@
+use lang::compiler::file::MoveFile;
+use lang::compiler::ConstPool;
+use move_language_server::global_state::initialize_new_global_state;
+use move_language_server::inner::config::Config;
+use move_language_server::inner::folding_ranges::FoldKind;
+use move_language_server::main_loop::FileSystemEvent;
+use resources::resources_dir;
+
+fn module_path() -> String {
+    resources_dir()
+        .join("folding.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+/// First lines of the folded regions.
+fn folds(source: &str) -> Vec<(String, FoldKind)> {
+    let mut global_state = initialize_new_global_state(Config::default());
+    global_state.update_from_events(vec![FileSystemEvent::AddFile(MoveFile::with_content(
+        module_path(),
+        source.to_owned(),
+    ))]);
+    global_state
+        .analysis()
+        .folding_ranges(&module_path())
+        .unwrap()
+        .into_iter()
+        .map(|fold| {
+            let text = &source[fold.span.0..fold.span.1];
+            (text.lines().next().unwrap().trim().to_owned(), fold.kind)
+        })
+        .collect()
+}
+
+#[test]
+fn test_folding_ranges() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+address 0x1 {
+module Coins {
+    use 0x1::Signer;
+    use 0x1::Vector;
+
+    use 0x1::Event;
+
+    // Coins of the account,
+    // stored under its address.
+    resource struct Balance {
+        value: u64,
+    }
+
+    /* single line */
+    public fun value(account: &signer): u64 acquires Balance {
+        borrow_global<Balance>(Signer::address_of(account)).value
+    }
+
+    spec fun value {
+        aborts_if false;
+    }
+}
+}
+";
+    assert_eq!(
+        folds(source),
+        vec![
+            ("module Coins {".to_owned(), FoldKind::Region),
+            ("use 0x1::Signer;".to_owned(), FoldKind::Imports),
+            ("// Coins of the account,".to_owned(), FoldKind::Comment),
+            ("resource struct Balance {".to_owned(), FoldKind::Region),
+            (
+                "public fun value(account: &signer): u64 acquires Balance {".to_owned(),
+                FoldKind::Region
+            ),
+            ("spec fun value {".to_owned(), FoldKind::Region),
+        ]
+    );
+}
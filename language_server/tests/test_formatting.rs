@@ -0,0 +1,118 @@
+use lang::compiler::ConstPool;
+use move_language_server::global_state::GlobalState;
+use move_language_server::inner::rename::FileEdit;
+use resources::modules_path;
+
+mod common;
+
+const UNFORMATTED: &str = r"address 0x2 {
+module Wallet {
+  use 0x1::Signer;
+
+      const MAX :u64 = 100;
+
+  public fun balance(account:&signer) : u64 {
+    let addr=Signer::address_of(account);
+        if (addr==0x2) {
+      MAX-1
+    } else { 0 }
+  }
+}
+}
+";
+
+const FORMATTED: &str = r"address 0x2 {
+module Wallet {
+    use 0x1::Signer;
+
+    const MAX: u64 = 100;
+
+    public fun balance(account: &signer): u64 {
+        let addr = Signer::address_of(account);
+        if (addr == 0x2) {
+            MAX - 1
+        } else { 0 }
+    }
+}
+}
+";
+
+fn wallet_path() -> String {
+    modules_path()
+        .join("wallet.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn global_state(text: &str) -> GlobalState {
+    common::global_state("libra", &[(wallet_path(), text)])
+}
+
+fn apply_edit(text: &str, edit: FileEdit) -> String {
+    let mut text = text.to_owned();
+    text.replace_range(edit.span.0..edit.span.1, &edit.new_text);
+    text
+}
+
+#[test]
+fn test_format_document() {
+    let _pool = ConstPool::new();
+
+    let global_state = global_state(UNFORMATTED);
+    let edit = global_state
+        .analysis()
+        .format(&wallet_path())
+        .unwrap()
+        .unwrap();
+    assert_eq!(edit.fpath, wallet_path());
+    assert_eq!(apply_edit(UNFORMATTED, edit), FORMATTED);
+}
+
+#[test]
+fn test_formatted_document_has_no_edits() {
+    let _pool = ConstPool::new();
+
+    let global_state = global_state(FORMATTED);
+    let edit = global_state.analysis().format(&wallet_path()).unwrap();
+    assert!(edit.is_none(), "{:?}", edit);
+}
+
+#[test]
+fn test_format_range_changes_only_selected_lines() {
+    let _pool = ConstPool::new();
+
+    let global_state = global_state(UNFORMATTED);
+    let edit = global_state
+        .analysis()
+        .format_lines(&wallet_path(), 6, 7)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        &UNFORMATTED[edit.span.0..edit.span.1],
+        "  public fun balance(account:&signer) : u64 {\n    let addr=Signer::address_of(account);"
+    );
+    assert_eq!(
+        edit.new_text,
+        "    public fun balance(account: &signer): u64 {\n        let addr = Signer::address_of(account);"
+    );
+
+    let expected = UNFORMATTED
+        .replace(
+            "  public fun balance(account:&signer) : u64 {",
+            "    public fun balance(account: &signer): u64 {",
+        )
+        .replace(
+            "    let addr=Signer::address_of(account);",
+            "        let addr = Signer::address_of(account);",
+        );
+    assert_eq!(apply_edit(UNFORMATTED, edit), expected);
+}
+
+#[test]
+fn test_unterminated_comment_is_not_formatted() {
+    let _pool = ConstPool::new();
+
+    let source = UNFORMATTED.replace("use 0x1::Signer;", "/* use 0x1::Signer;");
+    let global_state = global_state(&source);
+    assert!(global_state.analysis().format(&wallet_path()).is_err());
+}
@@ -0,0 +1,160 @@
+use lang::compiler::file::MoveFile;
+use lang::compiler::ConstPool;
+use move_language_server::global_state::initialize_new_global_state;
+use move_language_server::inner::config::Config;
+use move_language_server::inner::goto_definition::NavigationTarget;
+use move_language_server::main_loop::FileSystemEvent;
+use resources::{modules_path, resources_dir, stdlib_path};
+
+fn script_path() -> String {
+    resources_dir()
+        .join("script.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn config() -> Config {
+    let mut config = Config::default();
+    config.update(&serde_json::json!({
+        "stdlib_folder": stdlib_path(),
+        "modules_folders": [modules_path()],
+    }));
+    config
+}
+
+/// Offset of the `n`-th character of the first occurrence of `pattern` in `source`.
+fn offset_of(source: &str, pattern: &str, n: usize) -> usize {
+    source.find(pattern).unwrap() + n
+}
+
+fn goto_definition(source: &str, offset: usize) -> Option<NavigationTarget> {
+    let mut global_state = initialize_new_global_state(config());
+    global_state.update_from_events(vec![FileSystemEvent::AddFile(MoveFile::with_content(
+        script_path(),
+        source.to_owned(),
+    ))]);
+    global_state
+        .analysis()
+        .goto_definition(&script_path(), offset)
+}
+
+fn target_text(target: &NavigationTarget) -> String {
+    let text = std::fs::read_to_string(&target.fpath).unwrap();
+    text[target.focus_span.0..target.focus_span.1].to_owned()
+}
+
+#[test]
+fn test_goto_stdlib_module_from_use() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+script {
+    use 0x1::Signer;
+
+    fun main(s: &signer) {
+        let _ = Signer::address_of(s);
+    }
+}
+";
+    let target = goto_definition(source, offset_of(source, "Signer;", 2)).unwrap();
+    assert!(target.fpath.ends_with("signer.move"), "{:?}", target);
+    assert_eq!(target_text(&target), "Signer");
+}
+
+#[test]
+fn test_goto_stdlib_function() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+script {
+    use 0x1::Signer;
+
+    fun main(s: &signer) {
+        let _ = Signer::address_of(s);
+    }
+}
+";
+    let target = goto_definition(source, offset_of(source, "address_of", 3)).unwrap();
+    assert!(target.fpath.ends_with("signer.move"), "{:?}", target);
+    assert_eq!(target_text(&target), "address_of");
+}
+
+#[test]
+fn test_goto_struct_from_modules_folder() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+script {
+    use 0x2::Record;
+
+    fun main(s: &signer) {
+        let record: Record::T = Record::create(10);
+        Record::save(s, record);
+    }
+}
+";
+    let target = goto_definition(source, offset_of(source, "Record::T", 8)).unwrap();
+    assert!(target.fpath.ends_with("record.move"), "{:?}", target);
+    assert_eq!(target_text(&target), "T");
+}
+
+#[test]
+fn test_goto_struct_in_the_same_file() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+address 0x2 {
+    module Storage {
+        struct Item { value: u64 }
+
+        public fun value(item: &Item): u64 {
+            item.value
+        }
+    }
+}
+";
+    let target = goto_definition(source, offset_of(source, "&Item", 2)).unwrap();
+    assert_eq!(target.fpath, script_path());
+    assert_eq!(&source[target.focus_span.0..target.focus_span.1], "Item");
+}
+
+#[test]
+fn test_goto_capitalized_function_from_use() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+address 0x2 {
+    module Shop {
+        struct Buy {}
+
+        public fun Sell(): u64 { 1 }
+    }
+
+    module Customer {
+        use 0x2::Shop::Sell;
+    }
+}
+";
+    let target = goto_definition(source, offset_of(source, "Sell;", 1)).unwrap();
+    assert_eq!(target.fpath, script_path());
+    assert_eq!(&source[target.focus_span.0..target.focus_span.1], "Sell");
+    assert!(target.focus_span.0 < source.find("module Customer").unwrap());
+}
+
+#[test]
+fn test_no_definition_for_builtin_function() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+address 0x2 {
+    module Storage {
+        resource struct Item { value: u64 }
+
+        public fun has_item(addr: address): bool {
+            exists<Item>(addr)
+        }
+    }
+}
+";
+    assert!(goto_definition(source, offset_of(source, "exists", 1)).is_none());
+}
@@ -0,0 +1,233 @@
+use crossbeam_channel::unbounded;
+use lang::compiler::file::MoveFile;
+use lang::compiler::ConstPool;
+use move_language_server::global_state::GlobalState;
+use move_language_server::inner::hover::HoverResult;
+use move_language_server::main_loop::{compute_file_diagnostics, FileSystemEvent, ResponseEvent};
+use resources::resources_dir;
+
+mod common;
+
+fn script_path() -> String {
+    resources_dir()
+        .join("script.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn global_state(source: &str) -> GlobalState {
+    global_state_of_dialect("libra", source)
+}
+
+fn global_state_of_dialect(dialect: &str, source: &str) -> GlobalState {
+    common::global_state(dialect, &[(script_path(), source)])
+}
+
+fn hover(source: &str, offset: usize) -> Option<HoverResult> {
+    global_state(source)
+        .analysis()
+        .hover(&script_path(), offset)
+}
+
+fn hover_in_dialect(dialect: &str, source: &str, offset: usize) -> Option<HoverResult> {
+    global_state_of_dialect(dialect, source)
+        .analysis()
+        .hover(&script_path(), offset)
+}
+
+fn checked_global_state(source: &str) -> GlobalState {
+    let mut global_state = global_state(source);
+
+    let (task_sender, task_receiver) = unbounded::<ResponseEvent>();
+    compute_file_diagnostics(global_state.analysis(), task_sender, vec![script_path()]);
+    for task in task_receiver.try_iter() {
+        if let ResponseEvent::FileTypes(revision, file_types) = task {
+            global_state.update_file_types(revision, file_types);
+        }
+    }
+    global_state
+}
+
+fn hover_after_check(source: &str, offset: usize) -> Option<HoverResult> {
+    checked_global_state(source)
+        .analysis()
+        .hover(&script_path(), offset)
+}
+
+#[test]
+fn test_hover_stdlib_function_signature() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+script {
+    use 0x1::Signer;
+
+    fun main(s: &signer) {
+        let _ = Signer::address_of(s);
+    }
+}
+";
+    let offset = source.find("address_of").unwrap() + 2;
+    let hover = hover(source, offset).unwrap();
+    assert_eq!(&source[hover.span.0..hover.span.1], "address_of");
+    assert!(
+        hover
+            .markup
+            .contains("public fun address_of(s: &signer): address"),
+        "{}",
+        hover.markup
+    );
+}
+
+#[test]
+fn test_hover_struct_definition() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+address 0x2 {
+    module Storage {
+        resource struct Item<T: copyable> { value: T }
+    }
+}
+";
+    let offset = source.find("Item").unwrap() + 1;
+    let hover = hover(source, offset).unwrap();
+    assert_eq!(
+        hover.markup,
+        "```move\n0x2::Storage\n```\n\n```move\nresource struct Item<T: copyable> {\n    value: T\n}\n```"
+    );
+}
+
+#[test]
+fn test_hover_local_type() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+script {
+    use 0x2::Record;
+
+    fun main(s: &signer) {
+        let record = Record::create(10);
+        Record::save(s, record);
+    }
+}
+";
+    let offset = source.find("Record::save(s, record)").unwrap() + 17;
+    let hover = hover_after_check(source, offset).unwrap();
+    assert_eq!(&source[hover.span.0..hover.span.1], "record");
+    assert_eq!(hover.markup, "```move\nrecord: Record::T\n```");
+}
+
+#[test]
+fn test_no_local_types_before_check() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+script {
+    fun main() {
+        let a = 1;
+        let _ = a;
+    }
+}
+";
+    let offset = source.find("= a").unwrap() + 2;
+    assert!(hover(source, offset).is_none());
+}
+
+#[test]
+fn test_no_local_types_after_text_change() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+script {
+    use 0x2::Record;
+
+    fun main(s: &signer) {
+        let record = Record::create(10);
+        Record::save(s, record);
+    }
+}
+";
+    let mut global_state = checked_global_state(source);
+
+    let new_source = source.replace("Record::create(10)", "10");
+    global_state.update_from_events(vec![FileSystemEvent::ChangeFile(MoveFile::with_content(
+        script_path(),
+        new_source.clone(),
+    ))]);
+    let offset = new_source.find("Record::save(s, record)").unwrap() + 17;
+    assert!(global_state
+        .analysis()
+        .hover(&script_path(), offset)
+        .is_none());
+}
+
+#[test]
+fn test_hover_address_formats() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+script {
+    fun main() {
+        let _ = 0x1;
+        let _ = wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh;
+    }
+}
+";
+    let offset = source.find("0x1").unwrap() + 1;
+    let hover = hover(source, offset).unwrap();
+    assert_eq!(&source[hover.span.0..hover.span.1], "0x1");
+    assert!(hover.markup.starts_with("hex: `0x1`  \nbech32: `wallet1"));
+    assert!(hover.markup.contains("  \nss58: `"));
+    assert!(hover
+        .markup
+        .ends_with("  \ncompiled: `0x0000000000000000000000000000000000000001`"));
+
+    let offset = source.find("wallet1").unwrap() + 10;
+    let hover = hover(source, offset).unwrap();
+    assert_eq!(
+        &source[hover.span.0..hover.span.1],
+        "wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh"
+    );
+    assert!(hover
+        .markup
+        .contains("bech32: `wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh`"));
+    assert!(
+        hover
+            .markup
+            .ends_with("  \ncompiled: not valid in libra dialect"),
+        "{}",
+        hover.markup
+    );
+}
+
+#[test]
+fn test_hover_address_lists_every_format_in_any_dialect() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+script {
+    fun main() {
+        // of 0x9C786090E2598AE884FF9D1F01D6A1A9BAF13A9E61F73633A8928F4D80BF7DFE
+        let _ = 0x1;
+    }
+}
+";
+    let offset = source.find("0x1;").unwrap() + 1;
+    let hover = hover_in_dialect("dfinance", source, offset).unwrap();
+    assert!(
+        hover.markup.starts_with("hex: `0x1`  \nbech32: `wallet1"),
+        "{}",
+        hover.markup
+    );
+    assert!(hover.markup.contains("  \nss58: `"), "{}", hover.markup);
+
+    // 32-byte address doesn't fit into bech32
+    let offset = source.find("0x9C").unwrap() + 1;
+    let hover = hover_in_dialect("dfinance", source, offset).unwrap();
+    assert!(
+        hover.markup.contains("  \nbech32: -  \nss58: `"),
+        "{}",
+        hover.markup
+    );
+}
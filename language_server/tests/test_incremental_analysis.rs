@@ -0,0 +1,251 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use lang::compiler::file::load_move_files;
+use lang::compiler::ConstPool;
+use lsp_types::{Position, Range, TextDocumentContentChangeEvent};
+use move_language_server::inner::change::AnalysisChange;
+use move_language_server::inner::config::Config;
+use move_language_server::inner::db::RootDatabase;
+use resources::{modules_path, resources_dir, stdlib_path};
+
+const COIN: &str = r"
+address 0x2 {
+module Coin {
+    struct Coin { value: u64 }
+
+    public fun zero(): Coin {
+        Coin { value: 0 }
+    }
+}
+}
+";
+
+const WALLET: &str = r"
+address 0x2 {
+module Wallet {
+    use 0x2::Coin;
+
+    public fun empty(): Coin::Coin {
+        Coin::zero()
+    }
+}
+}
+";
+
+const EVENT: &str = r"
+address 0x2 {
+module Event {
+    public fun emit() {}
+}
+}
+";
+
+const SCRIPT: &str = r"
+script {
+    use 0x2::Wallet;
+
+    fun main() {
+        let _ = Wallet::empty();
+    }
+}
+";
+
+fn path(name: &str) -> String {
+    modules_path().join(name).to_string_lossy().to_string()
+}
+
+fn script_path() -> String {
+    resources_dir()
+        .join("script.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn db() -> RootDatabase {
+    let mut config = Config::default();
+    config.update(&serde_json::json!({
+        "stdlib_folder": stdlib_path(),
+        "modules_folders": [modules_path()],
+    }));
+    let mut db = RootDatabase::new(config);
+
+    let mut change = AnalysisChange::new();
+    for file in load_move_files(&[stdlib_path()]).unwrap() {
+        let (fpath, text) = file.into();
+        change.add_file(fpath, text);
+    }
+    change.add_file(path("coin.move"), COIN.to_owned());
+    change.add_file(path("wallet.move"), WALLET.to_owned());
+    change.add_file(path("event.move"), EVENT.to_owned());
+    change.add_file(script_path(), SCRIPT.to_owned());
+    db.apply_change(change);
+    db
+}
+
+fn update_file(db: &mut RootDatabase, fpath: String, text: &str) -> HashSet<String> {
+    let mut change = AnalysisChange::new();
+    change.update_file(fpath, text.to_owned());
+    db.apply_change(change)
+}
+
+fn set(files: Vec<String>) -> HashSet<String> {
+    files.into_iter().collect()
+}
+
+#[test]
+fn test_module_change_affects_its_dependents_transitively() {
+    let _pool = ConstPool::new();
+
+    let mut db = db();
+    let affected = update_file(&mut db, path("coin.move"), &COIN.replace("0 }", "1 }"));
+    assert_eq!(
+        affected,
+        set(vec![path("coin.move"), path("wallet.move"), script_path()])
+    );
+
+    let affected = update_file(&mut db, path("event.move"), &EVENT.replace("emit", "log"));
+    assert_eq!(affected, set(vec![path("event.move")]));
+}
+
+#[test]
+fn test_script_change_affects_only_the_script() {
+    let _pool = ConstPool::new();
+
+    let mut db = db();
+    let affected = update_file(&mut db, script_path(), &SCRIPT.replace("_", "wallet"));
+    assert_eq!(affected, set(vec![script_path()]));
+}
+
+#[test]
+fn test_results_are_outdated_after_change_of_the_dependency() {
+    let _pool = ConstPool::new();
+
+    let mut db = db();
+    let revision = db.revision;
+    assert!(!db.is_outdated(&script_path(), revision));
+
+    update_file(&mut db, path("coin.move"), &COIN.replace("0 }", "1 }"));
+    assert!(db.revision > revision);
+    assert!(db.is_outdated(&path("coin.move"), revision));
+    assert!(db.is_outdated(&script_path(), revision));
+    assert!(!db.is_outdated(&path("event.move"), revision));
+    assert!(!db.is_outdated(&script_path(), db.revision));
+}
+
+#[test]
+fn test_unchanged_text_is_not_reparsed() {
+    let _pool = ConstPool::new();
+
+    let mut db = db();
+    let symbols = db.file_symbols(&path("coin.move")).unwrap();
+
+    let affected = update_file(&mut db, path("coin.move"), COIN);
+    assert_eq!(affected, set(vec![path("coin.move")]));
+    assert!(Arc::ptr_eq(
+        &symbols,
+        &db.file_symbols(&path("coin.move")).unwrap()
+    ));
+}
+
+#[test]
+fn test_copy_of_database_shares_texts_and_symbols() {
+    let _pool = ConstPool::new();
+
+    let snapshot = Arc::new(db());
+    let mut db = (*snapshot).clone();
+    update_file(
+        &mut db,
+        path("event.move"),
+        "address 0x2 { module Event {} }",
+    );
+
+    let coin = path("coin.move");
+    assert!(Arc::ptr_eq(
+        &snapshot.available_files[&coin],
+        &db.available_files[&coin]
+    ));
+    assert!(Arc::ptr_eq(
+        &snapshot.file_symbols(&coin).unwrap(),
+        &db.file_symbols(&coin).unwrap()
+    ));
+    assert_ne!(
+        snapshot.available_files[&path("event.move")],
+        db.available_files[&path("event.move")]
+    );
+}
+
+#[test]
+fn test_invalid_edit_keeps_the_text() {
+    let _pool = ConstPool::new();
+
+    let mut db = db();
+    let mut change = AnalysisChange::new();
+    change.edit_file(
+        path("event.move"),
+        vec![TextDocumentContentChangeEvent {
+            range: Some(Range::new(Position::new(100, 0), Position::new(100, 1))),
+            range_length: None,
+            text: "fun".to_owned(),
+        }],
+    );
+    let affected = db.apply_change(change);
+
+    assert!(affected.is_empty());
+    assert_eq!(&*db.available_files[&path("event.move")], EVENT);
+    let errors = db.take_edit_errors();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("event.move"), "{}", errors[0]);
+}
+
+#[test]
+fn test_dependencies_contain_only_used_modules() {
+    let _pool = ConstPool::new();
+
+    let db = db();
+    let deps: HashSet<String> = db
+        .dependencies(&script_path(), SCRIPT)
+        .into_iter()
+        .collect();
+    assert_eq!(deps, set(vec![path("coin.move"), path("wallet.move")]));
+
+    let deps = db.dependencies(&path("coin.move"), COIN);
+    assert!(deps.is_empty(), "{:?}", deps);
+
+    let script = "script { use 0x1::Signer; fun main(s: &signer) { Signer::address_of(s); } }";
+    let deps = db.dependencies(&script_path(), script);
+    assert_eq!(deps.len(), 1, "{:?}", deps);
+    assert!(deps[0].ends_with("signer.move"), "{:?}", deps);
+}
+
+#[test]
+fn test_modules_of_broken_file_are_tracked_by_its_last_parsed_version() {
+    let _pool = ConstPool::new();
+
+    let mut db = db();
+    let broken = COIN.replace("public fun", "public fun fun");
+    let affected = update_file(&mut db, path("coin.move"), &broken);
+    assert!(db.file_symbols(&path("coin.move")).is_none());
+    assert_eq!(
+        affected,
+        set(vec![path("coin.move"), path("wallet.move"), script_path()])
+    );
+
+    let deps: HashSet<String> = db
+        .dependencies(&script_path(), SCRIPT)
+        .into_iter()
+        .collect();
+    assert_eq!(deps, set(vec![path("coin.move"), path("wallet.move")]));
+}
+
+#[test]
+fn test_never_parsed_dependency_affects_all_files() {
+    let _pool = ConstPool::new();
+
+    let mut db = db();
+    let affected = update_file(&mut db, path("broken.move"), "address 0x2 { modules T {} }");
+    assert_eq!(affected.len(), db.available_files.len());
+    assert!(db
+        .dependencies(&path("event.move"), EVENT)
+        .contains(&path("broken.move")));
+}
@@ -0,0 +1,140 @@
+use crossbeam_channel::unbounded;
+use lang::compiler::ConstPool;
+use move_language_server::global_state::GlobalState;
+use move_language_server::inner::inlay_hints::{InlayHint, InlayHintKind};
+use move_language_server::main_loop::{compute_file_diagnostics, ResponseEvent};
+use resources::resources_dir;
+
+mod common;
+
+fn module_path() -> String {
+    resources_dir()
+        .join("math.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn global_state(source: &str) -> GlobalState {
+    common::global_state("libra", &[(module_path(), source)])
+}
+
+fn checked_global_state(source: &str) -> GlobalState {
+    let mut global_state = global_state(source);
+
+    let (task_sender, task_receiver) = unbounded::<ResponseEvent>();
+    compute_file_diagnostics(global_state.analysis(), task_sender, vec![module_path()]);
+    for task in task_receiver.try_iter() {
+        if let ResponseEvent::FileTypes(revision, file_types) = task {
+            global_state.update_file_types(revision, file_types);
+        }
+    }
+    global_state
+}
+
+/// Hints of the source, with the text before each of them.
+fn hints(global_state: &GlobalState, source: &str) -> Vec<(String, String, InlayHintKind)> {
+    global_state
+        .analysis()
+        .inlay_hints(&module_path(), (0, source.len()))
+        .into_iter()
+        .map(
+            |InlayHint {
+                 offset,
+                 label,
+                 kind,
+             }| {
+                let line_start = source[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+                (source[line_start..offset].trim().to_owned(), label, kind)
+            },
+        )
+        .collect()
+}
+
+const SOURCE: &str = r"
+address 0x2 {
+module Math {
+    fun add(a: u64, b: u64): u64 {
+        a + b
+    }
+
+    fun sum(): u64 {
+        let total = add(1, 2);
+        let (x, flag) = (total, true);
+        let annotated: u64 = add(x, 3);
+        if (flag) annotated else total
+    }
+}
+}
+";
+
+#[test]
+fn test_inferred_types_of_let_bindings() {
+    let _pool = ConstPool::new();
+
+    let global_state = checked_global_state(SOURCE);
+    let type_hints: Vec<_> = hints(&global_state, SOURCE)
+        .into_iter()
+        .filter(|(_, _, kind)| *kind == InlayHintKind::Type)
+        .map(|(before, label, _)| (before, label))
+        .collect();
+    assert_eq!(
+        type_hints,
+        vec![
+            ("let total".to_owned(), ": u64".to_owned()),
+            ("let (x".to_owned(), ": u64".to_owned()),
+            ("let (x, flag".to_owned(), ": bool".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn test_no_type_hints_of_unpacked_fields() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+address 0x2 {
+module Mail {
+    struct Box { letter: u64 }
+
+    fun open(b: Box): u64 {
+        let Box { letter: value } = b;
+        let outlet = value;
+        outlet
+    }
+}
+}
+";
+    let global_state = checked_global_state(source);
+    assert_eq!(
+        hints(&global_state, source),
+        vec![(
+            "let outlet".to_owned(),
+            ": u64".to_owned(),
+            InlayHintKind::Type
+        )]
+    );
+}
+
+#[test]
+fn test_parameter_names_of_literal_arguments() {
+    let _pool = ConstPool::new();
+
+    // parameter names don't need the check
+    let global_state = global_state(SOURCE);
+    let hints = hints(&global_state, SOURCE);
+    assert_eq!(
+        hints,
+        vec![
+            (
+                "let total = add(".to_owned(),
+                "a:".to_owned(),
+                InlayHintKind::Parameter
+            ),
+            (
+                "let total = add(1,".to_owned(),
+                "b:".to_owned(),
+                InlayHintKind::Parameter
+            ),
+        ]
+    );
+}
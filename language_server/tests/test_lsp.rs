@@ -1,19 +1,28 @@
-use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
-use lsp_types::request::{Initialize, Shutdown};
+use std::thread;
+use std::time::Duration;
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
+use lsp_types::request::{Initialize, Shutdown, WorkspaceSymbol};
 use lsp_types::{
-    ClientCapabilities, DidChangeConfigurationParams, DidChangeWatchedFilesParams,
-    FileChangeType, FileEvent, InitializeParams, InitializedParams, Url,
+    CancelParams, ClientCapabilities, DidChangeConfigurationParams, DidChangeTextDocumentParams,
+    DidChangeWatchedFilesParams, DidOpenTextDocumentParams, FileChangeType, FileEvent,
+    InitializeParams, InitializedParams, NumberOrString, Position, Range,
+    TextDocumentContentChangeEvent, TextDocumentItem, Url, VersionedTextDocumentIdentifier,
+    WindowClientCapabilities, WorkspaceSymbolParams,
 };
 
-use lsp_types::notification::{DidChangeConfiguration, DidChangeWatchedFiles, Initialized};
+use lsp_types::notification::{
+    Cancel, DidChangeConfiguration, DidChangeTextDocument, DidChangeWatchedFiles,
+    DidOpenTextDocument, Initialized,
+};
 use move_language_server::global_state::{initialize_new_global_state, GlobalState};
 use move_language_server::main_loop::{main_loop, notification_new, request_new, FileSystemEvent};
 use move_language_server::server::run_server;
-use move_language_server::inner::config::Config;
+use move_language_server::inner::config::{Config, DEFAULT_WORKER_THREADS};
 use lang::compiler::dialects::DialectName;
 use lang::compiler::file::MoveFile;
 use lang::compiler::ConstPool;
-use resources::assets_dir;
+use resources::{assets_dir, modules_path, stdlib_path};
 
 const SHUTDOWN_REQ_ID: i32 = 10;
 
@@ -122,7 +131,7 @@ fn test_server_initialization() {
     assert_eq!(init_finished_resp.id, RequestId::from(1));
     assert_eq!(
         init_finished_resp.result.unwrap()["capabilities"]["textDocumentSync"],
-        serde_json::json!({"change": 1, "openClose": true})
+        serde_json::json!({"change": 2, "openClose": true})
     );
     let registration_req = client_conn.receiver.try_recv().unwrap().into_request();
     assert_eq!(registration_req.method, "client/registerCapability");
@@ -137,6 +146,122 @@ fn test_server_initialization() {
     client_conn.receiver.try_recv().unwrap_err();
 }
 
+fn progress_init_params() -> InitializeParams {
+    let mut init_params = client_initialize_params();
+    init_params.capabilities.window = Some(WindowClientCapabilities {
+        work_done_progress: Some(true),
+        ..WindowClientCapabilities::default()
+    });
+    init_params.initialization_options = Some(serde_json::json!({
+        "stdlib_folder": stdlib_path(),
+        "modules_folders": [modules_path()],
+    }));
+    init_params
+}
+
+/// Server running in its own thread, so that the client could respond to its requests.
+fn spawn_server(init_params: InitializeParams) -> (Connection, thread::JoinHandle<()>) {
+    let (client_conn, server_conn) = Connection::memory();
+    client_conn
+        .sender
+        .send(Message::Request(request_new::<Initialize>(
+            RequestId::from(1),
+            init_params,
+        )))
+        .unwrap();
+    client_conn
+        .sender
+        .send(notification::<Initialized>(InitializedParams {}))
+        .unwrap();
+    let server = thread::spawn(move || run_server(&server_conn).unwrap());
+    (client_conn, server)
+}
+
+fn next_message(client_conn: &Connection) -> Message {
+    client_conn
+        .receiver
+        .recv_timeout(Duration::from_secs(60))
+        .unwrap()
+}
+
+fn is_indexing_progress(not: &Notification) -> bool {
+    not.method == "$/progress"
+        && not.params["token"]
+            .as_str()
+            .unwrap()
+            .starts_with("move/indexing/")
+}
+
+#[test]
+fn test_indexing_progress_is_reported() {
+    let _pool = ConstPool::new();
+
+    let (client_conn, server) = spawn_server(progress_init_params());
+    let mut created_tokens = vec![];
+    let mut indexing: Vec<(String, serde_json::Value)> = vec![];
+    loop {
+        match next_message(&client_conn) {
+            Message::Request(req) if req.method == "window/workDoneProgress/create" => {
+                created_tokens.push(req.params["token"].as_str().unwrap().to_owned());
+                client_conn
+                    .sender
+                    .send(Message::Response(Response::new_ok(req.id, ())))
+                    .unwrap();
+            }
+            Message::Notification(not) if is_indexing_progress(&not) => {
+                let token = not.params["token"].as_str().unwrap().to_owned();
+                indexing.push((token, not.params["value"].clone()));
+                if not.params["value"]["kind"] == "end" {
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    send_shutdown(&client_conn);
+    server.join().unwrap();
+
+    let (token, begin) = indexing.first().unwrap();
+    assert!(created_tokens.contains(token), "{:?}", created_tokens);
+    assert!(indexing.iter().all(|(other, _)| other == token));
+    assert_eq!(begin["kind"], "begin");
+    assert_eq!(begin["title"], "Indexing");
+    let (_, end) = indexing.last().unwrap();
+    assert!(end["message"]
+        .as_str()
+        .unwrap()
+        .ends_with("modules indexed"));
+}
+
+#[test]
+fn test_progress_is_not_shown_if_client_fails_to_create_it() {
+    let _pool = ConstPool::new();
+
+    let (client_conn, server) = spawn_server(progress_init_params());
+    loop {
+        match next_message(&client_conn) {
+            Message::Request(req) if req.method == "window/workDoneProgress/create" => {
+                let error = Response::new_err(
+                    req.id,
+                    ErrorCode::InternalError as i32,
+                    "progress is not supported".to_string(),
+                );
+                client_conn.sender.send(Message::Response(error)).unwrap();
+                break;
+            }
+            Message::Notification(not) => assert!(!is_indexing_progress(&not)),
+            _ => {}
+        }
+    }
+    send_shutdown(&client_conn);
+    server.join().unwrap();
+
+    assert!(!client_conn.receiver.try_iter().any(|message| matches!(
+        message,
+        Message::Notification(not) if is_indexing_progress(&not)
+    )));
+}
+
 #[test]
 fn test_server_config_change() {
     let (client_conn, server_conn) = Connection::memory();
@@ -170,6 +295,97 @@ fn test_server_config_change() {
     assert_eq!(global_state.config().dialect_name, DialectName::DFinance);
 }
 
+#[test]
+fn test_opened_file_is_kept_after_config_change() {
+    let _pool = ConstPool::new();
+
+    let script_path = assets_dir().join("script.move");
+    let (client_conn, server_conn) = Connection::memory();
+
+    let text = "script {\n    fun main() {}\n}";
+    let open_notification = notification::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+        text_document: TextDocumentItem::new(
+            Url::from_file_path(&script_path).unwrap(),
+            "move".to_string(),
+            1,
+            text.to_string(),
+        ),
+    });
+    let didchange_notification =
+        notification::<DidChangeConfiguration>(DidChangeConfigurationParams {
+            settings: serde_json::json!(""),
+        });
+    let updated_settings_response = response(1, serde_json::json!([{"dialect": "dfinance"}]));
+    send_messages(
+        &client_conn,
+        vec![
+            open_notification,
+            didchange_notification,
+            updated_settings_response,
+        ],
+    );
+
+    let mut global_state = global_state(Config::default());
+    main_loop(&mut global_state, &server_conn).unwrap();
+
+    assert_eq!(global_state.config().dialect_name, DialectName::DFinance);
+    let fpath = script_path.to_string_lossy().to_string();
+    assert_eq!(
+        &*global_state.analysis_for(&fpath).db().available_files[&fpath],
+        text
+    );
+    // diagnostics are recomputed with the new dialect
+    assert!(global_state.take_affected_files().contains(&fpath));
+}
+
+#[test]
+fn test_worker_threads_setting() {
+    let mut config = Config::default();
+    config.update(&serde_json::json!({"worker_threads": 4}));
+    assert_eq!(config.worker_threads, 4);
+
+    config.update(&serde_json::json!({"worker_threads": 0}));
+    assert_eq!(config.worker_threads, DEFAULT_WORKER_THREADS);
+}
+
+#[test]
+fn test_canceled_request_is_responded_once() {
+    let _pool = ConstPool::new();
+
+    let (client_conn, server_conn) = Connection::memory();
+    let symbols_req = request_new::<WorkspaceSymbol>(
+        RequestId::from(1),
+        WorkspaceSymbolParams {
+            query: "Record".to_string(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+    );
+    let cancel_notification = notification::<Cancel>(CancelParams {
+        id: NumberOrString::Number(1),
+    });
+    send_messages(
+        &client_conn,
+        vec![Message::Request(symbols_req), cancel_notification],
+    );
+
+    let mut global_state = global_state(Config::default());
+    main_loop(&mut global_state, &server_conn).unwrap();
+
+    let responses: Vec<_> = client_conn
+        .receiver
+        .try_iter()
+        .filter_map(|message| match message {
+            Message::Response(response) if response.id == RequestId::from(1) => Some(response),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(responses.len(), 1);
+    if let Some(error) = &responses[0].error {
+        assert_eq!(error.code, ErrorCode::RequestCanceled as i32);
+    }
+}
+
 #[test]
 fn test_removed_file_not_present_in_the_diagnostics() {
     // TODO: use or rem ConstPool
@@ -202,3 +418,122 @@ fn test_removed_file_not_present_in_the_diagnostics() {
 
     assert!(global_state.analysis().db().available_files.is_empty());
 }
+
+#[test]
+fn test_changed_module_file_is_reloaded_from_disk() {
+    let _pool = ConstPool::new();
+
+    let module_path = modules_path().join("record.move");
+    let fpath = module_path.to_string_lossy().to_string();
+    let (client_conn, server_conn) = Connection::memory();
+
+    let mut config = Config::default();
+    config.update(&serde_json::json!({
+        "stdlib_folder": stdlib_path(),
+        "modules_folders": [modules_path()],
+    }));
+    let mut global_state = global_state(config);
+    global_state.update_from_events(vec![FileSystemEvent::AddFile(MoveFile::with_content(
+        fpath.clone(),
+        "address 0x2 { module Record {} }",
+    ))]);
+
+    let change_event = FileEvent::new(
+        Url::from_file_path(&module_path).unwrap(),
+        FileChangeType::Changed,
+    );
+    let files_changed_notification =
+        notification::<DidChangeWatchedFiles>(DidChangeWatchedFilesParams {
+            changes: vec![change_event],
+        });
+    send_messages(&client_conn, vec![files_changed_notification]);
+
+    main_loop(&mut global_state, &server_conn).unwrap();
+
+    assert_eq!(
+        &*global_state.analysis().db().available_files[&fpath],
+        std::fs::read_to_string(&module_path).unwrap()
+    );
+}
+
+#[test]
+fn test_opened_file_is_not_reloaded_from_disk() {
+    let _pool = ConstPool::new();
+
+    let module_path = modules_path().join("record.move");
+    let uri = Url::from_file_path(&module_path).unwrap();
+    let (client_conn, server_conn) = Connection::memory();
+
+    let text = "address 0x2 { module Record {} }";
+    let open_notification = notification::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+        text_document: TextDocumentItem::new(
+            uri.clone(),
+            "move".to_string(),
+            1,
+            text.to_string(),
+        ),
+    });
+    let files_changed_notification =
+        notification::<DidChangeWatchedFiles>(DidChangeWatchedFilesParams {
+            changes: vec![FileEvent::new(uri, FileChangeType::Changed)],
+        });
+    send_messages(
+        &client_conn,
+        vec![open_notification, files_changed_notification],
+    );
+
+    let mut config = Config::default();
+    config.update(&serde_json::json!({
+        "modules_folders": [modules_path()],
+    }));
+    let mut global_state = global_state(config);
+    main_loop(&mut global_state, &server_conn).unwrap();
+
+    assert_eq!(
+        &*global_state.analysis().db().available_files[module_path.to_str().unwrap()],
+        text
+    );
+}
+
+#[test]
+fn test_incremental_changes_are_applied_to_the_opened_file() {
+    let _pool = ConstPool::new();
+
+    let script_path = assets_dir().join("script.move");
+    let uri = Url::from_file_path(&script_path).unwrap();
+    let (client_conn, server_conn) = Connection::memory();
+
+    let open_notification = notification::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+        text_document: TextDocumentItem::new(
+            uri.clone(),
+            "move".to_string(),
+            1,
+            "script {\n    fun main() {}\n}".to_string(),
+        ),
+    });
+    let change_notification =
+        notification::<DidChangeTextDocument>(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier::new(uri, 2),
+            content_changes: vec![
+                TextDocumentContentChangeEvent {
+                    range: Some(Range::new(Position::new(1, 8), Position::new(1, 12))),
+                    range_length: None,
+                    text: "run".to_string(),
+                },
+                TextDocumentContentChangeEvent {
+                    range: Some(Range::new(Position::new(1, 12), Position::new(1, 12))),
+                    range_length: None,
+                    text: "_: &signer".to_string(),
+                },
+            ],
+        });
+    send_messages(&client_conn, vec![open_notification, change_notification]);
+
+    let mut global_state = global_state(Config::default());
+    main_loop(&mut global_state, &server_conn).unwrap();
+
+    assert_eq!(
+        &*global_state.analysis().db().available_files[script_path.to_str().unwrap()],
+        "script {\n    fun run(_: &signer) {}\n}"
+    );
+}
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+use lang::compiler::file::MoveFile;
+use lang::compiler::ConstPool;
+use move_language_server::global_state::{initialize_new_global_state, GlobalState};
+use move_language_server::inner::config::Config;
+use move_language_server::inner::rename::FileEdit;
+use move_language_server::main_loop::FileSystemEvent;
+use resources::{modules_path, resources_dir, stdlib_path};
+
+const WALLET: &str = r"
+address 0x2 {
+module Wallet {
+    resource struct Coin { value: u64 }
+
+    public fun deposit(value: u64): Coin {
+        Coin { value }
+    }
+
+    public fun value(coin: &Coin): u64 {
+        coin.value
+    }
+
+    spec fun deposit {
+        aborts_if false;
+    }
+}
+}
+";
+
+const SCRIPT: &str = r"
+script {
+    use 0x2::Wallet;
+    use 0x2::Wallet::{deposit};
+
+    fun main() {
+        let coin = Wallet::deposit(10);
+        let other = deposit(20);
+    }
+}
+";
+
+fn wallet_path() -> String {
+    modules_path()
+        .join("wallet.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn script_path() -> String {
+    resources_dir()
+        .join("script.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn config() -> Config {
+    let mut config = Config::default();
+    config.update(&serde_json::json!({
+        "stdlib_folder": stdlib_path(),
+        "modules_folders": [modules_path()],
+    }));
+    config
+}
+
+fn global_state(wallet: &str, script: &str) -> GlobalState {
+    let mut global_state = initialize_new_global_state(config());
+    global_state.update_from_events(vec![
+        FileSystemEvent::AddFile(MoveFile::with_content(wallet_path(), wallet.to_owned())),
+        FileSystemEvent::AddFile(MoveFile::with_content(script_path(), script.to_owned())),
+    ]);
+    global_state
+}
+
+/// Texts of the changed files after the edits are applied.
+fn apply_edits(global_state: &GlobalState, mut edits: Vec<FileEdit>) -> HashMap<String, String> {
+    let analysis = global_state.analysis();
+    let mut files = HashMap::new();
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.span));
+    for edit in edits {
+        let text = files
+            .entry(edit.fpath.clone())
+            .or_insert_with(|| analysis.db().available_files[&edit.fpath].to_string());
+        text.replace_range(edit.span.0..edit.span.1, &edit.new_text);
+    }
+    files
+}
+
+#[test]
+fn test_find_references_of_module_function() {
+    let _pool = ConstPool::new();
+
+    let global_state = global_state(WALLET, SCRIPT);
+    let offset = SCRIPT.find("deposit(20)").unwrap();
+    let search_result = global_state
+        .analysis()
+        .find_all_references(&script_path(), offset)
+        .unwrap();
+
+    let declaration = search_result.declaration.unwrap();
+    assert_eq!(declaration.fpath, wallet_path());
+    assert_eq!(&WALLET[declaration.span.0..declaration.span.1], "deposit");
+
+    let mut references: Vec<(String, usize)> = search_result
+        .references
+        .iter()
+        .map(|reference| (reference.fpath.clone(), reference.span.0))
+        .collect();
+    references.sort();
+    let mut expected = vec![
+        (wallet_path(), WALLET.find("spec fun deposit").unwrap() + 9),
+        (script_path(), SCRIPT.find("{deposit}").unwrap() + 1),
+        (script_path(), SCRIPT.find("Wallet::deposit").unwrap() + 8),
+        (script_path(), SCRIPT.find("deposit(20)").unwrap()),
+    ];
+    expected.sort();
+    assert_eq!(references, expected);
+}
+
+#[test]
+fn test_rename_function_updates_all_call_sites() {
+    let _pool = ConstPool::new();
+
+    let global_state = global_state(WALLET, SCRIPT);
+    let offset = SCRIPT.find("Wallet::deposit").unwrap() + 10;
+    let edits = global_state
+        .analysis()
+        .rename(&script_path(), offset, "mint")
+        .unwrap();
+    let files = apply_edits(&global_state, edits);
+
+    assert_eq!(files[&wallet_path()], WALLET.replace("deposit", "mint"));
+    assert_eq!(files[&script_path()], SCRIPT.replace("deposit", "mint"));
+}
+
+#[test]
+fn test_rename_field_expands_shorthand() {
+    let _pool = ConstPool::new();
+
+    let global_state = global_state(WALLET, SCRIPT);
+    let offset = WALLET.find("coin.value").unwrap() + 6;
+    let edits = global_state
+        .analysis()
+        .rename(&wallet_path(), offset, "amount")
+        .unwrap();
+    let files = apply_edits(&global_state, edits);
+
+    let expected = WALLET
+        .replace("Coin { value: u64 }", "Coin { amount: u64 }")
+        .replace("Coin { value }", "Coin { amount: value }")
+        .replace("coin.value", "coin.amount");
+    assert_eq!(files[&wallet_path()], expected);
+    assert!(!files.contains_key(&script_path()));
+}
+
+#[test]
+fn test_rename_local() {
+    let _pool = ConstPool::new();
+
+    let global_state = global_state(WALLET, SCRIPT);
+    let offset = WALLET.find("value: u64): Coin").unwrap();
+    let edits = global_state
+        .analysis()
+        .rename(&wallet_path(), offset, "amount")
+        .unwrap();
+    let files = apply_edits(&global_state, edits);
+
+    let expected = WALLET
+        .replace("deposit(value: u64)", "deposit(amount: u64)")
+        .replace("Coin { value }", "Coin { value: amount }");
+    assert_eq!(files[&wallet_path()], expected);
+}
+
+#[test]
+fn test_rename_module_keeps_aliases() {
+    let _pool = ConstPool::new();
+
+    let script = r"
+script {
+    use 0x2::Wallet as W;
+
+    fun main() {
+        W::deposit(1);
+    }
+}
+";
+    let global_state = global_state(WALLET, script);
+    let offset = WALLET.find("Wallet").unwrap();
+    let edits = global_state
+        .analysis()
+        .rename(&wallet_path(), offset, "Bank")
+        .unwrap();
+    let files = apply_edits(&global_state, edits);
+
+    assert_eq!(files[&wallet_path()], WALLET.replace("Wallet", "Bank"));
+    assert_eq!(
+        files[&script_path()],
+        script.replace("0x2::Wallet", "0x2::Bank")
+    );
+}
+
+#[test]
+fn test_rename_is_refused_for_builtins_and_dependencies() {
+    let _pool = ConstPool::new();
+
+    let script = r"
+script {
+    use 0x1::Signer;
+
+    fun main(s: &signer) {
+        let _ = Signer::address_of(s);
+        let _ = borrow_global<u64>(0x1);
+    }
+}
+";
+    let global_state = global_state(WALLET, script);
+    let analysis = global_state.analysis();
+
+    let offset = script.find("address_of").unwrap();
+    let error = analysis.prepare_rename(&script_path(), offset).unwrap_err();
+    assert!(error.0.contains("read-only"), "{}", error);
+
+    let offset = script.find("borrow_global").unwrap();
+    let error = analysis.prepare_rename(&script_path(), offset).unwrap_err();
+    assert!(error.0.contains("Builtin"), "{}", error);
+
+    let offset = WALLET.find("deposit").unwrap();
+    let span = analysis.prepare_rename(&wallet_path(), offset).unwrap();
+    assert_eq!(&WALLET[span.0..span.1], "deposit");
+    assert!(analysis.rename(&wallet_path(), offset, "fun").is_err());
+}
@@ -0,0 +1,179 @@
+use lang::compiler::ConstPool;
+use move_executor::explain::{ResourceChange, StepExecutionResult};
+use lsp_types::Url;
+use move_language_server::global_state::GlobalState;
+use move_language_server::handlers::handle_execute_script;
+use move_language_server::req::ExecuteScriptParams;
+use resources::resources_dir;
+
+mod common;
+
+const SCRIPTS: &str = r"
+script {
+    use 0x2::Record;
+
+    fun create(s: &signer) {
+        Record::create_record(s, 10);
+    }
+}
+
+script {
+    use 0x2::Record;
+
+    fun increment(s: &signer) {
+        Record::increment_record(s);
+    }
+}
+";
+
+fn scripts_path() -> String {
+    resources_dir()
+        .join("scripts")
+        .join("record.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn test_path() -> String {
+    resources_dir()
+        .join("tests")
+        .join("record.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn global_state(fpath: String, text: &str) -> GlobalState {
+    let mut config = common::config("libra");
+    config.sender_address = config.dialect().normalize_account_address("0x3").unwrap();
+    config.tests_folder = Some(resources_dir().join("tests"));
+    common::global_state_with_config(config, &[(fpath, text)])
+}
+
+#[test]
+fn test_every_script_is_runnable() {
+    let _pool = ConstPool::new();
+
+    let runnables = global_state(scripts_path(), SCRIPTS)
+        .analysis()
+        .runnables(&scripts_path());
+    let scripts: Vec<_> = runnables.iter().map(|runnable| runnable.script).collect();
+    assert_eq!(scripts, vec![Some(0), Some(1)]);
+    assert!(SCRIPTS[runnables[1].span.0..]
+        .starts_with("script {\n    use 0x2::Record;\n\n    fun increment"));
+}
+
+#[test]
+fn test_test_file_is_runnable_as_a_whole() {
+    let _pool = ConstPool::new();
+
+    let runnables = global_state(test_path(), SCRIPTS)
+        .analysis()
+        .runnables(&test_path());
+    assert_eq!(runnables.len(), 1);
+    assert_eq!(runnables[0].script, None);
+}
+
+#[test]
+fn test_run_single_script() {
+    let _pool = ConstPool::new();
+
+    let global_state = global_state(scripts_path(), SCRIPTS);
+    let result = global_state
+        .analysis()
+        .run(&scripts_path(), Some(0), vec![], &[])
+        .unwrap();
+    assert_eq!(result.step_results.len(), 1);
+    let effects = result.last().unwrap().effects();
+    let ResourceChange(ty, _) = &effects.resources()[0].changes[0].1;
+    assert_eq!(ty, "0x2::Record::T");
+
+    // the record is not created without the first script
+    let result = global_state
+        .analysis()
+        .run(&scripts_path(), Some(1), vec![], &[])
+        .unwrap();
+    assert!(matches!(
+        result.last().unwrap(),
+        StepExecutionResult::Error(_)
+    ));
+}
+
+#[test]
+fn test_run_test_file() {
+    let _pool = ConstPool::new();
+
+    let global_state = global_state(test_path(), SCRIPTS);
+    let result = global_state
+        .analysis()
+        .run(&test_path(), None, vec![], &[])
+        .unwrap();
+    assert_eq!(result.step_results.len(), 2);
+    assert!(matches!(
+        result.last().unwrap(),
+        StepExecutionResult::Success(_)
+    ));
+
+    // changes of the first script are not applied in the dry run
+    let result = global_state
+        .analysis()
+        .run(&test_path(), None, vec![], &["dry_run: true".to_string()])
+        .unwrap();
+    assert!(matches!(
+        result.last().unwrap(),
+        StepExecutionResult::Error(_)
+    ));
+}
+
+#[test]
+fn test_compilation_errors_are_reported() {
+    let _pool = ConstPool::new();
+
+    let text = SCRIPTS.replace("create_record(s, 10)", "create_record(s)");
+    let error = global_state(scripts_path(), &text)
+        .analysis()
+        .run(&scripts_path(), Some(0), vec![], &[])
+        .unwrap_err();
+    assert!(error.to_string().contains("error"), "{}", error);
+}
+
+#[test]
+fn test_execute_script_request() {
+    let _pool = ConstPool::new();
+
+    let global_state = global_state(test_path(), SCRIPTS);
+    let params = ExecuteScriptParams {
+        uri: Url::from_file_path(test_path()).unwrap(),
+        script: None,
+        args: vec![],
+        meta: vec!["signers: 0x4".to_string()],
+    };
+    let result = handle_execute_script(global_state.snapshot(), params).unwrap();
+    assert!(result.error.is_none());
+    let steps = serde_json::to_value(&result.steps).unwrap();
+    assert_eq!(steps[0]["name"], "create");
+    assert_eq!(steps[0]["status"], "success");
+    assert_eq!(steps[0]["resources"][0]["address"], "0x4");
+    assert!(steps[0]["gas"].as_u64().unwrap() > 0);
+    assert!(steps[0]["writeSetSize"].as_u64().unwrap() > 0);
+    assert!(steps[0].get("write_set_size").is_none());
+    assert_eq!(steps[1]["status"], "success");
+}
+
+#[test]
+fn test_execute_script_request_with_abort() {
+    let _pool = ConstPool::new();
+
+    let text = SCRIPTS.replace("increment_record", "destroy_record");
+    let global_state = global_state(scripts_path(), &text);
+    let params = ExecuteScriptParams {
+        uri: Url::from_file_path(scripts_path()).unwrap(),
+        script: Some(1),
+        args: vec![],
+        meta: vec![],
+    };
+    let result = handle_execute_script(global_state.snapshot(), params).unwrap();
+    let step = serde_json::to_value(&result.steps[0]).unwrap();
+    assert_eq!(step["status"], "error");
+    assert_eq!(step["abort"]["code"], 101);
+    assert_eq!(step["abort"]["constant"], "ERR_RECORD_DOES_NOT_EXIST");
+}
@@ -0,0 +1,70 @@
+use lang::compiler::file::MoveFile;
+use lang::compiler::ConstPool;
+use move_language_server::global_state::initialize_new_global_state;
+use move_language_server::inner::config::Config;
+use move_language_server::main_loop::FileSystemEvent;
+use resources::resources_dir;
+
+fn script_path() -> String {
+    resources_dir()
+        .join("script.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn selections(source: &str, offset: usize) -> Option<Vec<String>> {
+    let mut global_state = initialize_new_global_state(Config::default());
+    global_state.update_from_events(vec![FileSystemEvent::AddFile(MoveFile::with_content(
+        script_path(),
+        source.to_owned(),
+    ))]);
+    let spans = global_state
+        .analysis()
+        .selection_ranges(&script_path(), offset)?;
+    Some(
+        spans
+            .into_iter()
+            .map(|(start, end)| source[start..end].to_owned())
+            .collect(),
+    )
+}
+
+#[test]
+fn test_selection_walks_up_the_expression_tree() {
+    let _pool = ConstPool::new();
+
+    let source = r"script {
+    fun main() {
+        let a = 1;
+        let b = (a + 2) * 3;
+    }
+}";
+    let offset = source.find("a + 2").unwrap();
+    let selections = selections(source, offset).unwrap();
+    assert_eq!(selections[0], "a");
+    assert!(
+        selections
+            .iter()
+            .any(|selection| selection.trim_matches(|ch| ch == '(' || ch == ')') == "a + 2"),
+        "{:#?}",
+        selections
+    );
+    assert!(selections
+        .iter()
+        .any(|selection| selection.starts_with("let b = (a + 2) * 3")));
+    assert_eq!(selections.last().unwrap(), source);
+
+    // every selection contains the previous one
+    for pair in selections.windows(2) {
+        assert!(pair[1].contains(pair[0].as_str()), "{:#?}", selections);
+        assert_ne!(pair[0], pair[1]);
+    }
+}
+
+#[test]
+fn test_no_selection_ranges_for_syntax_errors() {
+    let _pool = ConstPool::new();
+
+    let source = r"script { fun main() { let a = ; } }";
+    assert!(selections(source, source.find('a').unwrap()).is_none());
+}
@@ -0,0 +1,190 @@
+use lang::compiler::ConstPool;
+use lsp_types::{SemanticTokenModifier, SemanticTokenType};
+use move_language_server::global_state::GlobalState;
+use move_language_server::inner::highlighting::{HighlightKind, HighlightedRange};
+use move_language_server::semantic_tokens::{semantic_tokens_legend, to_semantic_tokens, RESOURCE};
+use resources::modules_path;
+
+mod common;
+
+const WALLET: &str = r"
+address 0x2 {
+module Wallet {
+    // 0x3 is not an address here
+    resource struct Coin<Token> { value: u64 }
+    struct Info { owner: address }
+
+    const MAX: u64 = 100;
+
+    public fun deposit<Token>(coin: Coin<Token>, amount: u64): u64 {
+        let Coin { value } = coin;
+        value + amount + MAX
+    }
+}
+}
+";
+
+fn wallet_path() -> String {
+    modules_path()
+        .join("wallet.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn global_state(dialect: &str, text: &str) -> GlobalState {
+    common::global_state(dialect, &[(wallet_path(), text)])
+}
+
+fn highlight(dialect: &str, text: &str) -> Vec<HighlightedRange> {
+    global_state(dialect, text)
+        .analysis()
+        .highlight(&wallet_path(), None)
+}
+
+/// Kind and declaration flag of every range with the `text`.
+fn kinds_of(source: &str, ranges: &[HighlightedRange], text: &str) -> Vec<(HighlightKind, bool)> {
+    ranges
+        .iter()
+        .filter(|range| &source[range.span.0..range.span.1] == text)
+        .map(|range| (range.kind, range.is_declaration))
+        .collect()
+}
+
+#[test]
+fn test_highlight_module_items() {
+    let _pool = ConstPool::new();
+
+    let ranges = highlight("libra", WALLET);
+    assert_eq!(
+        kinds_of(WALLET, &ranges, "Wallet"),
+        vec![(HighlightKind::Module, true)]
+    );
+    assert_eq!(
+        kinds_of(WALLET, &ranges, "Coin"),
+        vec![
+            (HighlightKind::Resource, true),
+            (HighlightKind::Resource, false),
+            (HighlightKind::Resource, false)
+        ]
+    );
+    assert_eq!(
+        kinds_of(WALLET, &ranges, "Info"),
+        vec![(HighlightKind::Struct, true)]
+    );
+    assert_eq!(
+        kinds_of(WALLET, &ranges, "MAX"),
+        vec![
+            (HighlightKind::Constant, true),
+            (HighlightKind::Constant, false)
+        ]
+    );
+    assert_eq!(
+        kinds_of(WALLET, &ranges, "deposit"),
+        vec![(HighlightKind::Function, true)]
+    );
+    assert_eq!(
+        kinds_of(WALLET, &ranges, "amount"),
+        vec![(HighlightKind::Local, true), (HighlightKind::Local, false)]
+    );
+    assert!(kinds_of(WALLET, &ranges, "Token")
+        .iter()
+        .all(|(kind, _)| *kind == HighlightKind::TypeParameter));
+    assert_eq!(kinds_of(WALLET, &ranges, "Token").len(), 3);
+}
+
+#[test]
+fn test_highlight_address_literals_outside_of_comments() {
+    let _pool = ConstPool::new();
+
+    let ranges = highlight("libra", WALLET);
+    let addresses: Vec<_> = ranges
+        .iter()
+        .filter(|range| range.kind == HighlightKind::Address)
+        .map(|range| &WALLET[range.span.0..range.span.1])
+        .collect();
+    assert_eq!(addresses, vec!["0x2"]);
+}
+
+#[test]
+fn test_highlight_dialect_address_literals() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+script {
+    use wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh::Wallet;
+
+    fun main() {
+        let _ = {{sender}};
+    }
+}
+";
+    let ranges = highlight("dfinance", source);
+    let addresses: Vec<_> = ranges
+        .iter()
+        .filter(|range| range.kind == HighlightKind::Address)
+        .map(|range| &source[range.span.0..range.span.1])
+        .collect();
+    assert_eq!(
+        addresses,
+        vec![
+            "wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh",
+            "{{sender}}"
+        ]
+    );
+}
+
+#[test]
+fn test_highlight_range() {
+    let _pool = ConstPool::new();
+
+    let start = WALLET.find("const").unwrap();
+    let end = WALLET.find("public").unwrap();
+    let ranges = global_state("libra", WALLET)
+        .analysis()
+        .highlight(&wallet_path(), Some((start, end)));
+    assert_eq!(ranges.len(), 1);
+    assert_eq!(&WALLET[ranges[0].span.0..ranges[0].span.1], "MAX");
+}
+
+#[test]
+fn test_semantic_tokens_encoding() {
+    let source = "module M {\n    resource struct R {}\n}";
+    let ranges = vec![
+        HighlightedRange {
+            span: (7, 8),
+            kind: HighlightKind::Module,
+            is_declaration: true,
+        },
+        HighlightedRange {
+            span: (31, 32),
+            kind: HighlightKind::Resource,
+            is_declaration: true,
+        },
+    ];
+    let tokens = to_semantic_tokens(source, &ranges);
+    let legend = semantic_tokens_legend();
+    let type_index = |ty: SemanticTokenType| {
+        legend.token_types.iter().position(|it| *it == ty).unwrap() as u32
+    };
+    let modifier_bit = |modifier: SemanticTokenModifier| {
+        1 << legend
+            .token_modifiers
+            .iter()
+            .position(|it| *it == modifier)
+            .unwrap()
+    };
+
+    assert_eq!(tokens.len(), 2);
+    assert_eq!((tokens[0].delta_line, tokens[0].delta_start), (0, 7));
+    assert_eq!(
+        tokens[0].token_type,
+        type_index(SemanticTokenType::NAMESPACE)
+    );
+    assert_eq!((tokens[1].delta_line, tokens[1].delta_start), (1, 20));
+    assert_eq!(tokens[1].length, 1);
+    assert_eq!(tokens[1].token_type, type_index(SemanticTokenType::STRUCT));
+    assert_eq!(
+        tokens[1].token_modifiers_bitset,
+        modifier_bit(SemanticTokenModifier::DECLARATION) | modifier_bit(RESOURCE)
+    );
+}
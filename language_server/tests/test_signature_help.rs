@@ -0,0 +1,155 @@
+use lang::compiler::ConstPool;
+use move_language_server::global_state::GlobalState;
+use move_language_server::inner::signature_help::SignatureHelpResult;
+use resources::{modules_path, resources_dir};
+
+mod common;
+
+const WALLET: &str = r"
+address 0x2 {
+module Wallet {
+    resource struct Coin<Token> { value: u64 }
+
+    public fun transfer<Token: copyable, Fee: resource>(from: &signer, to: address, amount: u64) {
+        abort 1
+    }
+
+    fun fee(amount: u64): u64 {
+        amount / 100
+    }
+
+    public fun total(amount: u64): u64 {
+        amount + fee(amount)
+    }
+}
+}
+";
+
+fn wallet_path() -> String {
+    modules_path()
+        .join("wallet.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn script_path() -> String {
+    resources_dir()
+        .join("script.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn global_state(script: &str) -> GlobalState {
+    common::global_state("libra", &[(wallet_path(), WALLET), (script_path(), script)])
+}
+
+/// Signature help at the `|` marker in the script.
+fn signature_help(script: &str) -> Option<SignatureHelpResult> {
+    let offset = script.find('|').unwrap();
+    let script = script.replace('|', "");
+    global_state(&script)
+        .analysis()
+        .signature_help(&script_path(), offset)
+}
+
+fn parameters(signature: &SignatureHelpResult) -> Vec<&str> {
+    signature
+        .parameters
+        .iter()
+        .map(|(start, end)| &signature.label[*start..*end])
+        .collect()
+}
+
+#[test]
+fn test_signature_help_for_incomplete_call() {
+    let _pool = ConstPool::new();
+
+    let script = r"
+script {
+    use 0x2::Wallet;
+
+    fun main(account: &signer) {
+        Wallet::transfer<u8, u64>(account, 0x3, |
+    }
+}
+";
+    let signature = signature_help(script).unwrap();
+    assert_eq!(
+        signature.label,
+        "public fun transfer<Token: copyable, Fee: resource>(from: &signer, to: address, amount: u64)"
+    );
+    assert_eq!(
+        parameters(&signature),
+        vec!["from: &signer", "to: address", "amount: u64"]
+    );
+    assert_eq!(signature.active_parameter, Some(2));
+}
+
+#[test]
+fn test_signature_help_for_type_arguments() {
+    let _pool = ConstPool::new();
+
+    let script = r"
+script {
+    use 0x2::Wallet::{transfer as send};
+
+    fun main(account: &signer) {
+        send<vector<u8>, |u64>(account, 0x3, 10);
+    }
+}
+";
+    let signature = signature_help(script).unwrap();
+    assert_eq!(
+        parameters(&signature),
+        vec!["Token: copyable", "Fee: resource"]
+    );
+    assert_eq!(signature.active_parameter, Some(1));
+}
+
+#[test]
+fn test_signature_help_for_native_stdlib_function() {
+    let _pool = ConstPool::new();
+
+    let script = r"
+script {
+    use 0x1::Event;
+
+    fun main(account: &signer) {
+        Event::emit<u64>(account, (1 + 2) * |3);
+    }
+}
+";
+    let signature = signature_help(script).unwrap();
+    assert!(signature.label.contains("native"), "{}", signature.label);
+    assert_eq!(parameters(&signature), vec!["account: &signer", "msg: T"]);
+    assert_eq!(signature.active_parameter, Some(1));
+}
+
+#[test]
+fn test_signature_help_inside_module() {
+    let _pool = ConstPool::new();
+
+    let offset = WALLET.find("fee(amount)").unwrap() + 4;
+    let signature = global_state("script { fun main() {} }")
+        .analysis()
+        .signature_help(&wallet_path(), offset)
+        .unwrap();
+    assert_eq!(signature.label, "fun fee(amount: u64): u64");
+    assert_eq!(signature.active_parameter, Some(0));
+}
+
+#[test]
+fn test_no_signature_help_outside_of_calls() {
+    let _pool = ConstPool::new();
+
+    let script = r"
+script {
+    fun main() {
+        let a = 1;
+        if (a < |2) {};
+    }
+}
+";
+    assert!(signature_help(script).is_none());
+    assert!(signature_help("script { fun main() { let a = |1; } }").is_none());
+}
@@ -0,0 +1,78 @@
+use lsp_types::{Position, Range, TextDocumentContentChangeEvent};
+use move_language_server::inner::change::apply_text_changes;
+
+fn change(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+    TextDocumentContentChangeEvent {
+        range: Some(Range::new(
+            Position::new(start.0, start.1),
+            Position::new(end.0, end.1),
+        )),
+        range_length: None,
+        text: text.to_string(),
+    }
+}
+
+fn apply(text: &str, changes: Vec<TextDocumentContentChangeEvent>) -> String {
+    let mut text = text.to_string();
+    apply_text_changes(&mut text, &changes).unwrap();
+    text
+}
+
+#[test]
+fn test_insert_into_the_line() {
+    let text = apply(
+        "script {\n    fun main() {}\n}",
+        vec![change((1, 13), (1, 13), "s: &signer")],
+    );
+    assert_eq!(text, "script {\n    fun main(s: &signer) {}\n}");
+}
+
+#[test]
+fn test_each_change_is_applied_to_the_result_of_the_previous_one() {
+    let text = apply(
+        "let a = 1;\nlet b = 2;",
+        vec![
+            change((0, 4), (0, 5), "first"),
+            change((1, 0), (1, 10), ""),
+            change((0, 14), (1, 0), ""),
+        ],
+    );
+    assert_eq!(text, "let first = 1;");
+}
+
+#[test]
+fn test_multiline_replacement() {
+    let text = apply(
+        "script {\n    fun main() {\n    }\n}",
+        vec![change((1, 16), (2, 4), "\n        assert(true, 1);\n    ")],
+    );
+    assert_eq!(
+        text,
+        "script {\n    fun main() {\n        assert(true, 1);\n    }\n}"
+    );
+}
+
+#[test]
+fn test_non_ascii_characters_before_the_change() {
+    let text = apply("// привет\nlet a;", vec![change((0, 3), (0, 9), "hi")]);
+    assert_eq!(text, "// hi\nlet a;");
+}
+
+#[test]
+fn test_change_without_range_replaces_whole_text() {
+    let text = apply(
+        "let a;",
+        vec![TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "let b;".to_string(),
+        }],
+    );
+    assert_eq!(text, "let b;");
+}
+
+#[test]
+fn test_change_out_of_bounds_is_an_error() {
+    let mut text = "let a;".to_string();
+    assert!(apply_text_changes(&mut text, &[change((5, 0), (5, 1), "")]).is_err());
+}
@@ -0,0 +1,143 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use lang::compiler::dialects::DialectName;
+use lang::compiler::file::MoveFile;
+use lang::compiler::ConstPool;
+use lsp_types::{ClientCapabilities, InitializeParams, Url, WorkspaceFolder};
+use move_language_server::global_state::{load_global_state, GlobalState};
+use move_language_server::inner::config::Config;
+use move_language_server::main_loop::FileSystemEvent;
+use move_language_server::server::parse_workspace_configs;
+
+const BECH32_MODULE: &str = r"
+address wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh {
+    module Debug {
+        public fun main() {
+            let _ = wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh;
+        }
+    }
+}
+";
+
+fn manifest(dialect: &str) -> String {
+    format!("[package]\nname = \"Project\"\ndialect = \"{}\"\n", dialect)
+}
+
+/// Dove project of the dialect in the temporary directory.
+fn dove_project(name: &str, dialect: &str) -> PathBuf {
+    let project_dir = std::env::temp_dir().join(format!("move-language-server-folders-{}", name));
+    let _ = fs::remove_dir_all(&project_dir);
+    fs::create_dir_all(project_dir.join("modules")).unwrap();
+    fs::create_dir_all(project_dir.join("target").join(".external")).unwrap();
+    fs::write(project_dir.join("Dove.toml"), manifest(dialect)).unwrap();
+    project_dir
+}
+
+fn config(project_dir: &Path) -> Config {
+    let mut config = Config::default();
+    config.root_path = Some(project_dir.to_owned());
+    config.update(&serde_json::json!({}));
+    config
+}
+
+fn module_path(project_dir: &Path) -> String {
+    project_dir
+        .join("modules")
+        .join("debug.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Two folders of the different dialects with the same module added to both.
+fn global_state(name: &str) -> (GlobalState, String, String) {
+    let libra_dir = dove_project(&format!("{}-libra", name), "libra");
+    let dfinance_dir = dove_project(&format!("{}-dfinance", name), "dfinance");
+    let mut global_state =
+        load_global_state(vec![config(&libra_dir), config(&dfinance_dir)], |_, _| {});
+
+    let libra_path = module_path(&libra_dir);
+    let dfinance_path = module_path(&dfinance_dir);
+    global_state.update_from_events(vec![
+        FileSystemEvent::AddFile(MoveFile::with_content(libra_path.clone(), BECH32_MODULE)),
+        FileSystemEvent::AddFile(MoveFile::with_content(dfinance_path.clone(), BECH32_MODULE)),
+    ]);
+    (global_state, libra_path, dfinance_path)
+}
+
+#[test]
+fn test_every_workspace_folder_has_its_own_config() {
+    let libra_dir = dove_project("initialization-libra", "libra");
+    let dfinance_dir = dove_project("initialization-dfinance", "dfinance");
+    let folder = |dir: &PathBuf| WorkspaceFolder {
+        uri: Url::from_directory_path(dir).unwrap(),
+        name: dir.file_name().unwrap().to_string_lossy().to_string(),
+    };
+    #[allow(deprecated)]
+    let params = InitializeParams {
+        process_id: None,
+        root_path: None,
+        root_uri: Some(Url::from_file_path(&libra_dir).unwrap()),
+        initialization_options: None,
+        capabilities: ClientCapabilities::default(),
+        trace: None,
+        workspace_folders: Some(vec![folder(&libra_dir), folder(&dfinance_dir)]),
+        client_info: None,
+        locale: None,
+    };
+    let configs = parse_workspace_configs(serde_json::to_value(params).unwrap()).unwrap();
+
+    assert_eq!(configs.len(), 2);
+    assert_eq!(configs[0].root_path, Some(libra_dir));
+    assert_eq!(configs[0].dialect_name, DialectName::Libra);
+    assert_eq!(configs[1].root_path, Some(dfinance_dir));
+    assert_eq!(configs[1].dialect_name, DialectName::DFinance);
+}
+
+#[test]
+fn test_files_are_tracked_by_their_workspace_folder() {
+    let _pool = ConstPool::new();
+
+    let (global_state, libra_path, dfinance_path) = global_state("tracking");
+    let libra_analysis = global_state.analysis_for(&libra_path);
+    let dfinance_analysis = global_state.analysis_for(&dfinance_path);
+
+    assert_eq!(libra_analysis.db().config.dialect_name, DialectName::Libra);
+    assert!(libra_analysis
+        .db()
+        .available_files
+        .contains_key(&libra_path));
+    assert!(!libra_analysis
+        .db()
+        .available_files
+        .contains_key(&dfinance_path));
+
+    assert_eq!(
+        dfinance_analysis.db().config.dialect_name,
+        DialectName::DFinance
+    );
+    assert!(dfinance_analysis
+        .db()
+        .available_files
+        .contains_key(&dfinance_path));
+}
+
+#[test]
+fn test_files_are_checked_with_dialect_of_their_workspace_folder() {
+    let _pool = ConstPool::new();
+
+    let (mut global_state, libra_path, dfinance_path) = global_state("checking");
+    let affected_files = global_state.take_affected_files();
+    let groups = global_state.group_by_workspace(affected_files);
+    assert_eq!(groups.len(), 2);
+
+    let errors = global_state
+        .analysis_for(&dfinance_path)
+        .check_file(MoveFile::with_content(dfinance_path.clone(), BECH32_MODULE));
+    assert!(errors.is_empty(), "{:?}", errors);
+
+    let errors = global_state
+        .analysis_for(&libra_path)
+        .check_file(MoveFile::with_content(libra_path.clone(), BECH32_MODULE));
+    assert!(!errors.is_empty());
+}
@@ -0,0 +1,233 @@
+use std::path::{Path, PathBuf};
+
+use lang::compiler::file::MoveFile;
+use lang::compiler::ConstPool;
+use move_language_server::global_state::{
+    initialize_new_global_state, load_global_state, GlobalState,
+};
+use move_language_server::inner::config::Config;
+use move_language_server::inner::symbol_index::IndexedSymbol;
+use move_language_server::inner::symbols::ItemKind;
+use move_language_server::main_loop::FileSystemEvent;
+use resources::{modules_path, resources_dir, stdlib_path};
+
+fn module_path() -> String {
+    modules_path()
+        .join("wallet.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+fn config() -> Config {
+    let mut config = Config::default();
+    config.update(&serde_json::json!({
+        "stdlib_folder": stdlib_path(),
+        "modules_folders": [modules_path()],
+    }));
+    config
+}
+
+fn qualified_names(symbols: &[IndexedSymbol]) -> Vec<String> {
+    symbols
+        .iter()
+        .map(|symbol| symbol.qualified_name())
+        .collect()
+}
+
+fn search(global_state: &GlobalState, query: &str) -> Vec<String> {
+    qualified_names(&global_state.analysis().workspace_symbols(query, 128))
+}
+
+#[test]
+fn test_search_stdlib_function() {
+    let _pool = ConstPool::new();
+
+    let global_state = initialize_new_global_state(config());
+    let symbols = global_state.analysis().workspace_symbols("address_of", 128);
+    let symbol = &symbols[0];
+    assert_eq!(symbol.qualified_name(), "0x1::Signer::address_of");
+    assert_eq!(symbol.kind, ItemKind::Function);
+    assert!(symbol.fpath.ends_with("signer.move"), "{}", symbol.fpath);
+}
+
+#[test]
+fn test_fuzzy_search_puts_best_matches_first() {
+    let _pool = ConstPool::new();
+
+    let global_state = initialize_new_global_state(config());
+    let names = search(&global_state, "addrof");
+    assert_eq!(names[0], "0x1::Signer::address_of");
+
+    let names = search(&global_state, "signer");
+    assert_eq!(names[0], "0x1::Signer");
+}
+
+#[test]
+fn test_search_by_qualified_name() {
+    let _pool = ConstPool::new();
+
+    let global_state = initialize_new_global_state(config());
+    assert_eq!(
+        search(&global_state, "0x1::Signer::borrow"),
+        vec!["0x1::Signer::borrow_address"]
+    );
+    assert_eq!(
+        search(&global_state, "Signer::borrow"),
+        vec!["0x1::Signer::borrow_address"]
+    );
+    assert!(search(&global_state, "0x2::Signer::borrow").is_empty());
+}
+
+#[test]
+fn test_bech32_address_in_dfinance_dialect() {
+    let _pool = ConstPool::new();
+
+    let source = r"
+address wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh {
+    module Bank {
+        struct Account { owner: address }
+    }
+}
+";
+    let mut config = Config::default();
+    config.update(&serde_json::json!({"dialect": "dfinance"}));
+    let global_state = GlobalState::new(
+        config,
+        vec![FileSystemEvent::AddFile(MoveFile::with_content(
+            module_path(),
+            source.to_owned(),
+        ))],
+    );
+    let symbols = global_state.analysis().workspace_symbols("Account", 128);
+    assert_eq!(
+        symbols[0].container,
+        "wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh::Bank"
+    );
+    assert_eq!(
+        &source[symbols[0].name_span.0..symbols[0].name_span.1],
+        "Account"
+    );
+    assert_eq!(
+        search(
+            &global_state,
+            "wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh::Bank::"
+        ),
+        vec!["wallet1me0cdn52672y7feddy7tgcj6j4dkzq2su745vh::Bank::Account"]
+    );
+}
+
+#[test]
+fn test_index_is_updated_on_file_changes() {
+    let _pool = ConstPool::new();
+
+    let mut global_state = GlobalState::new(
+        Config::default(),
+        vec![FileSystemEvent::AddFile(MoveFile::with_content(
+            module_path(),
+            "address 0x2 { module Wallet { public fun deposit() {} } }".to_owned(),
+        ))],
+    );
+    assert_eq!(
+        search(&global_state, "deposit"),
+        vec!["0x2::Wallet::deposit"]
+    );
+
+    global_state.update_from_events(vec![FileSystemEvent::ChangeFile(MoveFile::with_content(
+        module_path(),
+        "address 0x2 { module Wallet { public fun withdraw() {} } }".to_owned(),
+    ))]);
+    assert!(search(&global_state, "deposit").is_empty());
+    assert_eq!(
+        search(&global_state, "withdraw"),
+        vec!["0x2::Wallet::withdraw"]
+    );
+
+    // symbols of the last parsed version are kept while the file is broken
+    global_state.update_from_events(vec![FileSystemEvent::ChangeFile(MoveFile::with_content(
+        module_path(),
+        "address 0x2 { module Wallet { public fun withdraw(".to_owned(),
+    ))]);
+    assert_eq!(
+        search(&global_state, "withdraw"),
+        vec!["0x2::Wallet::withdraw"]
+    );
+
+    global_state.update_from_events(vec![FileSystemEvent::RemoveFile(module_path())]);
+    assert!(search(&global_state, "withdraw").is_empty());
+}
+
+#[test]
+fn test_scripts_are_not_indexed() {
+    let _pool = ConstPool::new();
+
+    let script_path = resources_dir()
+        .join("script.move")
+        .to_string_lossy()
+        .to_string();
+    let global_state = GlobalState::new(
+        Config::default(),
+        vec![FileSystemEvent::AddFile(MoveFile::with_content(
+            script_path,
+            "script { fun main() {} }".to_owned(),
+        ))],
+    );
+    assert!(search(&global_state, "main").is_empty());
+}
+
+fn folder_config(root: &Path) -> Config {
+    let mut config = Config::default();
+    config.root_path = Some(root.to_owned());
+    config.update(&serde_json::json!({
+        "stdlib_folder": stdlib_path(),
+    }));
+    config
+}
+
+fn folder_module_path(root: &Path) -> String {
+    root.join("modules")
+        .join("wallet.move")
+        .to_string_lossy()
+        .to_string()
+}
+
+#[test]
+fn test_search_in_every_workspace_folder() {
+    let _pool = ConstPool::new();
+
+    let root = |name: &str| -> PathBuf {
+        std::env::temp_dir().join(format!("move-language-server-symbols-{}", name))
+    };
+    let (first, second) = (root("first"), root("second"));
+    let mut global_state = load_global_state(
+        vec![folder_config(&first), folder_config(&second)],
+        |_, _| {},
+    );
+    global_state.update_from_events(vec![
+        FileSystemEvent::AddFile(MoveFile::with_content(
+            folder_module_path(&first),
+            "address 0x2 { module Wallet { public fun deposit() {} } }",
+        )),
+        FileSystemEvent::AddFile(MoveFile::with_content(
+            folder_module_path(&second),
+            "address 0x3 { module Bank { public fun deposit() {} } }",
+        )),
+    ]);
+
+    let snapshot = global_state.snapshot();
+    let symbols = snapshot.workspace_symbols("deposit", 128);
+    assert_eq!(
+        qualified_names(&symbols),
+        vec!["0x2::Wallet::deposit", "0x3::Bank::deposit"]
+    );
+    assert_eq!(symbols[1].fpath, folder_module_path(&second));
+    assert_eq!(
+        qualified_names(&snapshot.workspace_symbols("deposit", 1)),
+        vec!["0x2::Wallet::deposit"]
+    );
+
+    // the standard library of both folders is listed once
+    assert_eq!(
+        qualified_names(&snapshot.workspace_symbols("address_of", 128)),
+        vec!["0x1::Signer::address_of"]
+    );
+}